@@ -0,0 +1,7 @@
+//! C and Fortran library compatible with the SAC library
+//!
+//! Builds `libsacio` as a shared and a static library from the `capi`
+//! feature of the sacio crate, see `sacio::capi`.  The header is
+//! `include/sacio.h`.
+
+pub use sacio_rs::capi::*;
@@ -0,0 +1,22 @@
+// include/sacio.h must match the header generated from src/capi.rs,
+// regenerate it with
+//
+//   cbindgen --config cbindgen.toml --crate sacio --output include/sacio.h
+
+use std::path::Path;
+
+#[test]
+fn header_is_current() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+    let mut generated = vec![];
+    cbindgen::Builder::new()
+        .with_crate(dir)
+        .with_config(config)
+        .generate()
+        .expect("Generating C header")
+        .write(&mut generated);
+    let current = std::fs::read(dir.join("include/sacio.h")).unwrap();
+    assert!(generated == current,
+            "include/sacio.h is out of date with src/capi.rs, regenerate it with cbindgen");
+}
@@ -0,0 +1,205 @@
+/* SAC file reading and writing, compatible with the SAC library sacio.h */
+
+#ifndef SACIO_H
+#define SACIO_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Success
+ */
+#define SAC_OK 0
+
+/**
+ * More data in the file than the array, data is truncated
+ */
+#define SAC_TRUNCATED -803
+
+/**
+ * Error reading or writing the file, or an invalid header
+ */
+#define SAC_ERROR_IO 101
+
+/**
+ * File does not exist
+ */
+#define SAC_ERROR_NOT_FOUND 108
+
+/**
+ * File is not evenly spaced
+ */
+#define SAC_ERROR_UNEVEN 801
+
+/**
+ * Header value is undefined
+ */
+#define SAC_ERROR_UNDEFINED 1336
+
+/**
+ * Header value does not exist
+ */
+#define SAC_ERROR_BAD_NAME 1337
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Read an evenly spaced file
+ *
+ * Reads `kname` into `yarray`, up to `max_` points.  The number of
+ * points, begin time and sampling interval are returned in `nlen`, `beg`
+ * and `del`.
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes and `yarray` to `max_` floats
+ */
+void rsac1(const char *kname,
+           float *yarray,
+           int *nlen,
+           float *beg,
+           float *del,
+           const int *max_,
+           int *nerr,
+           int kname_len);
+
+/**
+ * Read an unevenly spaced or spectral file
+ *
+ * Reads `kname` into `yarray` and `xarray`, up to `max_` points.  For
+ * evenly spaced files `xarray` is filled with the time of each sample.
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes, `yarray` and `xarray` to
+ * `max_` floats
+ */
+void rsac2(const char *kname,
+           float *yarray,
+           int *nlen,
+           float *xarray,
+           const int *max_,
+           int *nerr,
+           int kname_len);
+
+/**
+ * Write a file using the header in memory
+ *
+ * The number of points is taken from `npts`.  `xarray` is written for
+ * unevenly spaced and spectral files, as the second component.
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes, `yarray` and `xarray` to
+ * `npts` floats
+ */
+void wsac0(const char *kname, const float *xarray, const float *yarray, int *nerr, int kname_len);
+
+/**
+ * Write an evenly spaced file with a new header
+ *
+ * The header in memory is replaced by a new header with `nlen`, `beg`
+ * and `del` set
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes and `yarray` to `nlen` floats
+ */
+void wsac1(const char *kname,
+           const float *yarray,
+           const int *nlen,
+           const float *beg,
+           const float *del,
+           int *nerr,
+           int kname_len);
+
+/**
+ * Replace the header in memory with a new, undefined, header
+ */
+void newhdr(void);
+
+/**
+ * Get a real header value
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes
+ */
+void getfhv(const char *kname, float *fvalue, int *nerr, int kname_len);
+
+/**
+ * Set a real header value
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes
+ */
+void setfhv(const char *kname, const float *fvalue, int *nerr, int kname_len);
+
+/**
+ * Get an integer header value, e.g. "npts" or "nzyear"
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes
+ */
+void getnhv(const char *kname, int *nvalue, int *nerr, int kname_len);
+
+/**
+ * Set an integer header value
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes
+ */
+void setnhv(const char *kname, const int *nvalue, int *nerr, int kname_len);
+
+/**
+ * Get a logical header value, e.g. "leven", as 0 or 1
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes
+ */
+void getlhv(const char *kname, int *lvalue, int *nerr, int kname_len);
+
+/**
+ * Set a logical header value, e.g. "leven", from 0 or 1
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes
+ */
+void setlhv(const char *kname, const int *lvalue, int *nerr, int kname_len);
+
+/**
+ * Get a string header value
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes and `kvalue` to `kvalue_len`
+ * bytes
+ */
+void getkhv(const char *kname, char *kvalue, int *nerr, int kname_len, int kvalue_len);
+
+/**
+ * Set a string header value
+ *
+ * # Safety
+ *
+ * `kname` must point to `kname_len` bytes and `kvalue` to `kvalue_len`
+ * bytes
+ */
+void setkhv(const char *kname, const char *kvalue, int *nerr, int kname_len, int kvalue_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SACIO_H */
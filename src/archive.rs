@@ -0,0 +1,316 @@
+/*! SAC files inside tar and zip archives
+
+Requires the `archive` feature
+
+SAC files are read directly from tar, gzip compressed tar (`.tar.gz`,
+`.tgz`) or zip archives without unpacking them to disk.  The archive
+format is detected from the magic bytes.  Each regular file in the
+archive is read with [`Sac::read`] and returned with its path inside the
+archive; directories are skipped.  Files that are not SAC files are
+returned as errors rather than ending the iteration.
+
+| Archive     | Read | Write |
+|-------------|------|-------|
+| tar         | yes  | yes   |
+| tar.gz      | yes  | yes   |
+| zip         | yes  | yes   |
+
+The filename of each trace is set to its path inside the archive.
+
+```
+use sacio::Sac;
+use sacio::archive;
+use sacio::archive::Archive;
+use sacio::archive::Format;
+# use sacio::SacError;
+
+let mut s = Sac::from_file("tests/file.sac")?;
+let mut s2 = s.clone();
+s2.set_string(sacio::SacString::Station, "PAS");
+
+archive::write("tests/archive.tar.gz", Format::TarGz,
+               vec![("CDV.sac", &mut s), ("PAS.sac", &mut s2)])?;
+
+let mut a = Archive::open("tests/archive.tar.gz")?;
+for (path, sac) in a.entries()? {
+    let sac = sac?;
+    assert_eq!(sac.filename(), path.to_string_lossy());
+    assert_eq!(sac.npts(), 1000);
+}
+# std::fs::remove_file("tests/archive.tar.gz")?;
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::Sac;
+use crate::SacError;
+
+/// Archive format
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Format {
+    /// Uncompressed tar
+    Tar,
+    /// gzip compressed tar
+    TarGz,
+    /// zip, entries are deflate compressed
+    Zip,
+}
+
+trait ReadSeek: Read + Seek {}
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Archive of SAC files opened for reading
+pub struct Archive {
+    inner: Inner,
+}
+
+enum Inner {
+    Tar(tar::Archive<Box<dyn Read>>),
+    Zip(zip::ZipArchive<Box<dyn ReadSeek>>),
+}
+
+fn bad(e: zip::result::ZipError) -> SacError {
+    match e {
+        zip::result::ZipError::Io(e) => SacError::Io(e),
+        e => SacError::Format(format!("zip: {}", e)),
+    }
+}
+
+fn is_gzip(buf: &[u8]) -> bool {
+    buf.starts_with(&[0x1f, 0x8b])
+}
+
+fn is_zip(buf: &[u8]) -> bool {
+    buf.starts_with(b"PK\x03\x04") || buf.starts_with(b"PK\x05\x06")
+}
+
+impl Archive {
+    /// Open a tar, tar.gz or zip archive
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Archive, SacError> {
+        let mut file = BufReader::new(File::open(path)?);
+        if is_zip(file.fill_buf()?) {
+            Archive::zip(file)
+        } else {
+            Archive::tar(file)
+        }
+    }
+    /// Read a tar archive from a stream, decompressing gzip if needed
+    ///
+    /// Entries are read in order, the stream is not seeked
+    pub fn tar<R: Read + 'static>(r: R) -> Result<Archive, SacError> {
+        let mut r = BufReader::new(r);
+        let r: Box<dyn Read> = if is_gzip(r.fill_buf()?) {
+            Box::new(flate2::bufread::MultiGzDecoder::new(r))
+        } else {
+            Box::new(r)
+        };
+        Ok(Archive { inner: Inner::Tar(tar::Archive::new(r)) })
+    }
+    /// Read a zip archive
+    pub fn zip<R: Read + Seek + 'static>(r: R) -> Result<Archive, SacError> {
+        let r: Box<dyn ReadSeek> = Box::new(r);
+        let z = zip::ZipArchive::new(r).map_err(bad)?;
+        Ok(Archive { inner: Inner::Zip(z) })
+    }
+    /// Iterate over the SAC files in the archive
+    ///
+    /// Items are the path inside the archive and the result of reading
+    /// the file.  Errors reading the archive itself are returned with an
+    /// empty path.
+    ///
+    /// A tar archive read from a stream can only be iterated once
+    pub fn entries(&mut self) -> Result<Entries<'_>, SacError> {
+        let inner = match &mut self.inner {
+            Inner::Tar(a) => EntriesInner::Tar(a.entries()?),
+            Inner::Zip(z) => EntriesInner::Zip(z, 0),
+        };
+        Ok(Entries { inner })
+    }
+}
+
+/// Iterator over the SAC files in an [`Archive`]
+pub struct Entries<'a> {
+    inner: EntriesInner<'a>,
+}
+
+enum EntriesInner<'a> {
+    Tar(tar::Entries<'a, Box<dyn Read>>),
+    Zip(&'a mut zip::ZipArchive<Box<dyn ReadSeek>>, usize),
+}
+
+fn read_entry<R: Read>(path: PathBuf, r: &mut R) -> (PathBuf, Result<Sac, SacError>) {
+    let s = Sac::read(r).map(|mut s| {
+        s.file = path.to_string_lossy().to_string();
+        s
+    });
+    (path, s)
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = (PathBuf, Result<Sac, SacError>);
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            EntriesInner::Tar(entries) => loop {
+                let mut e = match entries.next()? {
+                    Ok(e) => e,
+                    Err(e) => return Some((PathBuf::new(), Err(e.into()))),
+                };
+                if !e.header().entry_type().is_file() {
+                    continue;
+                }
+                let path = match e.path() {
+                    Ok(p) => p.into_owned(),
+                    Err(err) => return Some((PathBuf::new(), Err(err.into()))),
+                };
+                return Some(read_entry(path, &mut e));
+            },
+            EntriesInner::Zip(z, i) => loop {
+                if *i >= z.len() {
+                    return None;
+                }
+                let mut e = match z.by_index(*i) {
+                    Ok(e) => e,
+                    Err(err) => {
+                        *i += 1;
+                        return Some((PathBuf::new(), Err(bad(err))));
+                    }
+                };
+                *i += 1;
+                if e.is_dir() {
+                    continue;
+                }
+                let path = PathBuf::from(e.name());
+                return Some(read_entry(path, &mut e));
+            },
+        }
+    }
+}
+
+/// Write SAC files into a tar, tar.gz or zip archive
+///
+/// Each entry is the path inside the archive and the trace to write
+pub fn write<'a, P, S, I>(path: P, format: Format, entries: I) -> Result<(), SacError>
+where
+    P: AsRef<Path>,
+    S: AsRef<str>,
+    I: IntoIterator<Item = (S, &'a mut Sac)>,
+{
+    let file = BufWriter::new(File::create(path)?);
+    match format {
+        Format::Tar => {
+            write_tar(file, entries)?.flush()?;
+        }
+        Format::TarGz => {
+            let w = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            write_tar(w, entries)?.finish()?.flush()?;
+        }
+        Format::Zip => {
+            write_zip(file, entries)?.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_tar<'a, W, S, I>(w: W, entries: I) -> Result<W, SacError>
+where
+    W: Write,
+    S: AsRef<str>,
+    I: IntoIterator<Item = (S, &'a mut Sac)>,
+{
+    let mut t = tar::Builder::new(w);
+    for (name, s) in entries {
+        let mut buf = vec![];
+        s.write(&mut buf)?;
+        let mut h = tar::Header::new_gnu();
+        h.set_size(buf.len() as u64);
+        h.set_mode(0o644);
+        t.append_data(&mut h, name.as_ref(), buf.as_slice())?;
+    }
+    Ok(t.into_inner()?)
+}
+
+fn write_zip<'a, W, S, I>(w: W, entries: I) -> Result<W, SacError>
+where
+    W: Write + Seek,
+    S: AsRef<str>,
+    I: IntoIterator<Item = (S, &'a mut Sac)>,
+{
+    let mut z = zip::ZipWriter::new(w);
+    let opts = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, s) in entries {
+        z.start_file(name.as_ref(), opts).map_err(bad)?;
+        s.write(&mut z)?;
+    }
+    z.finish().map_err(bad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(format: Format, ext: &str) {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        let mut s2 = s.clone();
+        s2.y.iter_mut().for_each(|v| *v *= 2.0);
+        let path = std::env::temp_dir().join(format!("sacio_archive.{}", ext));
+        write(&path, format, vec![("a/one.sac", &mut s), ("a/two.sac", &mut s2)]).unwrap();
+
+        let mut a = Archive::open(&path).unwrap();
+        let out: Vec<_> = a.entries().unwrap().collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].0, PathBuf::from("a/one.sac"));
+        assert_eq!(out[1].0, PathBuf::from("a/two.sac"));
+        let r0 = out[0].1.as_ref().unwrap();
+        let r1 = out[1].1.as_ref().unwrap();
+        assert_eq!(r0.y, s.y);
+        assert_eq!(r1.y, s2.y);
+        assert_eq!(r1.filename(), "a/two.sac");
+    }
+
+    #[test]
+    fn tar() {
+        round_trip(Format::Tar, "tar");
+    }
+    #[test]
+    fn tar_gz() {
+        round_trip(Format::TarGz, "tar.gz");
+    }
+    #[test]
+    fn zip() {
+        round_trip(Format::Zip, "zip");
+    }
+
+    #[test]
+    fn not_sac() {
+        let mut buf = vec![];
+        {
+            let mut t = tar::Builder::new(&mut buf);
+            let mut h = tar::Header::new_gnu();
+            h.set_size(5);
+            t.append_data(&mut h, "README", &b"hello"[..]).unwrap();
+            let mut s = Sac::from_file("tests/file.sac").unwrap();
+            let mut data = vec![];
+            s.write(&mut data).unwrap();
+            h.set_size(data.len() as u64);
+            t.append_data(&mut h, "file.sac", data.as_slice()).unwrap();
+            t.finish().unwrap();
+        }
+        let mut a = Archive::tar(std::io::Cursor::new(buf)).unwrap();
+        let out: Vec<_> = a.entries().unwrap().collect();
+        assert_eq!(out.len(), 2);
+        assert!(out[0].1.is_err());
+        assert!(out[1].1.is_ok());
+    }
+}
@@ -0,0 +1,597 @@
+/*! C and Fortran interface compatible with the SAC library
+
+Requires the `capi` feature
+
+Functions match the signatures of `rsac1`, `rsac2`, `wsac0`, `wsac1`,
+`newhdr`, `getfhv`, `setfhv`, `getnhv`, `setnhv`, `getlhv`, `setlhv`,
+`getkhv` and `setkhv` from the SAC distribution (`sacio.h`), so existing
+C and Fortran programs can be linked against `libsacio` built from this
+crate.  The `sacio-capi` package in `capi/` builds the shared and static
+libraries, `libsacio.so` and `libsacio.a`, with
+
+```sh
+cargo build --release -p sacio-capi
+```
+
+The header `include/sacio.h` is generated from this module with cbindgen
+and is checked against it by the `sacio-capi` tests.  Regenerate it when
+the interface changes with
+
+```sh
+cbindgen --config cbindgen.toml --crate sacio --output include/sacio.h
+```
+
+As in SAC, a single header is held in memory.  It is replaced by reading
+a file with `rsac1` or `rsac2`, by `newhdr` and by `wsac1`.  The `get`
+and `set` functions access this header by name, e.g. "delta", "NPTS" or
+"kstnm", ignoring case.
+
+Strings are passed with their length as trailing arguments, as Fortran
+does.  Input strings end at the length or the first NUL.  Output strings
+are padded with blanks and NUL terminated at the last position.  Fortran
+compilers append an underscore to the names, `rsac1_`, which are also
+provided.
+
+Errors are returned in `nerr` as in SAC
+
+| nerr | Meaning                                             |
+|------|-----------------------------------------------------|
+| 0    | Success                                             |
+| -803 | More data in the file than the array, truncated     |
+| 101  | Error reading or writing the file, or invalid header|
+| 108  | File does not exist                                 |
+| 801  | File is not evenly spaced, use `rsac2`              |
+| 1336 | Header value is undefined                           |
+| 1337 | Header value does not exist                         |
+
+```c
+#include "sacio.h"
+
+float y[1000], beg, del;
+int npts, nerr, max = 1000;
+rsac1("tests/file.sac", y, &npts, &beg, &del, &max, &nerr, strlen("tests/file.sac"));
+```
+*/
+
+use std::os::raw::c_char;
+use std::os::raw::c_float;
+use std::os::raw::c_int;
+use std::panic;
+use std::slice;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use crate::header;
+use crate::Sac;
+use crate::SacError;
+use crate::SAC_FLOAT_UNDEF;
+use crate::SAC_INT_UNDEF;
+use crate::SAC_STRING_UNDEF;
+
+/// Success
+pub const SAC_OK: c_int = 0;
+/// More data in the file than the array, data is truncated
+pub const SAC_TRUNCATED: c_int = -803;
+/// Error reading or writing the file, or an invalid header
+pub const SAC_ERROR_IO: c_int = 101;
+/// File does not exist
+pub const SAC_ERROR_NOT_FOUND: c_int = 108;
+/// File is not evenly spaced
+pub const SAC_ERROR_UNEVEN: c_int = 801;
+/// Header value is undefined
+pub const SAC_ERROR_UNDEFINED: c_int = 1336;
+/// Header value does not exist
+pub const SAC_ERROR_BAD_NAME: c_int = 1337;
+
+/// Header currently held in memory
+static CURRENT: Mutex<Option<Sac>> = Mutex::new(None);
+
+fn current() -> MutexGuard<'static, Option<Sac>> {
+    match CURRENT.lock() {
+        Ok(g) => g,
+        Err(e) => e.into_inner(),
+    }
+}
+
+fn io_error(e: SacError) -> c_int {
+    match e {
+        SacError::Io(ref e) if e.kind() == std::io::ErrorKind::NotFound => SAC_ERROR_NOT_FOUND,
+        _ => SAC_ERROR_IO,
+    }
+}
+
+/// Run `f`, returning an error in `nerr` rather than unwinding into C
+unsafe fn guard<F: FnOnce()>(nerr: *mut c_int, f: F) {
+    if panic::catch_unwind(panic::AssertUnwindSafe(f)).is_err() {
+        *nerr = SAC_ERROR_IO;
+    }
+}
+
+/// Input string with an explicit length, ending at the length or a NUL
+unsafe fn input(s: *const c_char, len: c_int) -> String {
+    let s = slice::from_raw_parts(s as *const u8, len.max(0) as usize);
+    let n = s.iter().position(|&c| c == 0).unwrap_or(s.len());
+    String::from_utf8_lossy(&s[..n]).trim().to_string()
+}
+
+/// Output string padded with blanks and NUL terminated
+unsafe fn output(value: &str, s: *mut c_char, len: c_int) {
+    if len <= 0 {
+        return;
+    }
+    let s = slice::from_raw_parts_mut(s as *mut u8, len as usize);
+    let n = s.len() - 1;
+    for (i, c) in s[..n].iter_mut().enumerate() {
+        *c = *value.as_bytes().get(i).unwrap_or(&b' ');
+    }
+    s[n] = 0;
+}
+
+/// Read an evenly spaced file
+///
+/// Reads `kname` into `yarray`, up to `max_` points.  The number of
+/// points, begin time and sampling interval are returned in `nlen`, `beg`
+/// and `del`.
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes and `yarray` to `max_` floats
+#[no_mangle]
+pub unsafe extern "C" fn rsac1(kname: *const c_char, yarray: *mut c_float, nlen: *mut c_int,
+                               beg: *mut c_float, del: *mut c_float, max_: *const c_int,
+                               nerr: *mut c_int, kname_len: c_int) {
+    guard(nerr, || {
+        let s = match Sac::from_file(input(kname, kname_len)) {
+            Ok(s) => s,
+            Err(e) => { *nerr = io_error(e); return; }
+        };
+        if !s.evenly_spaced() {
+            *nerr = SAC_ERROR_UNEVEN;
+            return;
+        }
+        let n = s.y.len().min((*max_).max(0) as usize);
+        slice::from_raw_parts_mut(yarray, n).copy_from_slice(&s.y[..n]);
+        *nlen = n as c_int;
+        *beg = s.b;
+        *del = s.delta;
+        *nerr = if n < s.y.len() { SAC_TRUNCATED } else { SAC_OK };
+        *current() = Some(s);
+    })
+}
+
+/// Read an unevenly spaced or spectral file
+///
+/// Reads `kname` into `yarray` and `xarray`, up to `max_` points.  For
+/// evenly spaced files `xarray` is filled with the time of each sample.
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes, `yarray` and `xarray` to
+/// `max_` floats
+#[no_mangle]
+pub unsafe extern "C" fn rsac2(kname: *const c_char, yarray: *mut c_float, nlen: *mut c_int,
+                               xarray: *mut c_float, max_: *const c_int,
+                               nerr: *mut c_int, kname_len: c_int) {
+    guard(nerr, || {
+        let s = match Sac::from_file(input(kname, kname_len)) {
+            Ok(s) => s,
+            Err(e) => { *nerr = io_error(e); return; }
+        };
+        let n = s.y.len().min((*max_).max(0) as usize);
+        slice::from_raw_parts_mut(yarray, n).copy_from_slice(&s.y[..n]);
+        let x = slice::from_raw_parts_mut(xarray, n);
+        if s.x.is_empty() {
+            for (i, v) in x.iter_mut().enumerate() {
+                *v = s.b + s.delta * i as f32;
+            }
+        } else {
+            x.copy_from_slice(&s.x[..n]);
+        }
+        *nlen = n as c_int;
+        *nerr = if n < s.y.len() { SAC_TRUNCATED } else { SAC_OK };
+        *current() = Some(s);
+    })
+}
+
+/// Write a file using the header in memory
+///
+/// The number of points is taken from `npts`.  `xarray` is written for
+/// unevenly spaced and spectral files, as the second component.
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes, `yarray` and `xarray` to
+/// `npts` floats
+#[no_mangle]
+pub unsafe extern "C" fn wsac0(kname: *const c_char, xarray: *const c_float,
+                               yarray: *const c_float, nerr: *mut c_int, kname_len: c_int) {
+    guard(nerr, || {
+        let mut cur = current();
+        let s = cur.get_or_insert_with(Sac::new);
+        let n = s.npts.max(0) as usize;
+        s.y = slice::from_raw_parts(yarray, n).to_vec();
+        s.x = if s.ncomps() == 2 {
+            slice::from_raw_parts(xarray, n).to_vec()
+        } else {
+            vec![]
+        };
+        *nerr = match s.to_file(input(kname, kname_len)) {
+            Ok(()) => SAC_OK,
+            Err(e) => io_error(e),
+        };
+    })
+}
+
+/// Write an evenly spaced file with a new header
+///
+/// The header in memory is replaced by a new header with `nlen`, `beg`
+/// and `del` set
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes and `yarray` to `nlen` floats
+#[no_mangle]
+pub unsafe extern "C" fn wsac1(kname: *const c_char, yarray: *const c_float, nlen: *const c_int,
+                               beg: *const c_float, del: *const c_float,
+                               nerr: *mut c_int, kname_len: c_int) {
+    guard(nerr, || {
+        let y = slice::from_raw_parts(yarray, (*nlen).max(0) as usize).to_vec();
+        let mut s = Sac::from_amp(y, f64::from(*beg), f64::from(*del));
+        s.calc_be();
+        *nerr = match s.to_file(input(kname, kname_len)) {
+            Ok(()) => SAC_OK,
+            Err(e) => io_error(e),
+        };
+        *current() = Some(s);
+    })
+}
+
+/// Replace the header in memory with a new, undefined, header
+#[no_mangle]
+pub extern "C" fn newhdr() {
+    *current() = Some(Sac::new());
+}
+
+/// Get a real header value
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn getfhv(kname: *const c_char, fvalue: *mut c_float,
+                                nerr: *mut c_int, kname_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    let cur = current();
+    let s = cur.as_ref();
+    *nerr = match s.and_then(|s| header::real(s, &key)) {
+        Some(&v) if v == SAC_FLOAT_UNDEF => { *fvalue = v; SAC_ERROR_UNDEFINED },
+        Some(&v) => { *fvalue = v; SAC_OK },
+        None if s.is_none() => SAC_ERROR_UNDEFINED,
+        None => SAC_ERROR_BAD_NAME,
+    };
+}
+
+/// Set a real header value
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn setfhv(kname: *const c_char, fvalue: *const c_float,
+                                nerr: *mut c_int, kname_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    let mut cur = current();
+    let s = cur.get_or_insert_with(Sac::new);
+    *nerr = match header::real_mut(s, &key) {
+        Some(v) => { *v = *fvalue; SAC_OK },
+        None => SAC_ERROR_BAD_NAME,
+    };
+}
+
+/// Get an integer header value, e.g. "npts" or "nzyear"
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn getnhv(kname: *const c_char, nvalue: *mut c_int,
+                                nerr: *mut c_int, kname_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    let cur = current();
+    let s = cur.as_ref();
+    *nerr = match s.and_then(|s| header::int(s, &key)) {
+        Some(&v) if v == SAC_INT_UNDEF => { *nvalue = v; SAC_ERROR_UNDEFINED },
+        Some(&v) => { *nvalue = v; SAC_OK },
+        None if s.is_none() => SAC_ERROR_UNDEFINED,
+        None => SAC_ERROR_BAD_NAME,
+    };
+}
+
+/// Set an integer header value
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn setnhv(kname: *const c_char, nvalue: *const c_int,
+                                nerr: *mut c_int, kname_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    let mut cur = current();
+    let s = cur.get_or_insert_with(Sac::new);
+    *nerr = match header::int_mut(s, &key) {
+        Some(v) => { *v = *nvalue; SAC_OK },
+        None => SAC_ERROR_BAD_NAME,
+    };
+}
+
+/// Get a logical header value, e.g. "leven", as 0 or 1
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn getlhv(kname: *const c_char, lvalue: *mut c_int,
+                                nerr: *mut c_int, kname_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    if !key.starts_with('l') {
+        *nerr = SAC_ERROR_BAD_NAME;
+        return;
+    }
+    getnhv(key.as_ptr() as *const c_char, lvalue, nerr, key.len() as c_int);
+    if *nerr == SAC_OK {
+        *lvalue = (*lvalue != 0) as c_int;
+    }
+}
+
+/// Set a logical header value, e.g. "leven", from 0 or 1
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn setlhv(kname: *const c_char, lvalue: *const c_int,
+                                nerr: *mut c_int, kname_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    if !key.starts_with('l') {
+        *nerr = SAC_ERROR_BAD_NAME;
+        return;
+    }
+    let v = (*lvalue != 0) as c_int;
+    setnhv(key.as_ptr() as *const c_char, &v, nerr, key.len() as c_int);
+}
+
+/// Get a string header value
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes and `kvalue` to `kvalue_len`
+/// bytes
+#[no_mangle]
+pub unsafe extern "C" fn getkhv(kname: *const c_char, kvalue: *mut c_char, nerr: *mut c_int,
+                                kname_len: c_int, kvalue_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    let cur = current();
+    let s = cur.as_ref();
+    *nerr = match s.and_then(|s| header::string(s, &key)) {
+        Some(v) if v.trim_end() == SAC_STRING_UNDEF.trim_end() => {
+            output(v, kvalue, kvalue_len);
+            SAC_ERROR_UNDEFINED
+        },
+        Some(v) => { output(v, kvalue, kvalue_len); SAC_OK },
+        None if s.is_none() => SAC_ERROR_UNDEFINED,
+        None => SAC_ERROR_BAD_NAME,
+    };
+}
+
+/// Set a string header value
+///
+/// # Safety
+///
+/// `kname` must point to `kname_len` bytes and `kvalue` to `kvalue_len`
+/// bytes
+#[no_mangle]
+pub unsafe extern "C" fn setkhv(kname: *const c_char, kvalue: *const c_char, nerr: *mut c_int,
+                                kname_len: c_int, kvalue_len: c_int) {
+    let key = input(kname, kname_len).to_lowercase();
+    let value = input(kvalue, kvalue_len);
+    let mut cur = current();
+    let s = cur.get_or_insert_with(Sac::new);
+    *nerr = match header::string_mut(s, &key) {
+        Some(v) => { *v = value; SAC_OK },
+        None => SAC_ERROR_BAD_NAME,
+    };
+}
+
+macro_rules! fortran {
+    ($($f:ident => $c:ident ( $($a:ident : $t:ty),* );)*) => { $(
+        #[doc = concat!("Fortran name of [`", stringify!($c), "`]")]
+        ///
+        /// # Safety
+        ///
+        #[doc = concat!("See [`", stringify!($c), "`]")]
+        #[no_mangle]
+        pub unsafe extern "C" fn $f($($a: $t),*) {
+            $c($($a),*)
+        }
+    )* };
+}
+
+fortran! {
+    rsac1_ => rsac1(kname: *const c_char, yarray: *mut c_float, nlen: *mut c_int,
+                    beg: *mut c_float, del: *mut c_float, max_: *const c_int,
+                    nerr: *mut c_int, kname_len: c_int);
+    rsac2_ => rsac2(kname: *const c_char, yarray: *mut c_float, nlen: *mut c_int,
+                    xarray: *mut c_float, max_: *const c_int,
+                    nerr: *mut c_int, kname_len: c_int);
+    wsac0_ => wsac0(kname: *const c_char, xarray: *const c_float, yarray: *const c_float,
+                    nerr: *mut c_int, kname_len: c_int);
+    wsac1_ => wsac1(kname: *const c_char, yarray: *const c_float, nlen: *const c_int,
+                    beg: *const c_float, del: *const c_float,
+                    nerr: *mut c_int, kname_len: c_int);
+    getfhv_ => getfhv(kname: *const c_char, fvalue: *mut c_float, nerr: *mut c_int, kname_len: c_int);
+    setfhv_ => setfhv(kname: *const c_char, fvalue: *const c_float, nerr: *mut c_int, kname_len: c_int);
+    getnhv_ => getnhv(kname: *const c_char, nvalue: *mut c_int, nerr: *mut c_int, kname_len: c_int);
+    setnhv_ => setnhv(kname: *const c_char, nvalue: *const c_int, nerr: *mut c_int, kname_len: c_int);
+    getlhv_ => getlhv(kname: *const c_char, lvalue: *mut c_int, nerr: *mut c_int, kname_len: c_int);
+    setlhv_ => setlhv(kname: *const c_char, lvalue: *const c_int, nerr: *mut c_int, kname_len: c_int);
+    getkhv_ => getkhv(kname: *const c_char, kvalue: *mut c_char, nerr: *mut c_int,
+                      kname_len: c_int, kvalue_len: c_int);
+    setkhv_ => setkhv(kname: *const c_char, kvalue: *const c_char, nerr: *mut c_int,
+                      kname_len: c_int, kvalue_len: c_int);
+}
+
+/// Fortran name of [`newhdr`]
+#[no_mangle]
+pub extern "C" fn newhdr_() {
+    newhdr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The header in memory is shared, tests using it run one at a time
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    fn c(s: &str) -> (*const c_char, c_int) {
+        (s.as_ptr() as *const c_char, s.len() as c_int)
+    }
+
+    #[test]
+    fn read_and_header() {
+        let _g = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut y = vec![0.0f32; 2000];
+        let (mut n, mut b, mut dt, mut nerr) = (0, 0.0, 0.0, -1);
+        let max = y.len() as c_int;
+        let (f, flen) = c("tests/file.sac");
+        unsafe {
+            rsac1(f, y.as_mut_ptr(), &mut n, &mut b, &mut dt, &max, &mut nerr, flen);
+        }
+        let s = Sac::from_file("tests/file.sac").unwrap();
+        assert_eq!(nerr, SAC_OK);
+        assert_eq!(n, 1000);
+        assert_eq!(&y[..1000], &s.y[..]);
+        assert_eq!(b, s.b());
+        assert_eq!(dt, s.delta());
+
+        let mut v = 0.0;
+        let mut i = 0;
+        let mut k = [1 as c_char; 9];
+        unsafe {
+            let (key, len) = c("DELTA");
+            getfhv(key, &mut v, &mut nerr, len);
+            assert_eq!((v, nerr), (0.01, SAC_OK));
+            let (key, len) = c("evel");
+            getfhv(key, &mut v, &mut nerr, len);
+            assert_eq!(nerr, SAC_ERROR_UNDEFINED);
+            let (key, len) = c("stlx");
+            getfhv(key, &mut v, &mut nerr, len);
+            assert_eq!(nerr, SAC_ERROR_BAD_NAME);
+            let (key, len) = c("npts\0ignored");
+            getnhv(key, &mut i, &mut nerr, len);
+            assert_eq!((i, nerr), (1000, SAC_OK));
+            let (key, len) = c("leven");
+            getlhv(key, &mut i, &mut nerr, len);
+            assert_eq!((i, nerr), (1, SAC_OK));
+            let (key, len) = c("kstnm");
+            getkhv(key, k.as_mut_ptr(), &mut nerr, len, k.len() as c_int);
+            assert_eq!(nerr, SAC_OK);
+            let k: Vec<u8> = k.iter().map(|&c| c as u8).collect();
+            assert_eq!(&k, b"CDV     \0");
+        }
+
+        let mut small = vec![0.0f32; 10];
+        let max = 10;
+        unsafe {
+            rsac1(f, small.as_mut_ptr(), &mut n, &mut b, &mut dt, &max, &mut nerr, flen);
+        }
+        assert_eq!((n, nerr), (10, SAC_TRUNCATED));
+        assert_eq!(&small[..], &s.y[..10]);
+
+        let (f, flen) = c("tests/does_not_exist.sac");
+        unsafe {
+            rsac1(f, small.as_mut_ptr(), &mut n, &mut b, &mut dt, &max, &mut nerr, flen);
+        }
+        assert_eq!(nerr, SAC_ERROR_NOT_FOUND);
+    }
+
+    #[test]
+    fn write() {
+        let _g = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = std::env::temp_dir().join("sacio_capi.sac");
+        let path = path.to_string_lossy().to_string();
+        let y = vec![1.0f32, 2.0, 3.0];
+        let (n, b, dt) = (3, 1.5, 0.25);
+        let mut nerr = -1;
+        let (f, flen) = c(&path);
+        unsafe {
+            wsac1(f, y.as_ptr(), &n, &b, &dt, &mut nerr, flen);
+        }
+        assert_eq!(nerr, SAC_OK);
+        let s = Sac::from_file(&path).unwrap();
+        assert_eq!(s.y, y);
+        assert_eq!(s.b(), 1.5);
+        assert_eq!(s.e(), 2.0);
+
+        newhdr();
+        let x = vec![0.0f32, 1.0, 3.0];
+        unsafe {
+            let (key, len) = c("npts");
+            setnhv(key, &n, &mut nerr, len);
+            let (key, len) = c("b");
+            setfhv(key, &0.0, &mut nerr, len);
+            let (key, len) = c("e");
+            setfhv(key, &3.0, &mut nerr, len);
+            let (key, len) = c("leven");
+            setlhv(key, &0, &mut nerr, len);
+            let (key, len) = c("kstnm");
+            let (value, vlen) = c("PAS\0");
+            setkhv(key, value, &mut nerr, len, vlen);
+            assert_eq!(nerr, SAC_OK);
+            wsac0(f, x.as_ptr(), y.as_ptr(), &mut nerr, flen);
+        }
+        assert_eq!(nerr, SAC_OK);
+        let s = Sac::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(s.y, y);
+        assert_eq!(s.x, x);
+        assert_eq!(s.string(crate::SacString::Station).trim(), "PAS");
+    }
+
+    #[test]
+    fn write_spectral() {
+        let _g = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = std::env::temp_dir().join("sacio_capi_spectral.sac");
+        let path = path.to_string_lossy().to_string();
+        let (f, flen) = c(&path);
+        let y = vec![1.0f32, 2.0, 3.0];
+        let x = vec![0.5f32, 0.25, 0.125];
+        let mut nerr = -1;
+        newhdr();
+        unsafe {
+            let (key, len) = c("npts");
+            setnhv(key, &3, &mut nerr, len);
+            let (key, len) = c("iftype");
+            setnhv(key, &2, &mut nerr, len);
+            let (key, len) = c("delta");
+            setfhv(key, &1.0, &mut nerr, len);
+            let (key, len) = c("leven");
+            setlhv(key, &1, &mut nerr, len);
+            wsac0(f, x.as_ptr(), y.as_ptr(), &mut nerr, flen);
+        }
+        assert_eq!(nerr, SAC_OK);
+        let s = Sac::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(s.y, y);
+        assert_eq!(s.x, x);
+
+        // An invalid file type is an error rather than a panic
+        unsafe {
+            let (key, len) = c("iftype");
+            setnhv(key, &99, &mut nerr, len);
+            wsac0(f, x.as_ptr(), y.as_ptr(), &mut nerr, flen);
+        }
+        assert_eq!(nerr, SAC_ERROR_IO);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,532 @@
+/*! Collections of SAC files
+
+A [`SacCollection`] behaves much like the data file list in SAC.  Files
+are read into memory together, can be sorted and filtered by header
+values and any per-trace operation can be applied to all of them at once.
+
+```
+use sacio::SacCollection;
+use sacio::SacSortKey;
+# use sacio::SacError;
+
+let mut sacs = SacCollection::from_glob("tests/file.sac*")?;
+assert_eq!(sacs.len(), 2);
+
+sacs.sort(SacSortKey::Gcarc);
+sacs.apply(|s| {
+    s.y.iter_mut().for_each(|v| *v *= 2.0);
+    s.extrema_amp();
+});
+assert!(sacs.iter().all(|s| s.max_amp() == 1.52064 * 2.0));
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use rayon::prelude::*;
+
+use crate::Sac;
+use crate::SacError;
+use crate::Marker;
+use crate::SacString;
+use crate::iis;
+use crate::SAC_STRING_UNDEF;
+
+/// Header values used to sort a collection
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SacSortKey {
+    /// Great circle distance in degrees (gcarc)
+    Gcarc,
+    /// Distance in km (dist)
+    Dist,
+    /// Event to station azimuth (az)
+    Az,
+    /// Station to event azimuth (baz)
+    Baz,
+    /// Network, Station, Location and Channel code
+    Nslc,
+    /// Reference time (nzyear, nzjday, ...)
+    Time,
+    /// Begin time value (b)
+    B,
+}
+
+/// Collection of SAC files
+///
+/// Traces keep the order they were read or pushed in until sorted.
+#[derive(Debug, Default, Clone)]
+pub struct SacCollection {
+    traces: Vec<Sac>,
+}
+
+/// Read a set of files in parallel, keeping the order of the paths
+fn read_files(paths: Vec<PathBuf>) -> Result<SacCollection, SacError> {
+    let traces = paths.par_iter()
+        .map(Sac::from_file)
+        .collect::<Result<Vec<Sac>,SacError>>()?;
+    Ok(SacCollection { traces })
+}
+
+/// Defined string value, trimmed, or an empty string
+fn string_or_empty(s: &Sac, key: SacString) -> &str {
+    let v = s.string(key);
+    if v == SAC_STRING_UNDEF { "" } else { v.trim() }
+}
+
+/// Give all traces the same reference time, like SAC's `synchronize`
+///
+/// The reference time is `reference` or, if `None`, the latest reference
+/// time of the traces.  Each trace keeps its data and the absolute times
+/// of its markers, so `b` values can be compared directly afterwards; see
+/// [`Sac::set_reference_time`].  The reference time used is returned.
+///
+/// Traces without a complete reference time (nzyear - nzmsec) are
+/// reported by their index in [`SacError::IncompleteTime`] and no trace
+/// is changed.
+///
+/// ```
+/// use sacio::{Sac, Marker, synchronize};
+/// # use sacio::SacError;
+/// use chrono::Duration;
+///
+/// let s = Sac::from_file("tests/file.sac")?;
+/// let mut t = s.clone();
+/// t.set_reference_time(s.time()? + Duration::seconds(30))?;
+/// let mut sacs = vec![s, t];
+/// let tref = synchronize(&mut sacs, None)?;
+/// assert_eq!(tref, sacs[1].time()?);
+/// assert_eq!(sacs[0].time()?, sacs[1].time()?);
+/// assert!((sacs[0].b() - sacs[1].b()).abs() < 1e-4);
+///
+/// sacs.push(Sac::from_amp(vec![0.0; 3], 0.0, 1.0));
+/// match synchronize(&mut sacs, None) {
+///     Err(SacError::IncompleteTime(bad)) => assert_eq!(bad, vec![2]),
+///     _ => panic!("expected an incomplete time"),
+/// }
+/// # Ok::<(), SacError>(())
+/// ```
+pub fn synchronize(sacs: &mut [Sac], reference: Option<NaiveDateTime>) -> Result<NaiveDateTime, SacError> {
+    let times: Vec<_> = sacs.iter().map(|s| s.time()).collect();
+    let bad: Vec<usize> = times.iter().enumerate()
+        .filter(|(_, t)| t.is_err())
+        .map(|(i, _)| i)
+        .collect();
+    if !bad.is_empty() {
+        return Err(SacError::IncompleteTime(bad));
+    }
+    let tref = match reference {
+        Some(t) => t,
+        None => times.into_iter().filter_map(Result::ok).max().ok_or(SacError::NotTime)?,
+    };
+    for s in sacs.iter_mut() {
+        s.set_reference_time(tref)?;
+    }
+    sacs.first().map(|s| s.time()).unwrap_or(Ok(tref))
+}
+
+/// Identifier of the event associated with a trace
+///
+/// Uses the event name (kevnm), then the event id (nevid) and
+/// finally the origin time
+fn event_key(s: &Sac) -> String {
+    let name = string_or_empty(s, SacString::EventName);
+    if !name.is_empty() {
+        return name.to_string();
+    }
+    if iis(s.nevid) {
+        return format!("{}", s.nevid);
+    }
+    match s.datetime(Marker::O) {
+        Ok(t) => format!("{}", t),
+        Err(_) => String::new(),
+    }
+}
+
+impl SacCollection {
+    /// Create an empty collection
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    ///
+    /// let sacs = SacCollection::new();
+    /// assert!(sacs.is_empty());
+    /// ```
+    pub fn new() -> SacCollection {
+        SacCollection { traces: vec![] }
+    }
+    /// Read all files matching a glob pattern
+    ///
+    /// Files are read in the alphabetical order of their paths
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// # use sacio::SacError;
+    ///
+    /// let sacs = SacCollection::from_glob("tests/file.sac*")?;
+    /// assert_eq!(sacs.len(), 2);
+    /// assert_eq!(sacs[0].filename(), "tests/file.sac");
+    /// assert_eq!(sacs[1].filename(), "tests/file.sac.swap");
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn from_glob(pattern: &str) -> Result<SacCollection, SacError> {
+        let paths = glob::glob(pattern).map_err(|_| SacError::BadPattern)?
+            .collect::<Result<Vec<PathBuf>,_>>()
+            .map_err(|e| SacError::Io(e.into()))?;
+        read_files(paths)
+    }
+    /// Read all files within a directory
+    ///
+    /// Every regular file in the directory must be a SAC file
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// # use sacio::SacError;
+    ///
+    /// # std::fs::create_dir_all("tests/from_dir")?;
+    /// # std::fs::copy("tests/file.sac", "tests/from_dir/file.sac")?;
+    /// # std::fs::copy("tests/file.sac.swap", "tests/from_dir/file.sac.swap")?;
+    /// let sacs = SacCollection::from_dir("tests/from_dir")?;
+    /// assert_eq!(sacs.len(), 2);
+    /// # std::fs::remove_dir_all("tests/from_dir")?;
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<SacCollection, SacError> {
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        read_files(paths)
+    }
+    /// Write each file into a directory, using the current filename
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// # use sacio::SacError;
+    ///
+    /// let mut sacs = SacCollection::from_glob("tests/file.sac*")?;
+    /// # std::fs::create_dir_all("tests/to_dir")?;
+    /// sacs.to_dir("tests/to_dir")?;
+    ///
+    /// let sacs2 = SacCollection::from_dir("tests/to_dir")?;
+    /// assert_eq!(sacs.len(), sacs2.len());
+    /// assert_eq!(sacs2[0].filename(), "tests/to_dir/file.sac");
+    /// # std::fs::remove_dir_all("tests/to_dir")?;
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn to_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<(), SacError> {
+        let dir = dir.as_ref();
+        self.try_apply(|s| {
+            let name = Path::new(s.filename()).file_name()
+                .ok_or(SacError::BadFilename)?;
+            let path = dir.join(name);
+            s.to_file(&path)?;
+            s.set_filename(&path.to_string_lossy());
+            Ok(())
+        })
+    }
+    /// Number of traces in the collection
+    pub fn len(&self) -> usize {
+        self.traces.len()
+    }
+    /// Check if the collection is empty
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+    /// Add a trace to the end of the collection
+    pub fn push(&mut self, s: Sac) {
+        self.traces.push(s);
+    }
+    /// Iterate over the traces
+    pub fn iter(&self) -> std::slice::Iter<'_, Sac> {
+        self.traces.iter()
+    }
+    /// Iterate mutably over the traces
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Sac> {
+        self.traces.iter_mut()
+    }
+    /// Traces as a slice
+    pub fn as_slice(&self) -> &[Sac] {
+        &self.traces
+    }
+    /// Traces as a mutable slice
+    pub fn as_mut_slice(&mut self) -> &mut [Sac] {
+        &mut self.traces
+    }
+    /// Return the underlying traces
+    pub fn into_vec(self) -> Vec<Sac> {
+        self.traces
+    }
+    /// Sort traces by a header value
+    ///
+    /// The sort is stable. Undefined values sort before defined values.
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::SacCollection;
+    /// use sacio::SacSortKey;
+    /// use sacio::SacString;
+    ///
+    /// let mut sacs = SacCollection::new();
+    /// for sta in &["PAS", "ANMO", "COR"] {
+    ///     let mut s = Sac::from_amp(vec![0.0; 3], 0.0, 1.0);
+    ///     s.set_string(SacString::Station, sta);
+    ///     sacs.push(s);
+    /// }
+    /// sacs.sort(SacSortKey::Nslc);
+    /// let stations : Vec<_> = sacs.iter().map(|s| s.nslc()).collect();
+    /// assert_eq!(stations, vec![".ANMO..", ".COR..", ".PAS.."]);
+    /// ```
+    pub fn sort(&mut self, key: SacSortKey) {
+        match key {
+            SacSortKey::Gcarc => self.sort_by_f32(|s| s.dist_deg()),
+            SacSortKey::Dist  => self.sort_by_f32(|s| s.dist_km()),
+            SacSortKey::Az    => self.sort_by_f32(|s| s.az()),
+            SacSortKey::Baz   => self.sort_by_f32(|s| s.baz()),
+            SacSortKey::B     => self.sort_by_f32(|s| s.b()),
+            SacSortKey::Nslc  => self.traces.sort_by_key(|s| s.nslc()),
+            SacSortKey::Time  => self.traces.sort_by_key(|s| s.time().ok()),
+        }
+    }
+    fn sort_by_f32<F: Fn(&Sac) -> f32>(&mut self, f: F) {
+        self.traces.sort_by(|a,b| f(a).total_cmp(&f(b)));
+    }
+    /// Sort traces using a comparison function
+    pub fn sort_by<F>(&mut self, compare: F)
+    where F: FnMut(&Sac, &Sac) -> std::cmp::Ordering
+    {
+        self.traces.sort_by(compare);
+    }
+    /// Keep only traces for which the predicate returns true
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// # use sacio::SacError;
+    ///
+    /// let mut sacs = SacCollection::from_glob("tests/file.sac*")?;
+    /// sacs.filter(|s| s.swapped());
+    /// assert_eq!(sacs.len(), 1);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn filter<F: FnMut(&Sac) -> bool>(&mut self, f: F) {
+        self.traces.retain(f);
+    }
+    /// Apply an operation to every trace in parallel
+    pub fn apply<F>(&mut self, f: F)
+    where F: Fn(&mut Sac) + Sync + Send
+    {
+        self.traces.par_iter_mut().for_each(f);
+    }
+    /// Apply a fallible operation to every trace in parallel
+    ///
+    /// Every trace is processed, the first error encountered is returned
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// # use sacio::SacError;
+    ///
+    /// let mut sacs = SacCollection::from_glob("tests/file.sac*")?;
+    /// sacs.try_apply(|s| s.set_cmpaz(90.0))?;
+    /// assert!(sacs.try_apply(|s| s.set_cmpaz(720.0)).is_err());
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn try_apply<F>(&mut self, f: F) -> Result<(), SacError>
+    where F: Fn(&mut Sac) -> Result<(), SacError> + Sync + Send
+    {
+        self.traces.par_iter_mut()
+            .map(f)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .collect()
+    }
+    /// Give all traces the same reference time, see [`synchronize`]
+    pub fn synchronize(&mut self, reference: Option<NaiveDateTime>) -> Result<NaiveDateTime, SacError> {
+        synchronize(&mut self.traces, reference)
+    }
+    /// Group traces by Network, Station and Location
+    ///
+    /// Useful for assembling 3-component sets
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// use sacio::SacString;
+    /// # use sacio::SacError;
+    ///
+    /// let mut sacs = SacCollection::from_glob("tests/file.sac*")?;
+    /// sacs.apply(|s| s.set_string(SacString::Network, "CI"));
+    /// let groups = sacs.group_by_station();
+    /// assert_eq!(groups.len(), 1);
+    /// assert_eq!(groups["CI.CDV."].len(), 2);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn group_by_station(self) -> BTreeMap<String, SacCollection> {
+        self.group_by(|s| {
+            [SacString::Network, SacString::Station, SacString::Location]
+                .iter()
+                .map(|k| string_or_empty(s, *k))
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+    }
+    /// Group traces by Event
+    ///
+    /// Events are identified by the event name (kevnm), the event id (nevid)
+    /// or the origin time, whichever is defined first
+    ///
+    /// ```
+    /// use sacio::SacCollection;
+    /// # use sacio::SacError;
+    ///
+    /// let sacs = SacCollection::from_glob("tests/file.sac*")?;
+    /// let groups = sacs.group_by_event();
+    /// assert_eq!(groups.len(), 1);
+    /// assert_eq!(groups["K8108838"].len(), 2);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn group_by_event(self) -> BTreeMap<String, SacCollection> {
+        self.group_by(event_key)
+    }
+    /// Group traces by an arbitrary key
+    pub fn group_by<K, F>(self, key: F) -> BTreeMap<K, SacCollection>
+    where K: Ord, F: Fn(&Sac) -> K
+    {
+        let mut groups : BTreeMap<K, SacCollection> = BTreeMap::new();
+        for s in self.traces {
+            groups.entry(key(&s)).or_default().push(s);
+        }
+        groups
+    }
+}
+
+impl From<Vec<Sac>> for SacCollection {
+    fn from(traces: Vec<Sac>) -> Self {
+        SacCollection { traces }
+    }
+}
+
+impl std::iter::FromIterator<Sac> for SacCollection {
+    fn from_iter<I: IntoIterator<Item = Sac>>(iter: I) -> Self {
+        SacCollection { traces: iter.into_iter().collect() }
+    }
+}
+
+impl std::iter::Extend<Sac> for SacCollection {
+    fn extend<I: IntoIterator<Item = Sac>>(&mut self, iter: I) {
+        self.traces.extend(iter);
+    }
+}
+
+impl IntoIterator for SacCollection {
+    type Item = Sac;
+    type IntoIter = std::vec::IntoIter<Sac>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.traces.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SacCollection {
+    type Item = &'a Sac;
+    type IntoIter = std::slice::Iter<'a, Sac>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.traces.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut SacCollection {
+    type Item = &'a mut Sac;
+    type IntoIter = std::slice::IterMut<'a, Sac>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.traces.iter_mut()
+    }
+}
+
+impl std::ops::Index<usize> for SacCollection {
+    type Output = Sac;
+    fn index(&self, i: usize) -> &Sac {
+        &self.traces[i]
+    }
+}
+
+impl std::ops::IndexMut<usize> for SacCollection {
+    fn index_mut(&mut self, i: usize) -> &mut Sac {
+        &mut self.traces[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeValue;
+    use chrono::Duration;
+
+    fn trace(sta: &str, gcarc: Option<f32>) -> Sac {
+        let mut s = Sac::from_amp(vec![0.0; 3], 0.0, 1.0);
+        s.set_string(SacString::Station, sta);
+        if let Some(g) = gcarc {
+            s.gcarc = g;
+        }
+        s
+    }
+    fn stations(sacs: &SacCollection) -> Vec<String> {
+        sacs.iter().map(|s| s.string(SacString::Station).trim().to_string()).collect()
+    }
+
+    #[test]
+    fn sort_undefined() {
+        let mut sacs: SacCollection = vec![
+            trace("A", Some(30.0)), trace("B", None), trace("C", Some(10.0)), trace("D", None),
+        ].into();
+        sacs.sort(SacSortKey::Gcarc);
+        // Undefined first, keeping their order
+        assert_eq!(stations(&sacs), vec!["B", "D", "C", "A"]);
+
+        let mut t = trace("E", None);
+        t.set_time(NaiveDateTime::parse_from_str("2000-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap());
+        sacs.push(t);
+        sacs.sort(SacSortKey::Time);
+        assert_eq!(stations(&sacs), vec!["B", "D", "C", "A", "E"]);
+
+        let mut empty = SacCollection::new();
+        empty.sort(SacSortKey::Nslc);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn filter_all() {
+        let mut sacs: SacCollection = vec![trace("A", None), trace("B", None)].into();
+        sacs.filter(|s| s.try_dist_deg().is_some());
+        assert!(sacs.is_empty());
+        assert!(sacs.group_by_station().is_empty());
+    }
+
+    #[test]
+    fn group_undefined() {
+        let mut sacs: SacCollection = vec![trace("A", None), trace("A", None), trace("B", None)].into();
+        sacs[2].set_string(SacString::Station, SAC_STRING_UNDEF);
+        let groups = sacs.clone().group_by_station();
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["..", ".A."]);
+        assert_eq!(groups[".A."].len(), 2);
+
+        // Event name, then event id, then origin time, else empty
+        sacs[0].nevid = 1234;
+        sacs[1].set_time(NaiveDateTime::parse_from_str("2000-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap());
+        sacs[1].set_o(TimeValue::Relative(Duration::seconds(-5))).unwrap();
+        let groups = sacs.group_by_event();
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["", "1234", "1999-12-31 23:59:55"]);
+        assert!(groups.values().all(|g| g.len() == 1));
+    }
+
+    #[test]
+    fn synchronize_empty() {
+        assert!(matches!(synchronize(&mut [], None), Err(SacError::NotTime)));
+        let t = NaiveDateTime::parse_from_str("2000-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(synchronize(&mut [], Some(t)).unwrap(), t);
+    }
+}
@@ -0,0 +1,151 @@
+/*! Compressed SAC files
+
+Files compressed with gzip, zstd or bzip2 are decompressed transparently
+by [`Sac::from_file`], detected by their magic bytes rather than the
+file extension.  Each format is behind a cargo feature of the same name
+(`gzip`, `zstd` and `bzip2`); reading a compressed file without the
+matching feature returns an error.
+
+```
+use sacio::Sac;
+use sacio::Compression;
+# use sacio::SacError;
+
+let mut s = Sac::from_file("tests/file.sac")?;
+# #[cfg(feature = "gzip")] {
+s.to_file_compressed("tests/compressed.sac.gz", Compression::Gzip)?;
+let s2 = Sac::from_file("tests/compressed.sac.gz")?;
+assert_eq!(s.y, s2.y);
+# std::fs::remove_file("tests/compressed.sac.gz")?;
+# }
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use crate::Sac;
+use crate::SacError;
+
+/// Compression of SAC file output
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Compression {
+    /// Uncompressed
+    None,
+    /// gzip, requires the `gzip` feature
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// zstd, requires the `zstd` feature
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// bzip2, requires the `bzip2` feature
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+}
+
+/// Name of the compression format from its magic bytes
+fn magic(buf: &[u8]) -> Option<&'static str> {
+    if buf.starts_with(&[0x1f, 0x8b, 0x08]) {
+        Some("gzip")
+    } else if buf.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Some("zstd")
+    } else if buf.len() >= 4 && buf.starts_with(b"BZh") && (b'1'..=b'9').contains(&buf[3]) {
+        Some("bzip2")
+    } else {
+        None
+    }
+}
+
+/// Read a SAC file, decompressing if needed
+pub(crate) fn read<R: BufRead>(r: &mut R) -> Result<Sac, SacError> {
+    match magic(r.fill_buf()?) {
+        None => Sac::read(r),
+        #[cfg(feature = "gzip")]
+        Some("gzip") => Sac::read(&mut flate2::bufread::MultiGzDecoder::new(r)),
+        #[cfg(feature = "zstd")]
+        Some("zstd") => Sac::read(&mut zstd::Decoder::with_buffer(r)?),
+        #[cfg(feature = "bzip2")]
+        Some("bzip2") => Sac::read(&mut bzip2::bufread::MultiBzDecoder::new(r)),
+        Some(name) => Err(SacError::Format(format!("{} compressed file, enable the {} feature", name, name))),
+    }
+}
+
+/// Write a SAC file with compression
+pub(crate) fn write<P: AsRef<Path>>(s: &mut Sac, path: P, c: Compression) -> Result<(), SacError> {
+    let file = BufWriter::new(File::create(path)?);
+    match c {
+        Compression::None => {
+            let mut w = file;
+            s.write(&mut w)?;
+            w.flush()?;
+        },
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let mut w = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            s.write(&mut w)?;
+            w.finish()?.flush()?;
+        },
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let mut w = zstd::Encoder::new(file, 0)?;
+            s.write(&mut w)?;
+            w.finish()?.flush()?;
+        },
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => {
+            let mut w = bzip2::write::BzEncoder::new(file, bzip2::Compression::default());
+            s.write(&mut w)?;
+            w.finish()?.flush()?;
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magic_bytes() {
+        assert_eq!(magic(&[0x1f, 0x8b, 0x08, 0x00]), Some("gzip"));
+        assert_eq!(magic(&[0x28, 0xb5, 0x2f, 0xfd]), Some("zstd"));
+        assert_eq!(magic(b"BZh9"), Some("bzip2"));
+        assert_eq!(magic(b"BZhx"), None);
+        let buf = std::fs::read("tests/file.sac").unwrap();
+        assert_eq!(magic(&buf), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        #[allow(unused_mut)]
+        let mut kinds = vec![Compression::None];
+        #[cfg(feature = "gzip")]
+        kinds.push(Compression::Gzip);
+        #[cfg(feature = "zstd")]
+        kinds.push(Compression::Zstd);
+        #[cfg(feature = "bzip2")]
+        kinds.push(Compression::Bzip2);
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        for (i, c) in kinds.into_iter().enumerate() {
+            let path = std::env::temp_dir().join(format!("sacio_compress_{}.sac", i));
+            write(&mut s, &path, c).unwrap();
+            let s2 = Sac::from_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(s2.y, s.y);
+            assert_eq!(s2.delta(), s.delta());
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "gzip"))]
+    fn missing_feature() {
+        let mut buf = vec![0x1f, 0x8b, 0x08];
+        buf.extend(std::fs::read("tests/file.sac").unwrap());
+        let err = read(&mut std::io::Cursor::new(buf)).unwrap_err();
+        assert!(err.to_string().contains("gzip feature"));
+    }
+}
@@ -0,0 +1,574 @@
+/*! Import and export of CSS3.0 wfdisc databases
+
+A wfdisc table describes one waveform segment per row, pointing into
+binary `.w` files.  Reading a wfdisc creates a [`Sac`] for each row.
+The site, sitechan and origin tables are joined when present next to
+the wfdisc file, e.g. `db.site`, `db.sitechan` and `db.origin` for
+`db.wfdisc`.
+
+ Header   | CSS3.0
+ ---------|-------------------------------------------
+ nz*, b   | wfdisc time
+ delta    | 1 / wfdisc samprate
+ scale    | wfdisc calib
+ kstnm    | wfdisc sta
+ kcmpnm   | wfdisc chan
+ kinst    | wfdisc instype
+ nwfid    | wfdisc wfid
+ stla     | site lat
+ stlo     | site lon
+ stel     | site elev (km -> m)
+ stdp     | sitechan edepth (km -> m)
+ cmpaz    | sitechan hang
+ cmpinc   | sitechan vang
+ evla     | origin lat
+ evlo     | origin lon
+ evdp     | origin depth
+ o        | origin time
+ mag      | origin mb, ms or ml
+ norid    | origin orid
+ nevid    | origin evid
+
+Site and sitechan rows are matched by station, channel and the julian
+date of the waveform.  The origin used is the latest one occurring
+before the end of the waveform.
+
+Supported sample types are s4, t4, s2, t8 (big endian) and i4, f4, i2,
+f8 (little endian).
+
+```
+use sacio::Sac;
+use sacio::css;
+# use sacio::SacError;
+
+let s = Sac::from_file("tests/file.sac")?;
+let dir = std::env::temp_dir().join("sacio_css_doc");
+css::write(&[s.clone()], &dir, "db", &Default::default())?;
+
+let sacs = css::from_wfdisc(dir.join("db.wfdisc"))?;
+assert_eq!(sacs.len(), 1);
+assert_eq!(sacs[0].y, s.y);
+# std::fs::remove_dir_all(&dir)?;
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use byteorder::{BigEndian, LittleEndian, ByteOrder};
+use chrono::DateTime;
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDateTime;
+use chrono::Timelike;
+
+use crate::Sac;
+use crate::SacError;
+use crate::Marker;
+use crate::SacInt;
+use crate::SacMagnitudeType;
+use crate::SacString;
+use crate::SacZeroTime;
+use crate::fis;
+use crate::iis;
+
+/// Sample type of a `.w` file
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DataType {
+    /// 4-byte big endian integer
+    S4,
+    /// 2-byte big endian integer
+    S2,
+    /// 4-byte big endian float
+    T4,
+    /// 8-byte big endian float
+    T8,
+    /// 4-byte little endian integer
+    I4,
+    /// 2-byte little endian integer
+    I2,
+    /// 4-byte little endian float
+    F4,
+    /// 8-byte little endian float
+    F8,
+}
+
+impl DataType {
+    fn name(self) -> &'static str {
+        match self {
+            DataType::S4 => "s4",
+            DataType::S2 => "s2",
+            DataType::T4 => "t4",
+            DataType::T8 => "t8",
+            DataType::I4 => "i4",
+            DataType::I2 => "i2",
+            DataType::F4 => "f4",
+            DataType::F8 => "f8",
+        }
+    }
+    fn from_name(name: &str) -> Result<Self, SacError> {
+        match name {
+            "s4" => Ok(DataType::S4),
+            "s2" => Ok(DataType::S2),
+            "t4" => Ok(DataType::T4),
+            "t8" => Ok(DataType::T8),
+            "i4" => Ok(DataType::I4),
+            "i2" => Ok(DataType::I2),
+            "f4" => Ok(DataType::F4),
+            "f8" => Ok(DataType::F8),
+            _ => Err(bad(format!("Unsupported wfdisc datatype: {}", name))),
+        }
+    }
+    fn size(self) -> usize {
+        match self {
+            DataType::S2 | DataType::I2 => 2,
+            DataType::T8 | DataType::F8 => 8,
+            _ => 4,
+        }
+    }
+    fn decode(self, b: &[u8]) -> Vec<f32> {
+        b.chunks_exact(self.size()).map(|c| match self {
+            DataType::S4 => BigEndian::read_i32(c) as f32,
+            DataType::S2 => BigEndian::read_i16(c) as f32,
+            DataType::T4 => BigEndian::read_f32(c),
+            DataType::T8 => BigEndian::read_f64(c) as f32,
+            DataType::I4 => LittleEndian::read_i32(c) as f32,
+            DataType::I2 => LittleEndian::read_i16(c) as f32,
+            DataType::F4 => LittleEndian::read_f32(c),
+            DataType::F8 => LittleEndian::read_f64(c) as f32,
+        }).collect()
+    }
+    /// Encode samples, integer types require integer values within range
+    fn encode(self, y: &[f32]) -> Result<Vec<u8>, SacError> {
+        let mut out = vec![0u8; y.len() * self.size()];
+        for (c, &v) in out.chunks_exact_mut(self.size()).zip(y) {
+            let int = |lo: f32, hi: f32| -> Result<i32, SacError> {
+                if v.fract() != 0.0 || v < lo || v > hi {
+                    return Err(bad(format!("Value not representable as {}: {}", self.name(), v)));
+                }
+                Ok(v as i32)
+            };
+            match self {
+                DataType::S4 => BigEndian::write_i32(c, int(-2_147_483_648.0, 2_147_483_520.0)?),
+                DataType::S2 => BigEndian::write_i16(c, int(-32768.0, 32767.0)? as i16),
+                DataType::T4 => BigEndian::write_f32(c, v),
+                DataType::T8 => BigEndian::write_f64(c, f64::from(v)),
+                DataType::I4 => LittleEndian::write_i32(c, int(-2_147_483_648.0, 2_147_483_520.0)?),
+                DataType::I2 => LittleEndian::write_i16(c, int(-32768.0, 32767.0)? as i16),
+                DataType::F4 => LittleEndian::write_f32(c, v),
+                DataType::F8 => LittleEndian::write_f64(c, f64::from(v)),
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Options for writing wfdisc databases
+#[derive(Debug, Clone, Copy)]
+pub struct WriteOptions {
+    /// Sample type of the `.w` files, default t4
+    pub datatype: DataType,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions { datatype: DataType::T4 }
+    }
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+/// Fixed width column, 1-based and inclusive as in the schema
+fn col(line: &str, a: usize, b: usize) -> &str {
+    let b = b.min(line.len());
+    if a > b {
+        return "";
+    }
+    line.get(a - 1..b).unwrap_or("").trim()
+}
+
+fn num<T: std::str::FromStr>(line: &str, a: usize, b: usize, what: &str) -> Result<T, SacError> {
+    let v = col(line, a, b);
+    v.parse().map_err(|_| bad(format!("Invalid {}: {:?}", what, v)))
+}
+
+/// Optional value, CSS uses -999 as a null value
+fn opt(line: &str, a: usize, b: usize) -> Option<f64> {
+    col(line, a, b).parse().ok().filter(|v| *v != -999.0)
+}
+
+fn epoch(t: f64) -> Option<NaiveDateTime> {
+    let secs = t.floor();
+    let ns = ((t - secs) * 1e9).round() as i64;
+    DateTime::from_timestamp(secs as i64, 0)
+        .map(|d| d.naive_utc() + Duration::nanoseconds(ns))
+}
+
+fn to_epoch(t: NaiveDateTime) -> f64 {
+    let t = t.and_utc();
+    t.timestamp() as f64 + f64::from(t.timestamp_subsec_nanos()) / 1e9
+}
+
+fn jdate(t: NaiveDateTime) -> i32 {
+    t.year() * 1000 + t.ordinal() as i32
+}
+
+struct Site {
+    sta: String,
+    ondate: i32,
+    offdate: i32,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    elev: Option<f64>,
+}
+
+struct SiteChan {
+    sta: String,
+    chan: String,
+    ondate: i32,
+    offdate: i32,
+    edepth: Option<f64>,
+    hang: Option<f64>,
+    vang: Option<f64>,
+}
+
+struct Origin {
+    lat: Option<f64>,
+    lon: Option<f64>,
+    depth: Option<f64>,
+    time: f64,
+    orid: Option<i32>,
+    evid: Option<i32>,
+    mag: Option<(f64, SacMagnitudeType)>,
+}
+
+fn active(jd: i32, ondate: i32, offdate: i32) -> bool {
+    jd >= ondate && (offdate < 0 || jd <= offdate)
+}
+
+fn id(line: &str, a: usize, b: usize) -> Option<i32> {
+    col(line, a, b).parse().ok().filter(|v| *v >= 0)
+}
+
+fn parse_site(line: &str) -> Result<Site, SacError> {
+    Ok(Site {
+        sta: col(line, 1, 6).to_string(),
+        ondate: num(line, 8, 15, "site ondate")?,
+        offdate: num(line, 17, 24, "site offdate")?,
+        lat: opt(line, 26, 34),
+        lon: opt(line, 36, 44),
+        elev: opt(line, 46, 54),
+    })
+}
+
+fn parse_sitechan(line: &str) -> Result<SiteChan, SacError> {
+    Ok(SiteChan {
+        sta: col(line, 1, 6).to_string(),
+        chan: col(line, 8, 15).to_string(),
+        ondate: num(line, 17, 24, "sitechan ondate")?,
+        offdate: num(line, 35, 42, "sitechan offdate")?,
+        edepth: opt(line, 49, 57),
+        hang: opt(line, 59, 64).filter(|v| *v >= 0.0),
+        vang: opt(line, 66, 71).filter(|v| *v >= 0.0),
+    })
+}
+
+fn parse_origin(line: &str) -> Result<Origin, SacError> {
+    let mag = [(129, 135, SacMagnitudeType::BodyWave),
+               (146, 152, SacMagnitudeType::SurfaceWave),
+               (163, 169, SacMagnitudeType::Local)]
+        .iter()
+        .find_map(|&(a, b, t)| opt(line, a, b).map(|m| (m, t)));
+    Ok(Origin {
+        lat: opt(line, 1, 9),
+        lon: opt(line, 11, 19),
+        depth: opt(line, 21, 29),
+        time: num(line, 31, 47, "origin time")?,
+        orid: id(line, 49, 56),
+        evid: id(line, 58, 65),
+        mag,
+    })
+}
+
+/// Parse the rows of an optional table next to the wfdisc
+fn table<T, F>(wfdisc: &Path, ext: &str, parse: F) -> Result<Vec<T>, SacError>
+    where F: Fn(&str) -> Result<T, SacError>
+{
+    let path = wfdisc.with_extension(ext);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse)
+        .collect()
+}
+
+fn read_samples(path: &Path, foff: u64, n: usize, dt: DataType) -> Result<Vec<f32>, SacError> {
+    use std::io::{Seek, SeekFrom};
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(foff))?;
+    let mut buf = vec![0u8; n * dt.size()];
+    file.read_exact(&mut buf)?;
+    Ok(dt.decode(&buf))
+}
+
+fn row_to_sac(line: &str, base: &Path) -> Result<Sac, SacError> {
+    let time: f64 = num(line, 17, 33, "wfdisc time")?;
+    let nsamp: usize = num(line, 80, 87, "wfdisc nsamp")?;
+    let rate: f64 = num(line, 89, 99, "wfdisc samprate")?;
+    if rate <= 0.0 {
+        return Err(bad("Invalid wfdisc samprate"));
+    }
+    let dt = DataType::from_name(col(line, 144, 145))?;
+    let path = base.join(col(line, 149, 212)).join(col(line, 214, 245));
+    let foff: u64 = num(line, 247, 256, "wfdisc foff")?;
+    let y = read_samples(&path, foff, nsamp, dt)?;
+
+    let start = epoch(time).ok_or_else(|| bad("Invalid wfdisc time"))?;
+    let ms = start.with_nanosecond(start.nanosecond() / 1_000_000 * 1_000_000)
+        .unwrap_or(start);
+    let b = (start - ms).num_nanoseconds().unwrap_or(0) as f64 / 1e9;
+    let mut s = Sac::from_amp(y, b, 1.0 / rate);
+    s.set_time(ms);
+    s.set_zero_time_type(SacZeroTime::B);
+    s.set_string(SacString::Station, col(line, 1, 6));
+    s.set_string(SacString::Channel, col(line, 8, 15));
+    let inst = col(line, 135, 140);
+    if !inst.is_empty() && inst != "-" {
+        s.set_string(SacString::Instrument, inst);
+    }
+    if let Some(wfid) = id(line, 35, 42) {
+        s.set_id(SacInt::WaveformID, wfid);
+    }
+    if let Some(calib) = opt(line, 101, 116).filter(|v| *v != 0.0) {
+        s.scale = calib as f32;
+    }
+    Ok(s)
+}
+
+fn join(s: &mut Sac, end: f64, sites: &[Site], chans: &[SiteChan], origins: &[Origin])
+        -> Result<(), SacError> {
+    let t = s.time()?;
+    let jd = jdate(t);
+    let sta = s.string(SacString::Station).trim().to_string();
+    let cha = s.string(SacString::Channel).trim().to_string();
+    if let Some(site) = sites.iter().find(|x| x.sta == sta && active(jd, x.ondate, x.offdate)) {
+        if let (Some(lat), Some(lon)) = (site.lat, site.lon) {
+            s.stla = lat as f32;
+            s.stlo = lon as f32;
+        }
+        if let Some(elev) = site.elev {
+            s.stel = (elev * 1000.0) as f32;
+        }
+    }
+    if let Some(sc) = chans.iter().find(|x| x.sta == sta && x.chan == cha && active(jd, x.ondate, x.offdate)) {
+        if let Some(edepth) = sc.edepth {
+            s.stdp = (edepth * 1000.0) as f32;
+        }
+        if let Some(hang) = sc.hang {
+            s.cmpaz = hang as f32;
+        }
+        if let Some(vang) = sc.vang {
+            s.cmpinc = vang as f32;
+        }
+    }
+    let origin = origins.iter()
+        .filter(|o| o.time <= end)
+        .max_by(|a, b| a.time.total_cmp(&b.time));
+    if let Some(o) = origin {
+        if let (Some(lat), Some(lon)) = (o.lat, o.lon) {
+            s.evla = lat as f32;
+            s.evlo = lon as f32;
+        }
+        if let Some(depth) = o.depth {
+            s.evdp = depth as f32;
+        }
+        if let Some(ot) = epoch(o.time) {
+            s.o = ((ot - t).num_microseconds().unwrap_or(0) as f64 / 1e6) as f32;
+        }
+        if let Some(orid) = o.orid {
+            s.set_id(SacInt::OriginID, orid);
+        }
+        if let Some(evid) = o.evid {
+            s.set_id(SacInt::EventID, evid);
+        }
+        if let Some((mag, t)) = o.mag {
+            s.mag = mag as f32;
+            s.set_magnitude_type(t);
+        }
+    }
+    if fis(s.stla) && fis(s.stlo) && fis(s.evla) && fis(s.evlo) {
+        s.update_regions();
+        s.compute_dist_az();
+    }
+    Ok(())
+}
+
+/// Read all waveforms described by a wfdisc file
+///
+/// Data files are relative to the directory of the wfdisc file
+pub fn from_wfdisc<P: AsRef<Path>>(path: P) -> Result<Vec<Sac>, SacError> {
+    let path = path.as_ref();
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    let sites = table(path, "site", parse_site)?;
+    let chans = table(path, "sitechan", parse_sitechan)?;
+    let origins = table(path, "origin", parse_origin)?;
+    let mut sacs = vec![];
+    for line in std::fs::read_to_string(path)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut s = row_to_sac(line, base)?;
+        let end: f64 = num(line, 62, 78, "wfdisc endtime")?;
+        join(&mut s, end, &sites, &chans, &origins)?;
+        s.set_filename(&path.to_string_lossy());
+        sacs.push(s);
+    }
+    Ok(sacs)
+}
+
+fn css_str(s: &Sac, key: SacString, n: usize) -> String {
+    let v = s.string(key).trim();
+    if v.is_empty() || v == "-12345" {
+        return String::from("-");
+    }
+    v.get(..n).unwrap_or(v).to_string()
+}
+
+/// Fixed point value within `width` columns, with at most `prec` decimals
+fn fixed(v: f64, width: usize, prec: usize) -> String {
+    (0 ..= prec).rev()
+        .map(|p| format!("{:>w$.p$}", v, w = width, p = p))
+        .find(|f| f.len() <= width)
+        .unwrap_or_else(|| format!("{:>w$.0}", v, w = width))
+}
+
+/// Write SAC files as a wfdisc database
+///
+/// Writes `name.wfdisc` and one `name.<wfid>.w` file per trace into
+/// `dir`, which is created if needed.  The wfid is taken from `nwfid`
+/// when defined, or otherwise counts from 1.  Files must be time series
+/// and evenly spaced with a defined reference time.  The integer
+/// datatypes, s4, s2, i4 and i2, require integer valued data within range.
+pub fn write<P: AsRef<Path>>(sacs: &[Sac], dir: P, name: &str, opts: &WriteOptions)
+                             -> Result<(), SacError> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut wfdisc = String::new();
+    for (i, s) in sacs.iter().enumerate() {
+        if !s.is_time() || !s.evenly_spaced() {
+            return Err(SacError::NotEvenlySpaced);
+        }
+        let wfid = if iis(s.nwfid) { s.nwfid } else { i as i32 + 1 };
+        let dfile = format!("{}.{}.w", name, wfid);
+        File::create(dir.join(&dfile))?.write_all(&opts.datatype.encode(&s.y)?)?;
+
+        let start = s.datetime(Marker::B)?;
+        let t = to_epoch(start);
+        let rate = 1.0 / f64::from(s.delta());
+        let end = t + (s.npts() as f64 - 1.0).max(0.0) / rate;
+        let calib = if fis(s.scale) { f64::from(s.scale) } else { 1.0 };
+        let line = format!("{:<6} {:<8} {:>17.5} {:>8} {:>8} {:>8} {:>17.5} {:>8} {:>11} {:>16} {:>16.6} {:<6} {} {} {} {:<64} {:<32} {:>10} {:>8} {:<17}\n",
+                           css_str(s, SacString::Station, 6),
+                           css_str(s, SacString::Channel, 8),
+                           t, wfid, -1, jdate(start), end, s.npts(),
+                           fixed(rate, 11, 7), fixed(calib, 16, 6), 1.0,
+                           css_str(s, SacString::Instrument, 6),
+                           "o", opts.datatype.name(), "-",
+                           ".", dfile, 0, -1, "-");
+        wfdisc.push_str(&line);
+    }
+    std::fs::write(dir.join(format!("{}.wfdisc", name)), wfdisc)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datatypes() {
+        let y = vec![1.0, -2.0, 300.0, -32000.0];
+        for dt in &[DataType::S4, DataType::S2, DataType::T4, DataType::T8,
+                    DataType::I4, DataType::I2, DataType::F4, DataType::F8] {
+            assert_eq!(DataType::from_name(dt.name()).unwrap(), *dt);
+            assert_eq!(dt.decode(&dt.encode(&y).unwrap()), y);
+        }
+        assert_eq!(DataType::S4.encode(&[1.0]).unwrap(), vec![0, 0, 0, 1]);
+        assert_eq!(DataType::I4.encode(&[1.0]).unwrap(), vec![1, 0, 0, 0]);
+        for dt in &[DataType::S4, DataType::S2, DataType::I4, DataType::I2] {
+            assert!(dt.encode(&[0.5]).is_err());
+            assert!(dt.encode(&[3e9]).is_err());
+        }
+        assert!(DataType::S2.encode(&[40000.0]).is_err());
+        assert!(DataType::I2.encode(&[-32769.0]).is_err());
+    }
+
+    #[test]
+    fn high_sample_rate() {
+        let dir = std::env::temp_dir().join("sacio_css_rate_test");
+        for delta in [0.001, 1.0 / 1024.0, 0.0001] {
+            let mut s = Sac::from_amp(vec![1.0, 2.0, 3.0], 0.0, delta);
+            s.set_time(epoch(1.0e9).unwrap());
+            s.set_string(SacString::Station, "CDV");
+            write(&[s.clone()], &dir, "db", &Default::default()).unwrap();
+            let line = std::fs::read_to_string(dir.join("db.wfdisc")).unwrap();
+            let sacs = from_wfdisc(dir.join("db.wfdisc")).unwrap();
+            std::fs::remove_dir_all(&dir).unwrap();
+            assert_eq!(col(&line, 144, 145), WriteOptions::default().datatype.name());
+            assert_eq!(sacs[0].y, s.y);
+            assert_eq!(sacs[0].delta(), s.delta());
+        }
+    }
+
+    #[test]
+    fn round_trip_with_tables() {
+        let dir = std::env::temp_dir().join("sacio_css_test");
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_string(SacString::Channel, "BHZ");
+        s.set_id(SacInt::WaveformID, 42);
+        let opts = WriteOptions { datatype: DataType::S4 };
+        assert!(write(&[s.clone()], &dir, "db", &opts).is_err());
+        s.y.iter_mut().for_each(|v| *v = v.round());
+        write(&[s.clone()], &dir, "db", &opts).unwrap();
+        let t = to_epoch(s.datetime(Marker::B).unwrap());
+
+        let site = format!("{:<6} {:>8} {:>8} {:>9.4} {:>9.4} {:>9.4} {:<50} {:<4} {:<6} {:>9.4} {:>9.4} {:<17}\n",
+                           "CDV", 1970001, -1, 48.0, -120.0, 1.5, "Test station", "ss", "CDV", 0.0, 0.0, "-");
+        let sitechan = format!("{:<6} {:<8} {:>8} {:>8} {:>8} {:<4} {:>9.4} {:>6.1} {:>6.1} {:<50} {:<17}\n",
+                               "CDV", "BHZ", 1970001, 1, -1, "n", 0.01, -1.0, 0.0, "-", "-");
+        let origin = format!("{:>9.4} {:>9.4} {:>9.4} {:>17.5} {:>8} {:>8} {:>8} {:>4} {:>4} {:>4} {:>8} {:>8} {:<7} {:>9.4} {} {:>7.2} {:>8} {:>7.2} {:>8} {:>7.2} {:>8} {:<15} {:<15} {:>8} {:<17}\n",
+                             48.0, -125.0, 15.0, t - 20.0, 7, 3, 1981088, 0, 0, 0, -1, -1, "-", -999.0, "-",
+                             -999.0, -1, 4.5, -1, -999.0, -1, "-", "-", -1, "-");
+        std::fs::write(dir.join("db.site"), site).unwrap();
+        std::fs::write(dir.join("db.sitechan"), sitechan).unwrap();
+        std::fs::write(dir.join("db.origin"), origin).unwrap();
+
+        let sacs = from_wfdisc(dir.join("db.wfdisc")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sacs.len(), 1);
+        let s2 = &sacs[0];
+        assert_eq!(s2.y, s.y);
+        assert_eq!(s2.npts(), s.npts());
+        assert_eq!(s2.id(SacInt::WaveformID), 42);
+        assert_eq!(s2.id(SacInt::OriginID), 7);
+        assert_eq!(s2.id(SacInt::EventID), 3);
+        assert_eq!((s2.stla, s2.stlo, s2.stel, s2.stdp), (48.0, -120.0, 1500.0, 10.0));
+        assert_eq!((s2.cmpaz, s2.cmpinc), (crate::SAC_FLOAT_UNDEF, 0.0));
+        assert_eq!((s2.evla, s2.evlo, s2.evdp), (48.0, -125.0, 15.0));
+        assert_eq!(s2.mag, 4.5);
+        let o = s2.datetime(Marker::B).unwrap() - Duration::seconds(20);
+        let dt = s2.datetime(Marker::O).unwrap() - o;
+        assert!(dt.num_microseconds().unwrap().abs() < 100);
+        let mut s3 = s.clone();
+        s3.compute_dist_az();
+        assert!((s2.gcarc - s3.gcarc).abs() < 1e-3);
+    }
+}
@@ -9,8 +9,8 @@ Extra documentation for sacio
   depmax   | max_amp   | extrama_amp / calc_max_amp
   scale    | direct   | direct
   odelta   | direct   | direct
-  b        | b   | set_b
-  e        | e   | automatically computed
+  b        | b, start_time   | set_b
+  e        | e, end_time   | automatically computed
   o        | o   | set_o
   a        | direct   | direct
   fmt      | direct   | direct
@@ -37,12 +37,12 @@ Extra documentation for sacio
   resp9    | direct   | direct
   stla     | station_lat       | set_station_location
   stlo     | station_lon       | set_station_location
-  stel     | station_elevation | set_station_location
-  stdp     | direct            | unavailable
+  stel     | station_elevation | set_station_location, set_station_elevation
+  stdp     | station_depth     | set_station_depth
   evla     | event_lat         | set_event_location
   evlo     | event_lon         | set_event_location
-  evel     | direct            | unavailable
-  evdp     | event_depth       | set_event_location
+  evel     | event_elevation   | set_event_elevation
+  evdp     | event_depth       | set_event_location, set_event_depth
   mag      | direct   | direct
   user0    | direct   | direct
   user1    | direct   | direct
@@ -67,13 +67,13 @@ Extra documentation for sacio
   xmaximum | -   | -
   yminimum | -   | -
   ymaximum | -   | -
-  nzyear   | time   | set_time
-  nzjday   | time   | set_time
-  nzhour   | time   | set_time
-  nzmin    | time   | set_time
-  nzsec    | time   | set_time
-  nsmsec   | time   | set_time
-  nvhdr    | header_version   | -
+  nzyear   | time, time_utc   | set_time, set_time_utc, set_time_precise, set_reference_time, set_zero_to
+  nzjday   | time, time_utc   | set_time, set_time_utc, set_time_precise, set_reference_time, set_zero_to
+  nzhour   | time, time_utc   | set_time, set_time_utc, set_time_precise, set_reference_time, set_zero_to
+  nzmin    | time, time_utc   | set_time, set_time_utc, set_time_precise, set_reference_time, set_zero_to
+  nzsec    | time, time_utc   | set_time, set_time_utc, set_time_precise, set_reference_time, set_zero_to
+  nsmsec   | time, time_utc   | set_time, set_time_utc, set_time_precise, set_reference_time, set_zero_to
+  nvhdr    | version   | set_version
   norid    | int   | set_int
   nevid    | int   | set_int
   npts     | npts   | -
@@ -83,10 +83,10 @@ Extra documentation for sacio
   nysize   | -   | -
   iftype   | file_type   | set_file_type
   idep     | amp_type  | set_amp_type
-  iztype   | zero_time_type   | set_zero_time_type
+  iztype   | zero_time   | set_zero_time_type, set_zero_to
   iinst    | instrument_type  | set_intrument_type
-  istreg   | station_region   | update_regions
-  ievreg   | event_region   | update_regions
+  istreg   | station_region, station_region_name   | update_regions
+  ievreg   | event_region, event_region_name   | update_regions
   ievtyp   | event_type   | set_event_type
   iqual    | data_quality   | set_data_quality
   isynth   | synthetic   | set_synthetic
@@ -97,7 +97,7 @@ Extra documentation for sacio
   lovrok   | mutability   | set_mutability
   lcalda   | calc_dist_az   | set_calc_dist_az
   kstnm    | string | set_string
-  kevnm    |  string | set_string
+  kevnm    |  string | set_string, update_regions (if undefined or from the region)
   khole    |  string | set_string
   ko       |  string | set_string
   ka       |  string | set_string
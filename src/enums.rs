@@ -57,11 +57,12 @@ pub enum SacDataType {
 ///
 /// Present in the iftype value
 #[repr(i32)]
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
 pub enum SacFileType {
     //None   = IUNDEF,
     //Real   = 0,
     /// Time Series file
+    #[default]
     Time     = ITIME,
     /// Complex data: Real + Imaginary
     RealImag = IRLIM,
@@ -111,9 +112,148 @@ pub enum SacZeroTime {
     T9  = IT9,
 }
 
+/// Time markers
+///
+/// Header time values relative to the reference time, with the reference
+/// time itself and the start of its day.  Markers parse from their SAC
+/// names, e.g. `"t0".parse::<Marker>()`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Marker {
+    /// Reference time (z)
+    Z,
+    /// Start of the day of the reference time (day)
+    Day,
+    /// Begin time (b)
+    B,
+    /// End time (e)
+    E,
+    /// Origin time (o)
+    O,
+    /// First arrival (a)
+    A,
+    /// Event end (f)
+    F,
+    /// Timing mark 0 (t0)
+    T0,
+    /// Timing mark 1 (t1)
+    T1,
+    /// Timing mark 2 (t2)
+    T2,
+    /// Timing mark 3 (t3)
+    T3,
+    /// Timing mark 4 (t4)
+    T4,
+    /// Timing mark 5 (t5)
+    T5,
+    /// Timing mark 6 (t6)
+    T6,
+    /// Timing mark 7 (t7)
+    T7,
+    /// Timing mark 8 (t8)
+    T8,
+    /// Timing mark 9 (t9)
+    T9,
+}
+
+impl Marker {
+    /// String header holding the label of the marker
+    pub(crate) fn label(self) -> Option<SacString> {
+        match self {
+            Marker::Z | Marker::Day | Marker::B | Marker::E => None,
+            Marker::O  => Some(SacString::O),
+            Marker::A  => Some(SacString::A),
+            Marker::F  => Some(SacString::EventEnd),
+            Marker::T0 => Some(SacString::T0),
+            Marker::T1 => Some(SacString::T1),
+            Marker::T2 => Some(SacString::T2),
+            Marker::T3 => Some(SacString::T3),
+            Marker::T4 => Some(SacString::T4),
+            Marker::T5 => Some(SacString::T5),
+            Marker::T6 => Some(SacString::T6),
+            Marker::T7 => Some(SacString::T7),
+            Marker::T8 => Some(SacString::T8),
+            Marker::T9 => Some(SacString::T9),
+        }
+    }
+    /// Zero time equivalence (iztype) of the marker, `None` for the reference time
+    pub(crate) fn zero_time(self) -> Option<SacZeroTime> {
+        let z = match self {
+            Marker::Z  => return None,
+            Marker::Day => SacZeroTime::Day,
+            Marker::B  => SacZeroTime::B,
+            Marker::O  => SacZeroTime::O,
+            Marker::A  => SacZeroTime::A,
+            Marker::T0 => SacZeroTime::T0,
+            Marker::T1 => SacZeroTime::T1,
+            Marker::T2 => SacZeroTime::T2,
+            Marker::T3 => SacZeroTime::T3,
+            Marker::T4 => SacZeroTime::T4,
+            Marker::T5 => SacZeroTime::T5,
+            Marker::T6 => SacZeroTime::T6,
+            Marker::T7 => SacZeroTime::T7,
+            Marker::T8 => SacZeroTime::T8,
+            Marker::T9 => SacZeroTime::T9,
+            Marker::E | Marker::F => SacZeroTime::None,
+        };
+        Some(z)
+    }
+    /// Header markers, relative to the reference time
+    pub(crate) const HEADER: [Marker; 15] = [Marker::B, Marker::E, Marker::O, Marker::A,
+                                             Marker::F, Marker::T0, Marker::T1, Marker::T2,
+                                             Marker::T3, Marker::T4, Marker::T5, Marker::T6,
+                                             Marker::T7, Marker::T8, Marker::T9];
+    /// Timing marks t0-t9
+    pub const TIMING: [Marker; 10] = [Marker::T0, Marker::T1, Marker::T2, Marker::T3,
+                                      Marker::T4, Marker::T5, Marker::T6, Marker::T7,
+                                      Marker::T8, Marker::T9];
+}
+
+impl std::str::FromStr for Marker {
+    type Err = crate::SacError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let m = match s.trim().to_lowercase().as_str() {
+            "z"   => Marker::Z,
+            "day" => Marker::Day,
+            "b"   => Marker::B,
+            "e"   => Marker::E,
+            "o"   => Marker::O,
+            "a"   => Marker::A,
+            "f"   => Marker::F,
+            "t0"  => Marker::T0,
+            "t1"  => Marker::T1,
+            "t2"  => Marker::T2,
+            "t3"  => Marker::T3,
+            "t4"  => Marker::T4,
+            "t5"  => Marker::T5,
+            "t6"  => Marker::T6,
+            "t7"  => Marker::T7,
+            "t8"  => Marker::T8,
+            "t9"  => Marker::T9,
+            _ => return Err(crate::SacError::BadKey),
+        };
+        Ok(m)
+    }
+}
+
+/// Earth model for distance and azimuth
+///
+/// Used by [`Sac::compute_dist_az_with`](crate::Sac::compute_dist_az_with)
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub enum DistAzMethod {
+    /// Geodesic on the WGS84 ellipsoid
+    #[default]
+    Geodesic,
+    /// Spherical formulas with geocentric latitudes, as in SAC's `distaz`
+    ///
+    /// The distance in km is the geodesic on SAC's ellipsoid,
+    /// a = 6378.160 km and f = 1/298.25
+    Geocentric,
+}
+
 /// Type of Event
 ///
 /// Present in ievtyp
+#[derive(Debug, PartialEq, Copy, Clone)]
 #[repr(i32)]
 pub enum SacEventType {
     /// No Event Type
@@ -193,6 +333,7 @@ pub enum SacEventType {
 ///
 /// Present in iinst
 #[repr(i32)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum SacInstrument {
     /// Radial NTS
     RadNV = 23,
@@ -227,6 +368,7 @@ pub enum SacInstrument {
 /// Magnitude type
 ///
 /// Present in imagtyp
+#[derive(Debug, PartialEq, Copy, Clone)]
 #[repr(i32)]
 pub enum SacMagnitudeType {
     BodyWave     = 52,
@@ -237,7 +379,9 @@ pub enum SacMagnitudeType {
     UserDefined = 57,
 }
 /// Magnitude Source
+#[derive(Debug, PartialEq, Copy, Clone)]
 #[repr(i32)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum SacMagnitudeSource {
     NEIC = 58,
     PDEQ = 59,
@@ -366,10 +510,6 @@ impl From<SacInstrument> for i32 {
     }
 }
 
-impl Default for SacFileType {
-    fn default() -> SacFileType { SacFileType::Time }
-}
-
 impl From<i32> for SacDataType {
     fn from(t: i32) -> SacDataType {
         match t {
@@ -421,6 +561,28 @@ impl From<i32> for SacEventType {
             IQUARRY   => SacEventType::QuarryBlast,
             IQUARRY1  => SacEventType::QuarryBlast1,
             IQUARRY2  => SacEventType::QuarryBlast2,
+            75        => SacEventType::QuarryBlastSingle,
+            76        => SacEventType::QuarryTremor,
+            78        => SacEventType::EarthquakeSwarm,
+            79        => SacEventType::FeltEarthquake,
+            80        => SacEventType::MarineExplosion,
+            81        => SacEventType::Explosion,
+            83        => SacEventType::NuclearCavityCollapse,
+            84        => SacEventType::Unknown,
+            85        => SacEventType::LocalEvent,
+            86        => SacEventType::RegionalEvent,
+            87        => SacEventType::TeleseismicEvent,
+            88        => SacEventType::Undetermined,
+            89        => SacEventType::DamagingEarthquake,
+            90        => SacEventType::ProbableEarthquake,
+            91        => SacEventType::ProbableExplosion,
+            92        => SacEventType::MineCollapse,
+            93        => SacEventType::ProbableMineBlast,
+            94        => SacEventType::Geyser,
+            95        => SacEventType::Light,
+            96        => SacEventType::MeteoricEvent,
+            97        => SacEventType::Odors,
+            103       => SacEventType::OtherUnknown,
             _ => panic!("Unknown Event Type: {}", t),
         }
     }
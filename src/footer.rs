@@ -0,0 +1,100 @@
+//! Version 7 header footer
+//!
+//! Version 7 files store double precision copies of the time and location
+//! values after the data: delta, b, e, o, a, t0-t9, f, evlo, evla, stlo,
+//! stla, sb and sdelta.  The single precision values remain in the header.
+
+use std::io::{Read, Write};
+
+use byteorder::{NativeEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::fis;
+use crate::Marker;
+use crate::NonNativeEndian;
+use crate::SacError;
+use crate::SAC_FLOAT_UNDEF;
+
+/// Number of values in the footer
+const N: usize = 22;
+
+/// Position of delta in the footer
+pub(crate) const DELTA: usize = 0;
+
+/// Double precision header values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Footer(pub(crate) [f64; N]);
+
+impl Default for Footer {
+    fn default() -> Self {
+        Footer([SAC_FLOAT_UNDEF as f64; N])
+    }
+}
+
+/// Position of a marker in the footer
+pub(crate) fn index(m: Marker) -> Option<usize> {
+    let i = match m {
+        Marker::Z | Marker::Day => return None,
+        Marker::B  => 1,
+        Marker::E  => 2,
+        Marker::O  => 3,
+        Marker::A  => 4,
+        Marker::T0 => 5,
+        Marker::T1 => 6,
+        Marker::T2 => 7,
+        Marker::T3 => 8,
+        Marker::T4 => 9,
+        Marker::T5 => 10,
+        Marker::T6 => 11,
+        Marker::T7 => 12,
+        Marker::T8 => 13,
+        Marker::T9 => 14,
+        Marker::F  => 15,
+    };
+    Some(i)
+}
+
+/// Double precision value of a header value
+///
+/// The footer value is only used while it rounds to the header value,
+/// i.e. the header value has not been changed since
+pub(crate) fn precise(h: f32, p: f64) -> f64 {
+    if fis(h) && p as f32 == h {
+        p
+    } else {
+        h as f64
+    }
+}
+
+impl Footer {
+    /// Values to write, taken from the header unless more precise
+    pub(crate) fn merged(&self, header: [f32; N]) -> Footer {
+        let mut out = *self;
+        for (p, h) in out.0.iter_mut().zip(header.iter()) {
+            *p = precise(*h, *p);
+        }
+        out
+    }
+}
+
+/// Read the footer following the data
+pub(crate) fn read<R: Read>(file: &mut R, swap: bool) -> Result<Footer, SacError> {
+    let mut f = Footer::default();
+    if swap {
+        file.read_f64_into::<NonNativeEndian>(&mut f.0)?;
+    } else {
+        file.read_f64_into::<NativeEndian>(&mut f.0)?;
+    }
+    Ok(f)
+}
+
+/// Write the footer following the data
+pub(crate) fn write<W: Write>(file: &mut W, swap: bool, f: &Footer) -> Result<(), SacError> {
+    for &v in f.0.iter() {
+        if swap {
+            file.write_f64::<NonNativeEndian>(v)?;
+        } else {
+            file.write_f64::<NativeEndian>(v)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,669 @@
+/*! Conversion between GSE2.0 / IMS1.0 waveforms and SAC files
+
+Waveforms are read from WID2 sections of GSE2.0 or IMS1.0 messages,
+with or without the surrounding `BEGIN` / `STOP` message lines.
+Data may be stored as integers (INT) or compressed second differences
+in 6-bit (CM6) or 8-bit (CM8) form.  The CHK2 checksum of each section
+is verified on read.
+
+ Header   | WID2 / STA2
+ ---------|-------------------------------------------
+ nz*      | Date and time of the first sample
+ delta    | 1 / samprat
+ scale    | calib (nm/count)
+ resp0    | calper (s)
+ kstnm    | sta
+ kcmpnm   | chan
+ khole    | auxid
+ kinst    | instype
+ cmpaz    | hang
+ cmpinc   | vang
+ knetwk   | STA2 network
+ stla     | STA2 lat
+ stlo     | STA2 lon
+ stel     | STA2 elev (km -> m)
+ stdp     | STA2 edepth (km -> m)
+
+Samples are kept as counts, calib is only recorded in `scale`.
+The calibration period, calper, has no SAC counterpart and is kept in
+`resp0`, written as 1.0 when undefined.  Times are written to the
+nearest millisecond and samples must be integer valued.
+
+```
+use sacio::Sac;
+use sacio::gse;
+# use sacio::SacError;
+
+let mut s = Sac::from_file("tests/file.sac")?;
+s.y.iter_mut().for_each(|v| *v = v.round());
+let msg = gse::write(&[s.clone()], &Default::default())?;
+
+let sacs = gse::decode(&msg)?;
+assert_eq!(sacs.len(), 1);
+assert_eq!(sacs[0].npts(), s.npts());
+assert_eq!(sacs[0].string(sacio::SacString::Station), "CDV");
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::Duration;
+use chrono::NaiveDateTime;
+
+use crate::Sac;
+use crate::SacError;
+use crate::Marker;
+use crate::SacString;
+use crate::SacZeroTime;
+use crate::fis;
+
+const CM6: &[u8] = b"+-0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const MODULO: i64 = 100_000_000;
+const LINE: usize = 80;
+
+/// Data format of a WID2 section
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Compression {
+    /// Integers in ASCII
+    Int,
+    /// Second differences in 6-bit ASCII compression
+    Cm6,
+    /// Second differences in 8-bit binary compression
+    Cm8,
+}
+
+impl Compression {
+    fn name(self) -> &'static str {
+        match self {
+            Compression::Int => "INT",
+            Compression::Cm6 => "CM6",
+            Compression::Cm8 => "CM8",
+        }
+    }
+    fn from_name(name: &str) -> Result<Self, SacError> {
+        match name {
+            "INT" => Ok(Compression::Int),
+            "CM6" => Ok(Compression::Cm6),
+            "CM8" => Ok(Compression::Cm8),
+            _ => Err(bad(format!("Unsupported GSE data type: {}", name))),
+        }
+    }
+}
+
+/// Options for writing GSE2.0 / IMS1.0 messages
+#[derive(Debug, Clone, Copy)]
+pub struct WriteOptions {
+    /// Data format, default CM6
+    pub compression: Compression,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions { compression: Compression::Cm6 }
+    }
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+/// CHK2 checksum of integer samples
+pub fn checksum(y: &[i32]) -> i64 {
+    let mut sum = 0i64;
+    for &v in y {
+        sum = (sum + i64::from(v) % MODULO) % MODULO;
+    }
+    sum.abs()
+}
+
+fn diff2(y: &[i32]) -> Vec<i64> {
+    let (mut x1, mut d1) = (0i64, 0i64);
+    y.iter().map(|&v| {
+        let v = i64::from(v);
+        let d = v - x1;
+        let d2 = d - d1;
+        x1 = v;
+        d1 = d;
+        d2
+    }).collect()
+}
+
+fn undiff2(d: &[i64]) -> Result<Vec<i32>, SacError> {
+    let (mut d1, mut x) = (0i64, 0i64);
+    d.iter().map(|&v| {
+        d1 += v;
+        x += d1;
+        i32::try_from(x).map_err(|_| bad("GSE sample out of range"))
+    }).collect()
+}
+
+// Split |v| into the leading `first` bits and `rest` bit groups,
+//   most significant group first
+fn groups(v: i64, first: u32, rest: u32) -> Vec<u8> {
+    let mut v = v.unsigned_abs();
+    let mut out = vec![];
+    while v >> first != 0 {
+        out.push((v & ((1 << rest) - 1)) as u8);
+        v >>= rest;
+    }
+    out.push(v as u8);
+    out.reverse();
+    out
+}
+
+fn encode_cm6(d: &[i64]) -> Vec<u8> {
+    let mut out = vec![];
+    for &v in d {
+        let g = groups(v, 4, 5);
+        let n = g.len();
+        for (i, k) in g.into_iter().enumerate() {
+            let mut c = k;
+            if i == 0 && v < 0 {
+                c |= 16;
+            }
+            if i + 1 < n {
+                c |= 32;
+            }
+            out.push(CM6[c as usize]);
+        }
+    }
+    out
+}
+
+fn encode_cm8(d: &[i64]) -> Vec<u8> {
+    let mut out = vec![];
+    for &v in d {
+        let g = groups(v, 6, 7);
+        let n = g.len();
+        for (i, mut c) in g.into_iter().enumerate() {
+            if i == 0 && v < 0 {
+                c |= 0x40;
+            }
+            if i + 1 < n {
+                c |= 0x80;
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn cm6_value(c: u8) -> Option<i64> {
+    CM6.iter().position(|&x| x == c).map(|k| k as i64)
+}
+
+/// Decode `n` CM6 samples from `buf`, returning them and the bytes used
+fn decode_cm6(buf: &[u8], n: usize) -> Result<(Vec<i64>, usize), SacError> {
+    let mut out = Vec::with_capacity(n);
+    let mut chars = buf.iter().enumerate().filter(|(_, c)| !c.is_ascii_whitespace());
+    let mut used = 0;
+    let mut next = || -> Result<i64, SacError> {
+        let (i, &c) = chars.next().ok_or_else(|| bad("Truncated CM6 data"))?;
+        used = i + 1;
+        cm6_value(c).ok_or_else(|| bad(format!("Invalid CM6 character: {:?}", c as char)))
+    };
+    while out.len() < n {
+        let k = next()?;
+        let mut v = k & 15;
+        let mut more = k & 32 != 0;
+        while more {
+            let k = next()?;
+            more = k & 32 != 0;
+            v = (v << 5) | (k & 31);
+        }
+        out.push(if k & 16 != 0 { -v } else { v });
+    }
+    Ok((out, used))
+}
+
+/// Decode `n` CM8 samples from `buf`, returning them and the bytes used
+fn decode_cm8(buf: &[u8], n: usize) -> Result<(Vec<i64>, usize), SacError> {
+    let mut out = Vec::with_capacity(n);
+    let mut i = 0;
+    let mut next = || -> Result<i64, SacError> {
+        let c = *buf.get(i).ok_or_else(|| bad("Truncated CM8 data"))?;
+        i += 1;
+        Ok(i64::from(c))
+    };
+    while out.len() < n {
+        let k = next()?;
+        let mut v = k & 0x3f;
+        let mut more = k & 0x80 != 0;
+        while more {
+            let k = next()?;
+            more = k & 0x80 != 0;
+            v = (v << 7) | (k & 0x7f);
+        }
+        out.push(if k & 0x40 != 0 { -v } else { v });
+    }
+    Ok((out, i))
+}
+
+/// Decode `n` INT samples from `buf`, returning them and the bytes used
+fn decode_int(buf: &[u8], n: usize) -> Result<(Vec<i32>, usize), SacError> {
+    let mut out = Vec::with_capacity(n);
+    let mut i = 0;
+    while out.len() < n {
+        while i < buf.len() && buf[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let start = i;
+        while i < buf.len() && !buf[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if start == i {
+            return Err(bad("Truncated INT data"));
+        }
+        let word = String::from_utf8_lossy(&buf[start..i]);
+        let v = word.parse().map_err(|_| bad(format!("Invalid INT sample: {}", word)))?;
+        out.push(v);
+    }
+    Ok((out, i))
+}
+
+/// Fixed width column, 1-based and inclusive as in the format description
+fn col(line: &str, a: usize, b: usize) -> &str {
+    let b = b.min(line.len());
+    if a > b {
+        return "";
+    }
+    line.get(a - 1..b).unwrap_or("").trim()
+}
+
+fn num<T: std::str::FromStr>(line: &str, a: usize, b: usize, what: &str) -> Result<T, SacError> {
+    let v = col(line, a, b);
+    v.parse().map_err(|_| bad(format!("Invalid {}: {:?}", what, v)))
+}
+
+fn opt_num(line: &str, a: usize, b: usize) -> Option<f32> {
+    col(line, a, b).parse().ok()
+}
+
+struct Wid2 {
+    time: NaiveDateTime,
+    sta: String,
+    chan: String,
+    aux: String,
+    kind: Compression,
+    npts: usize,
+    rate: f64,
+    calib: Option<f32>,
+    calper: Option<f32>,
+    inst: String,
+    hang: Option<f32>,
+    vang: Option<f32>,
+}
+
+fn parse_wid2(line: &str) -> Result<Wid2, SacError> {
+    let t = format!("{} {}", col(line, 6, 15), col(line, 17, 28));
+    let time = NaiveDateTime::parse_from_str(&t, "%Y/%m/%d %H:%M:%S%.f")
+        .map_err(|_| bad(format!("Invalid WID2 time: {:?}", t)))?;
+    let rate: f64 = num(line, 58, 68, "WID2 sample rate")?;
+    if rate <= 0.0 {
+        return Err(bad("Invalid WID2 sample rate"));
+    }
+    Ok(Wid2 {
+        time,
+        sta: col(line, 30, 34).to_string(),
+        chan: col(line, 36, 38).to_string(),
+        aux: col(line, 40, 43).to_string(),
+        kind: Compression::from_name(col(line, 45, 47))?,
+        npts: num(line, 49, 56, "WID2 number of samples")?,
+        rate,
+        calib: opt_num(line, 70, 79),
+        calper: opt_num(line, 81, 87).filter(|v| *v > 0.0),
+        inst: col(line, 89, 94).to_string(),
+        hang: opt_num(line, 96, 100).filter(|v| *v >= 0.0),
+        vang: opt_num(line, 102, 105).filter(|v| *v >= 0.0),
+    })
+}
+
+fn wid2_to_sac(w: &Wid2, y: Vec<i32>) -> Sac {
+    let y = y.into_iter().map(|v| v as f32).collect();
+    let mut s = Sac::from_amp(y, 0.0, 1.0 / w.rate);
+    s.set_time(w.time);
+    s.set_zero_time_type(SacZeroTime::B);
+    s.set_string(SacString::Station, &w.sta);
+    s.set_string(SacString::Channel, &w.chan);
+    s.set_string(SacString::Location, &w.aux);
+    s.set_string(SacString::Instrument, &w.inst);
+    if let Some(calib) = w.calib {
+        s.scale = calib;
+    }
+    if let Some(calper) = w.calper {
+        s.resp0 = calper;
+    }
+    if let Some(hang) = w.hang {
+        s.cmpaz = hang;
+    }
+    if let Some(vang) = w.vang {
+        s.cmpinc = vang;
+    }
+    s
+}
+
+fn apply_sta2(s: &mut Sac, line: &str) {
+    let net = col(line, 6, 14);
+    if !net.is_empty() {
+        s.set_string(SacString::Network, net);
+    }
+    if let (Some(lat), Some(lon)) = (opt_num(line, 16, 24), opt_num(line, 26, 35)) {
+        s.stla = lat;
+        s.stlo = lon;
+    }
+    if let Some(elev) = opt_num(line, 50, 54) {
+        s.stel = elev * 1000.0;
+    }
+    if let Some(depth) = opt_num(line, 56, 60) {
+        s.stdp = depth * 1000.0;
+    }
+}
+
+/// Split off the next line of `buf` starting at `pos`
+fn next_line(buf: &[u8], pos: &mut usize) -> Option<String> {
+    if *pos >= buf.len() {
+        return None;
+    }
+    let rest = &buf[*pos..];
+    let n = rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+    *pos += (n + 1).min(rest.len());
+    let line = String::from_utf8_lossy(&rest[..n]);
+    Some(line.trim_end_matches('\r').to_string())
+}
+
+/// Decode all WID2 sections within a GSE2.0 / IMS1.0 message
+pub fn decode(buf: &[u8]) -> Result<Vec<Sac>, SacError> {
+    let mut sacs = vec![];
+    let mut pos = 0;
+    let mut cur: Option<Wid2> = None;
+    let mut sta2: Option<String> = None;
+    while let Some(line) = next_line(buf, &mut pos) {
+        if line.starts_with("WID2") {
+            cur = Some(parse_wid2(&line)?);
+            sta2 = None;
+        } else if line.starts_with("STA2") {
+            sta2 = Some(line);
+        } else if line.starts_with("DAT2") {
+            let w = cur.take().ok_or_else(|| bad("DAT2 without WID2"))?;
+            let data = &buf[pos..];
+            let (y, used) = match w.kind {
+                Compression::Int => decode_int(data, w.npts)?,
+                Compression::Cm6 => {
+                    let (d, used) = decode_cm6(data, w.npts)?;
+                    (undiff2(&d)?, used)
+                },
+                Compression::Cm8 => {
+                    let (d, used) = decode_cm8(data, w.npts)?;
+                    (undiff2(&d)?, used)
+                },
+            };
+            pos += used;
+            // Remainder of the last data line
+            next_line(buf, &mut pos);
+            let chk = loop {
+                match next_line(buf, &mut pos) {
+                    Some(l) if l.trim().is_empty() => continue,
+                    Some(l) if l.starts_with("CHK2") => break l,
+                    _ => return Err(bad("Missing CHK2 after DAT2")),
+                }
+            };
+            let expected: i64 = chk[4..].trim().parse()
+                .map_err(|_| bad(format!("Invalid CHK2 line: {:?}", chk)))?;
+            if expected != checksum(&y) {
+                return Err(bad(format!("CHK2 checksum mismatch for {}", w.sta)));
+            }
+            let mut s = wid2_to_sac(&w, y);
+            if let Some(l) = sta2.take() {
+                apply_sta2(&mut s, &l);
+            }
+            sacs.push(s);
+        }
+    }
+    Ok(sacs)
+}
+
+/// Read all WID2 sections from a reader
+pub fn read<R: Read>(reader: &mut R) -> Result<Vec<Sac>, SacError> {
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf)?;
+    decode(&buf)
+}
+
+/// Read all WID2 sections from a file
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Sac>, SacError> {
+    let file = File::open(&path)?;
+    let mut file = BufReader::new(file);
+    let mut sacs = read(&mut file)?;
+    for s in sacs.iter_mut() {
+        s.set_filename(&path.as_ref().to_string_lossy());
+    }
+    Ok(sacs)
+}
+
+/// Fortran style exponential, e.g. 1.00e+00
+fn exp_fmt(v: f32, prec: usize) -> String {
+    let s = format!("{:.*e}", prec, v);
+    match s.split_once('e') {
+        Some((m, e)) => {
+            let e: i32 = e.parse().unwrap_or(0);
+            format!("{}e{}{:02}", m, if e < 0 { '-' } else { '+' }, e.abs())
+        },
+        None => s,
+    }
+}
+
+/// Fixed point value within `width` columns, with at most `prec` decimals
+fn fixed(v: f32, width: usize, prec: usize, what: &str) -> Result<String, SacError> {
+    (0 ..= prec).rev()
+        .map(|p| format!("{:>w$.p$}", v, w = width, p = p))
+        .find(|f| f.len() <= width)
+        .ok_or_else(|| bad(format!("{} does not fit in GSE2.0: {}", what, v)))
+}
+
+fn trunc(s: &str, n: usize) -> &str {
+    let s = s.trim();
+    s.get(..n).unwrap_or(s)
+}
+
+fn defined(s: &Sac, key: SacString) -> &str {
+    let v = s.string(key).trim();
+    if v == "-12345" { "" } else { v }
+}
+
+fn write_one(s: &Sac, opts: &WriteOptions, out: &mut Vec<u8>) -> Result<(), SacError> {
+    if !s.is_time() || !s.evenly_spaced() {
+        return Err(SacError::NotEvenlySpaced);
+    }
+    let t0 = s.datetime(Marker::B)? + Duration::microseconds(500);
+    if !s.y.iter().all(|v| v.fract() == 0.0 && v.abs() < 2_147_483_648.0) {
+        return Err(bad("GSE data must be integer valued"));
+    }
+    let y: Vec<i32> = s.y.iter().map(|v| *v as i32).collect();
+    let calib = if fis(s.scale) { s.scale } else { 1.0 };
+    let calper = if fis(s.resp0) { s.resp0 } else { 1.0 };
+    let hang = if fis(s.cmpaz) { s.cmpaz } else { -1.0 };
+    let vang = if fis(s.cmpinc) { s.cmpinc } else { -1.0 };
+    let wid2 = format!("WID2 {} {:<5} {:<3} {:<4} {} {:>8} {:>11.6} {:>10} {} {:<6} {:>5.1} {:>4.1}\n",
+                       t0.format("%Y/%m/%d %H:%M:%S%.3f"),
+                       trunc(defined(s, SacString::Station), 5),
+                       trunc(defined(s, SacString::Channel), 3),
+                       trunc(defined(s, SacString::Location), 4),
+                       opts.compression.name(),
+                       y.len(),
+                       1.0 / s.delta(),
+                       exp_fmt(calib, 2),
+                       fixed(calper, 7, 3, "Calibration period")?,
+                       trunc(defined(s, SacString::Instrument), 6),
+                       hang, vang);
+    out.extend_from_slice(wid2.as_bytes());
+    if fis(s.stla) && fis(s.stlo) {
+        let elev = if fis(s.stel) { s.stel / 1000.0 } else { 0.0 };
+        let depth = if fis(s.stdp) { s.stdp / 1000.0 } else { 0.0 };
+        // Elevations below sea level and depths of 10 km or more use
+        // fewer decimals to stay within the f5.3 columns
+        let sta2 = format!("STA2 {:<9} {:>9.5} {:>10.5} {:<12} {} {}\n",
+                           trunc(defined(s, SacString::Network), 9),
+                           s.stla, s.stlo, "WGS-84",
+                           fixed(elev, 5, 3, "Station elevation")?,
+                           fixed(depth, 5, 3, "Station depth")?);
+        out.extend_from_slice(sta2.as_bytes());
+    }
+    out.extend_from_slice(b"DAT2\n");
+    match opts.compression {
+        Compression::Int => {
+            let mut line = String::new();
+            for v in &y {
+                let w = v.to_string();
+                if !line.is_empty() && line.len() + 1 + w.len() > LINE {
+                    out.extend_from_slice(line.as_bytes());
+                    out.push(b'\n');
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&w);
+            }
+            if !line.is_empty() {
+                out.extend_from_slice(line.as_bytes());
+                out.push(b'\n');
+            }
+        },
+        Compression::Cm6 => {
+            for chunk in encode_cm6(&diff2(&y)).chunks(LINE) {
+                out.extend_from_slice(chunk);
+                out.push(b'\n');
+            }
+        },
+        Compression::Cm8 => {
+            out.extend(encode_cm8(&diff2(&y)));
+            out.push(b'\n');
+        },
+    }
+    out.extend_from_slice(format!("CHK2 {:>8}\n\n", checksum(&y)).as_bytes());
+    Ok(())
+}
+
+/// Write SAC files as WID2 sections of an IMS1.0 waveform message
+///
+/// Files must be time series and evenly spaced with a defined reference time
+pub fn write(sacs: &[Sac], opts: &WriteOptions) -> Result<Vec<u8>, SacError> {
+    let mut out = vec![];
+    out.extend_from_slice(b"BEGIN IMS1.0\nMSG_TYPE DATA\nMSG_ID sacio\nDATA_TYPE WAVEFORM IMS1.0\n");
+    for s in sacs {
+        write_one(s, opts, &mut out)?;
+    }
+    out.extend_from_slice(b"STOP\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chk2() {
+        assert_eq!(checksum(&[1, 2, 3]), 6);
+        assert_eq!(checksum(&[-5, 2]), 3);
+        assert_eq!(checksum(&[99_999_999, 2]), 1);
+        assert_eq!(checksum(&[i32::MIN, i32::MAX]), 1);
+    }
+
+    #[test]
+    fn cm6() {
+        let y = vec![0, 1, -1, 15, 16, -16, 1000, -123_456, i32::MAX, i32::MIN, 7];
+        let d = diff2(&y);
+        assert_eq!(undiff2(&d).unwrap(), y);
+        let enc = encode_cm6(&d);
+        assert!(enc.iter().all(|c| CM6.contains(c)));
+        assert_eq!(decode_cm6(&enc, d.len()).unwrap(), (d.clone(), enc.len()));
+        let enc = encode_cm8(&d);
+        assert_eq!(decode_cm8(&enc, d.len()).unwrap(), (d, enc.len()));
+        // Single characters: value, sign bit and continuation bit
+        assert_eq!(encode_cm6(&[0, 1, -1, 16]), b"+-FUE".to_vec());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.y.iter_mut().for_each(|v| *v = (*v * 100.0).round());
+        s.set_string(SacString::Network, "XX");
+        s.set_string(SacString::Location, "00");
+        s.scale = 0.5;
+        s.resp0 = 20.0;
+        s.cmpaz = 90.0;
+        s.cmpinc = 90.0;
+        s.stdp = 10.0;
+        for c in &[Compression::Int, Compression::Cm6, Compression::Cm8] {
+            let buf = write(&[s.clone(), s.clone()], &WriteOptions { compression: *c }).unwrap();
+            let sacs = decode(&buf).unwrap();
+            assert_eq!(sacs.len(), 2);
+            let s2 = &sacs[1];
+            assert_eq!(s2.y, s.y);
+            assert_eq!(s2.delta(), s.delta());
+            let dt = s2.datetime(Marker::B).unwrap() - s.datetime(Marker::B).unwrap();
+            assert!(dt.num_microseconds().unwrap().abs() <= 500);
+            assert_eq!(s2.nslc(), s.nslc());
+            assert_eq!(s2.scale, 0.5);
+            assert_eq!(s2.resp0, 20.0);
+            assert_eq!((s2.cmpaz, s2.cmpinc), (90.0, 90.0));
+            assert_eq!((s2.stla, s2.stlo), (s.stla, s.stlo));
+            assert_eq!(s2.stdp, 10.0);
+        }
+    }
+
+    #[test]
+    fn sta2_columns() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.y.iter_mut().for_each(|v| *v = v.round());
+        s.stel = -500.0;
+        s.stdp = 12000.0;
+        let buf = write(&[s.clone()], &Default::default()).unwrap();
+        let msg = String::from_utf8(buf.clone()).unwrap();
+        let sta2 = msg.lines().find(|l| l.starts_with("STA2")).unwrap();
+        assert_eq!(&sta2[49..60], "-0.50 12.00");
+        let s2 = &decode(&buf).unwrap()[0];
+        assert_eq!((s2.stel, s2.stdp), (-500.0, 12000.0));
+
+        s.stdp = 1.0e9;
+        assert!(matches!(write(&[s], &Default::default()), Err(SacError::Format(_))));
+    }
+
+    #[test]
+    fn integer_data() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        assert!(matches!(write(&[s.clone()], &Default::default()), Err(SacError::Format(_))));
+        s.y.iter_mut().for_each(|v| *v = v.round());
+        s.y[0] = 3e9;
+        assert!(matches!(write(&[s.clone()], &Default::default()), Err(SacError::Format(_))));
+        s.y[0] = 0.0;
+        let s2 = &decode(&write(&[s.clone()], &Default::default()).unwrap()).unwrap()[0];
+        assert_eq!(s2.y, s.y);
+        assert_eq!(s2.resp0, 1.0);
+    }
+
+    #[test]
+    fn read_int() {
+        let msg = b"WID2 2020/01/02 03:04:05.678 ABC   BHZ      INT        5   20.000000   2.00e+00   1.000 STS-2   -1.0  0.0\r\n\
+                    DAT2\r\n1 2\r\n3 -4 5\r\nCHK2        7\r\n";
+        let sacs = decode(msg).unwrap();
+        assert_eq!(sacs.len(), 1);
+        let s = &sacs[0];
+        assert_eq!(s.y, vec![1.0, 2.0, 3.0, -4.0, 5.0]);
+        assert_eq!(s.delta(), 0.05);
+        assert_eq!(s.scale, 2.0);
+        assert_eq!(s.resp0, 1.0);
+        assert_eq!(s.cmpinc, 0.0);
+        assert!(!fis(s.cmpaz));
+        assert_eq!(s.string(SacString::Instrument).trim(), "STS-2");
+        assert_eq!(s.datetime(Marker::B).unwrap().format("%H:%M:%S%.3f").to_string(), "03:04:05.678");
+
+        let bad = String::from_utf8_lossy(msg).replace("CHK2        7", "CHK2        8");
+        assert!(decode(bad.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Header access by SAC field name, e.g. "delta" or "kstnm"
+
+// Each of the serde, capi, python and wasm features uses only part of this module
+#![allow(dead_code)]
+
+use crate::Sac;
+
+/// SAC names of the enumerated header values
+const ENUMS: &[(i32, &str)] = &[
+    (1, "ITIME"), (2, "IRLIM"), (3, "IAMPH"), (4, "IXY"), (5, "IUNKN"),
+    (6, "IDISP"), (7, "IVEL"), (8, "IACC"), (9, "IB"), (10, "IDAY"),
+    (11, "IO"), (12, "IA"), (13, "IT0"), (14, "IT1"), (15, "IT2"),
+    (16, "IT3"), (17, "IT4"), (18, "IT5"), (19, "IT6"), (20, "IT7"),
+    (21, "IT8"), (22, "IT9"), (23, "IRADNV"), (24, "ITANNV"), (25, "IRADEV"),
+    (26, "ITANEV"), (27, "INORTH"), (28, "IEAST"), (29, "IHORZA"), (30, "IDOWN"),
+    (31, "IUP"), (32, "ILLLBB"), (33, "IWWSN1"), (34, "IWWSN2"), (35, "IHGLP"),
+    (36, "ISRO"), (37, "INUCL"), (38, "IPREN"), (39, "IPOSTN"), (40, "IQUAKE"),
+    (41, "IPREQ"), (42, "IPOSTQ"), (43, "ICHEM"), (44, "IOTHER"), (45, "IGOOD"),
+    (46, "IGLCH"), (47, "IDROP"), (48, "ILOWSN"), (49, "IRLDTA"), (50, "IVOLTS"),
+    (51, "IXYZ"), (52, "IMB"), (53, "IMS"), (54, "IML"), (55, "IMW"),
+    (56, "IMD"), (57, "IMX"), (58, "INEIC"), (59, "IPDEQ"), (60, "IPDEW"),
+    (61, "IPDE"), (62, "IISC"), (63, "IREB"), (64, "IUSGS"), (65, "IBRK"),
+    (66, "ICALTECH"), (67, "ILLNL"), (68, "IEVLOC"), (69, "IJSOP"), (70, "IUSER"),
+    (71, "IUNKNOWN"), (72, "IQB"), (73, "IQB1"), (74, "IQB2"), (75, "IQBX"),
+    (76, "IQMT"), (77, "IEQ"), (78, "IEQ1"), (79, "IEQ2"), (80, "IME"),
+    (81, "IEX"), (82, "INU"), (83, "INC"), (84, "IO_"), (85, "IL"),
+    (86, "IR"), (87, "IT"), (88, "IU"), (89, "IEQ3"), (90, "IEQ0"),
+    (91, "IEX0"), (92, "IQC"), (93, "IQB0"), (94, "IGEY"), (95, "ILIT"),
+    (96, "IMET"), (97, "IODOR"), (103, "IOS"),
+];
+
+/// Integer header values holding enumerated values
+const ENUM_KEYS: &[&str] = &[
+    "iftype", "idep", "iztype", "iinst", "ievtyp",
+    "iqual", "isynth", "imagtyp", "imagsrc",
+];
+
+/// Logical header values
+const LOGICAL_KEYS: &[&str] = &["leven", "lpspol", "lovrok", "lcalda"];
+
+/// Kind of an integer header value
+#[derive(Debug, PartialEq)]
+pub(crate) enum Kind {
+    Int,
+    Enum,
+    Logical,
+    Unused,
+}
+
+/// Kind of an integer header value from its name
+pub(crate) fn kind(key: &str) -> Kind {
+    if key.starts_with("unused") {
+        Kind::Unused
+    } else if ENUM_KEYS.contains(&key) {
+        Kind::Enum
+    } else if LOGICAL_KEYS.contains(&key) {
+        Kind::Logical
+    } else {
+        Kind::Int
+    }
+}
+
+/// SAC name of an enumerated value
+pub(crate) fn enum_name(v: i32) -> Option<&'static str> {
+    ENUMS.iter().find(|(k, _)| *k == v).map(|(_, name)| *name)
+}
+
+/// Enumerated value from its SAC name
+pub(crate) fn enum_value(name: &str) -> Option<i32> {
+    ENUMS.iter().find(|(_, n)| n.eq_ignore_ascii_case(name)).map(|(k, _)| *k)
+}
+
+macro_rules! field_ref {
+    ($s:ident, $k:ident, $t:ty, $($x:ident),*) => (
+        return match $k { $( stringify!($x) => Some(&$s.$x), )* _ => None }
+    );
+}
+macro_rules! field_mut {
+    ($s:ident, $k:ident, $t:ty, $($x:ident),*) => (
+        return match $k { $( stringify!($x) => Some(&mut $s.$x), )* _ => None }
+    );
+}
+macro_rules! string_ref {
+    ($s:ident, $k:ident, $($x:ident),*) => (
+        return match $k { $( stringify!($x) => Some(&$s.$x), )* _ => None }
+    );
+}
+macro_rules! string_mut {
+    ($s:ident, $k:ident, $($x:ident),*) => (
+        return match $k { $( stringify!($x) => Some(&mut $s.$x), )* _ => None }
+    );
+}
+
+macro_rules! names {
+    ($s:ident, $v:ident, $t:ty, $($x:ident),*) => ( $v.extend_from_slice(&[$(stringify!($x)),*]); );
+}
+macro_rules! string_names {
+    ($s:ident, $v:ident, $($x:ident),*) => ( $v.extend_from_slice(&[$(stringify!($x)),*]); );
+}
+
+/// Names of the header values, without the unused values
+pub(crate) fn names() -> Vec<&'static str> {
+    let mut v = vec![];
+    sac_reals!(_s, v, names);
+    sac_ints!(_s, v, names);
+    sac_strings!(_s, v, string_names);
+    v.retain(|k| kind(k) != Kind::Unused);
+    v
+}
+
+/// Real header value
+pub(crate) fn real<'a>(s: &'a Sac, key: &str) -> Option<&'a f32> {
+    sac_reals!(s, key, field_ref);
+}
+/// Mutable real header value
+pub(crate) fn real_mut<'a>(s: &'a mut Sac, key: &str) -> Option<&'a mut f32> {
+    sac_reals!(s, key, field_mut);
+}
+/// Integer header value, including enumerated and logical values
+pub(crate) fn int<'a>(s: &'a Sac, key: &str) -> Option<&'a i32> {
+    sac_ints!(s, key, field_ref);
+}
+/// Mutable integer header value, including enumerated and logical values
+pub(crate) fn int_mut<'a>(s: &'a mut Sac, key: &str) -> Option<&'a mut i32> {
+    sac_ints!(s, key, field_mut);
+}
+/// String header value
+pub(crate) fn string<'a>(s: &'a Sac, key: &str) -> Option<&'a String> {
+    sac_strings!(s, key, string_ref);
+}
+/// Mutable string header value
+pub(crate) fn string_mut<'a>(s: &'a mut Sac, key: &str) -> Option<&'a mut String> {
+    sac_strings!(s, key, string_mut);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn by_name() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        assert_eq!(real(&s, "delta"), Some(&0.01));
+        assert_eq!(int(&s, "npts"), Some(&1000));
+        assert_eq!(string(&s, "kstnm").map(|v| v.trim()), Some("CDV"));
+        assert_eq!(real(&s, "npts"), None);
+        assert_eq!(int(&s, "stla"), None);
+        *real_mut(&mut s, "stla").unwrap() = 12.0;
+        assert_eq!(s.station_lat(), 12.0);
+        *string_mut(&mut s, "knetwk").unwrap() = "CI".to_string();
+        assert_eq!(s.string(crate::SacString::Network), "CI");
+        *int_mut(&mut s, "nevid").unwrap() = 3;
+        assert_eq!(s.id(crate::SacInt::EventID), 3);
+    }
+}
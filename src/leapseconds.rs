@@ -0,0 +1,128 @@
+/*! Leap seconds
+
+UTC days ending with a leap second, 23:59:60, and time differences that
+count them.  A leap second is represented as in chrono, second 59 with
+more than 10^9 nanoseconds.
+
+```
+use sacio::leapseconds;
+use chrono::{Duration, NaiveDate};
+
+let day = NaiveDate::from_ymd_opt(2016, 12, 31).unwrap();
+assert!(leapseconds::is_leap_day(day));
+
+let a = day.and_hms_opt(23, 59, 59).unwrap();
+let b = NaiveDate::from_ymd_opt(2017, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+assert_eq!(leapseconds::elapsed(a, b), Duration::seconds(2));
+
+// 23:59:60.5
+let t = leapseconds::add_elapsed(a, Duration::milliseconds(1500));
+assert_eq!(t, day.and_hms_milli_opt(23, 59, 59, 1500).unwrap());
+```
+
+Times before 1972 are treated as UTC without leap seconds.  The table
+ends with the leap second of 2016-12-31.
+*/
+
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::Timelike;
+
+/// Days ending with a leap second, IERS Bulletin C
+const LEAP_DAYS: [(i32, u32, u32); 27] = [
+    (1972,  6, 30), (1972, 12, 31), (1973, 12, 31), (1974, 12, 31),
+    (1975, 12, 31), (1976, 12, 31), (1977, 12, 31), (1978, 12, 31),
+    (1979, 12, 31), (1981,  6, 30), (1982,  6, 30), (1983,  6, 30),
+    (1985,  6, 30), (1987, 12, 31), (1989, 12, 31), (1990, 12, 31),
+    (1992,  6, 30), (1993,  6, 30), (1994,  6, 30), (1995, 12, 31),
+    (1997,  6, 30), (1998, 12, 31), (2005, 12, 31), (2008, 12, 31),
+    (2012,  6, 30), (2015,  6, 30), (2016, 12, 31),
+];
+
+fn leap_day(i: usize) -> NaiveDate {
+    let (y, m, d) = LEAP_DAYS[i];
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// End of the leap second `i`, midnight of the following day
+fn leap_end(i: usize) -> NaiveDateTime {
+    leap_day(i).succ_opt().unwrap().and_hms_opt(0, 0, 0).unwrap()
+}
+
+fn epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap()
+}
+
+/// If `day` ends with a leap second
+pub fn is_leap_day(day: NaiveDate) -> bool {
+    (0..LEAP_DAYS.len()).any(|i| leap_day(i) == day)
+}
+
+/// Number of leap seconds completed before `t`
+pub fn leap_seconds_before(t: NaiveDateTime) -> i64 {
+    (0..LEAP_DAYS.len()).filter(|&i| leap_end(i) <= t).count() as i64
+}
+
+/// Time since 1970 including leap seconds
+fn continuous(t: NaiveDateTime) -> Duration {
+    let days = (t.date() - epoch().date()).num_days();
+    Duration::days(days)
+        + Duration::seconds(t.num_seconds_from_midnight() as i64 + leap_seconds_before(t))
+        + Duration::nanoseconds(t.nanosecond() as i64)
+}
+
+/// Inverse of `continuous`
+fn from_continuous(c: Duration) -> NaiveDateTime {
+    let k = (0..LEAP_DAYS.len()).filter(|&i| continuous(leap_end(i)) <= c).count();
+    if k < LEAP_DAYS.len() {
+        let start = continuous(leap_end(k)) - Duration::seconds(1);
+        if c >= start {
+            let ns = (c - start).num_nanoseconds().unwrap() as u32;
+            return leap_day(k).and_hms_nano_opt(23, 59, 59, 1_000_000_000 + ns).unwrap();
+        }
+    }
+    epoch() + (c - Duration::seconds(k as i64))
+}
+
+/// Time from `from` to `to`, counting leap seconds
+pub fn elapsed(from: NaiveDateTime, to: NaiveDateTime) -> Duration {
+    continuous(to) - continuous(from)
+}
+
+/// Time `dt` after `t`, counting leap seconds
+pub fn add_elapsed(t: NaiveDateTime, dt: Duration) -> NaiveDateTime {
+    from_continuous(continuous(t) + dt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn leap_seconds() {
+        assert_eq!(leap_seconds_before(ymd(1971, 1, 1).and_hms_opt(0, 0, 0).unwrap()), 0);
+        assert_eq!(leap_seconds_before(ymd(2020, 1, 1).and_hms_opt(0, 0, 0).unwrap()), 27);
+        assert!(!is_leap_day(ymd(2016, 12, 30)));
+
+        let a = ymd(2016, 12, 31).and_hms_opt(23, 59, 50).unwrap();
+        let b = ymd(2017, 1, 1).and_hms_opt(0, 0, 9).unwrap();
+        assert_eq!(elapsed(a, b), Duration::seconds(20));
+        assert_eq!(elapsed(b, a), Duration::seconds(-20));
+        assert_eq!(add_elapsed(a, Duration::seconds(20)), b);
+        assert_eq!(add_elapsed(b, Duration::seconds(-20)), a);
+        let leap = ymd(2016, 12, 31).and_hms_milli_opt(23, 59, 59, 1_250).unwrap();
+        assert_eq!(add_elapsed(a, Duration::milliseconds(10_250)), leap);
+        assert_eq!(elapsed(a, leap), Duration::milliseconds(10_250));
+
+        // Several leap seconds, none before 1972
+        let a = ymd(1960, 1, 1).and_hms_opt(0, 0, 0).unwrap();
+        let b = ymd(1990, 1, 1).and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(elapsed(a, b), (b - a) + Duration::seconds(15));
+        assert_eq!(add_elapsed(a, elapsed(a, b)), b);
+    }
+}
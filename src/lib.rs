@@ -40,22 +40,35 @@ use std::fs::File;
 use std::io::BufReader;
 use std::io::BufWriter;
 use std::path::Path;
+use std::convert::TryFrom;
 use std::io::prelude::*;
-use geographiclib::Geodesic;
+use geographiclib_rs::Geodesic;
+use geographiclib_rs::InverseGeodesic;
 use chrono::Duration;
 use chrono::NaiveDateTime;
+use chrono::DateTime;
+use chrono::Utc;
+use chrono::TimeZone;
 use chrono::NaiveDate;
 use chrono::NaiveTime;
 use chrono::Datelike;
 use chrono::Timelike;
+use chrono::DurationRound;
 use byteorder::{BigEndian, LittleEndian, WriteBytesExt, ReadBytesExt, NativeEndian};
 
 mod enums;
-use enums::*;
 pub use enums::SacString;
 pub use enums::SacZeroTime;
 pub use enums::SacFileType;
 pub use enums::SacDataType;
+pub use enums::SacInt;
+pub use enums::SacEventType;
+pub use enums::SacMagnitudeType;
+pub use enums::SacMagnitudeSource;
+pub use enums::SacQuality;
+pub use enums::SacInstrument;
+pub use enums::Marker;
+pub use enums::DistAzMethod;
 
 #[cfg(target_endian = "big")]
 type NonNativeEndian = LittleEndian;
@@ -70,28 +83,155 @@ type __NativeEndian = LittleEndian;
 //const HEADER_SIZE : usize = 632;
 const SAC_INT_UNDEF : i32 = -12345;
 const SAC_FLOAT_UNDEF : f32 = -12345.0;
-const SAC_STRING_UNDEF : &'static str = "-12345  ";
+const SAC_STRING_UNDEF : &str = "-12345  ";
 
 #[inline]
 fn fis(x: f32) -> bool {
     x != SAC_FLOAT_UNDEF
 }
+/// Deepest and highest elevation accepted, m
+const MAX_DEPTH_M: f32 = 12000.0;
+const MAX_ELEVATION_M: f32 = 10000.0;
+/// Deepest event depth accepted, km
+const MAX_EVENT_DEPTH_KM: f32 = 1000.0;
+
+fn check_lat_lon(lat: f32, lon: f32) -> Result<(), SacError> {
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(SacError::BadLatitude);
+    }
+    if !(-360.0..=360.0).contains(&lon) {
+        return Err(SacError::BadLongitude);
+    }
+    Ok(())
+}
+fn check_elevation(elev: f32) -> Result<(), SacError> {
+    if !(-MAX_DEPTH_M..=MAX_ELEVATION_M).contains(&elev) {
+        return Err(SacError::BadElevation);
+    }
+    Ok(())
+}
+fn check_event_depth(depth: f32) -> Result<(), SacError> {
+    if !(-10.0..=MAX_EVENT_DEPTH_KM).contains(&depth) {
+        return Err(SacError::BadDepth);
+    }
+    Ok(())
+}
+
+/// Angle in degrees within [0, 360)
+fn azimuth(x: f64) -> f64 {
+    let x = x.rem_euclid(360.0);
+    if x >= 360.0 { 0.0 } else { x }
+}
+
+/// Distance (km), distance (deg), azimuth and back azimuth from an event to a station
+fn dist_az(evla: f64, evlo: f64, stla: f64, stlo: f64, method: DistAzMethod) -> (f64, f64, f64, f64) {
+    match method {
+        DistAzMethod::Geodesic => {
+            let g = Geodesic::wgs84();
+            let (s12, az1, az2, a12): (f64, f64, f64, f64) = g.inverse(evla, evlo, stla, stlo);
+            // az2 is the forward azimuth at the station, pointing away from the event
+            (s12 / 1000.0, a12, azimuth(az1), azimuth(az2 + 180.0))
+        },
+        DistAzMethod::Geocentric => {
+            let f = 1.0 / 298.25;
+            let g = Geodesic::new(6378160.0, f);
+            let s12: f64 = g.inverse(evla, evlo, stla, stlo);
+            let geocentric = |lat: f64| ((1.0 - f) * (1.0 - f) * lat.to_radians().tan()).atan();
+            let (e, s) = (geocentric(evla), geocentric(stla));
+            let dlon = (stlo - evlo).to_radians();
+            let (x, y) = (e.cos() * s.cos() * dlon.cos() + e.sin() * s.sin(),
+                          ((s.cos() * dlon.sin()).powi(2)
+                           + (e.cos() * s.sin() - e.sin() * s.cos() * dlon.cos()).powi(2)).sqrt());
+            let gcarc = y.atan2(x).to_degrees();
+            let az = (dlon.sin() * s.cos()).atan2(e.cos() * s.sin() - e.sin() * s.cos() * dlon.cos());
+            let back = (-dlon.sin() * e.cos()).atan2(s.cos() * e.sin() - s.sin() * e.cos() * dlon.cos());
+            (s12 / 1000.0, gcarc, azimuth(az.to_degrees()), azimuth(back.to_degrees()))
+        },
+    }
+}
+
 #[inline]
 fn iis(x: i32) -> bool {
     x != SAC_INT_UNDEF
 }
+#[inline]
+fn fopt(x: f32) -> Option<f32> {
+    if fis(x) { Some(x) } else { None }
+}
+#[inline]
+fn iopt(x: i32) -> Option<i32> {
+    if iis(x) { Some(x) } else { None }
+}
+#[inline]
+fn sopt(x: &str) -> Option<&str> {
+    let x = x.trim();
+    if x.is_empty() || x == SAC_STRING_UNDEF.trim() { None } else { Some(x) }
+}
 
 #[macro_use] mod macros;
 mod eq;
+mod footer;
+#[cfg(any(feature = "serde", feature = "capi", feature = "python", feature = "wasm"))]
+mod header;
 
 pub mod doc;
+pub mod compress;
+pub use compress::Compression;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "serde")]
+pub mod serialize;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+mod collection;
+pub use collection::SacCollection;
+pub use collection::SacSortKey;
+pub use collection::synchronize;
+
+#[cfg(feature = "plot")]
+pub mod plot;
+
+pub mod miniseed;
+pub mod segy;
+pub mod gse;
+pub mod css;
+pub mod response;
+pub mod resp;
+pub mod stationxml;
+pub mod quakeml;
+pub mod region;
+pub use region::Region;
+pub mod leapseconds;
 
 /// Value containing an absolute or relative time
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub enum TimeValue {
     /// Relative time in seconds
     Relative(Duration),
     /// Absolute time year-month-day, HH:MM:SS
     Absolute(NaiveDateTime),
+    /// Time relative to another marker, e.g. 10 s after the origin time
+    Marker(Marker, Duration),
+}
+
+impl From<DateTime<Utc>> for TimeValue {
+    fn from(t: DateTime<Utc>) -> Self {
+        TimeValue::Absolute(t.naive_utc())
+    }
+}
+
+/// Time marker value and its label
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pick {
+    /// Time relative to the reference time in seconds
+    pub time: f32,
+    /// Label, e.g. a phase name
+    pub label: Option<String>,
 }
 
 
@@ -130,11 +270,23 @@ fn sac_data_write<F: Write>(file: &mut F, s: &mut Sac, npts: usize) -> Result<()
     if npts != s.y.len() {
         panic!("Inconsistent Data: npts [{}] != data len [{}]", npts, s.y.len());
     }
-    if s.swap {
-        s.y.iter().map(|&y| file.write_f32::<NonNativeEndian>(y))
+    sac_data_write_comp(file, s.swap, &s.y)?;
+    if s.ncomps() == 2 {
+        if npts != s.x.len() {
+            panic!("Inconsistent Data: npts [{}] != data len [{}]", npts, s.x.len());
+        }
+        sac_data_write_comp(file, s.swap, &s.x)?;
+    }
+    Ok(())
+}
+
+/// Write a single data component
+fn sac_data_write_comp<F: Write>(file: &mut F, swap: bool, y: &[f32]) -> Result<(),SacError> {
+    if swap {
+        y.iter().map(|&y| file.write_f32::<NonNativeEndian>(y))
             .collect::<Result<Vec<()>,_>>()?;
     } else {
-        s.y.iter().map(|&y| file.write_f32::<NativeEndian>(y))
+        y.iter().map(|&y| file.write_f32::<NativeEndian>(y))
             .collect::<Result<Vec<()>,_>>()?;
     }
     Ok(())
@@ -187,8 +339,8 @@ fn sac_header_read<T: Read>(file: &mut T, h: &mut Sac) -> Result<(),SacError>{
         h.swap = false;
     } else {
         let v = swap_i32(h.nvhdr);
-        if v < 5 || v > 8 {
-            panic!("Unknown file type: {} {}", h.nvhdr, v);
+        if !(5..=8).contains(&v) {
+            return Err(SacError::UnknownFileType);
         }
         h.swap = true;
         sac_ints!(h, i32_swap);
@@ -233,8 +385,20 @@ pub enum SacError {
     BadLongitude,
     BadAzimuth,
     BadInclination,
+    BadDepth,
+    BadElevation,
     Io(std::io::Error),
     BadKey,
+    UnknownFileType,
+    BadPattern,
+    BadFilename,
+    Format(String),
+    NotEvenlySpaced,
+    MissingMetadata(String),
+    IncompleteTime(Vec<usize>),
+    Response(String),
+    #[cfg(feature = "plot")]
+    Plot(String),
 }
 
 impl std::fmt::Display for SacError {
@@ -247,7 +411,22 @@ impl std::fmt::Display for SacError {
             SacError::BadLongitude => write!(f, "Invalid Longitude value"),
             SacError::BadAzimuth => write!(f, "Invalid Azimuth value"),
             SacError::BadInclination => write!(f, "Invalid Inclination value"),
+            SacError::BadDepth => write!(f, "Invalid Depth value"),
+            SacError::BadElevation => write!(f, "Invalid Elevation value"),
             SacError::BadKey => write!(f, "Invalid key"),
+            SacError::UnknownFileType => write!(f, "Unknown file type"),
+            SacError::BadPattern => write!(f, "Invalid file pattern"),
+            SacError::BadFilename => write!(f, "Invalid filename"),
+            SacError::Format(e) => write!(f, "Invalid file format: {}", e),
+            SacError::NotEvenlySpaced => write!(f, "Data not evenly spaced"),
+            SacError::MissingMetadata(e) => write!(f, "No metadata found for {}", e),
+            SacError::IncompleteTime(v) => {
+                let v: Vec<_> = v.iter().map(|i| i.to_string()).collect();
+                write!(f, "Incomplete reference time for traces {}", v.join(", "))
+            },
+            SacError::Response(e) => write!(f, "Response error: {}", e),
+            #[cfg(feature = "plot")]
+            SacError::Plot(e) => write!(f, "Plot error: {}", e),
             SacError::Io(e) => write!(f, "{}", e),
         }
     }
@@ -261,34 +440,63 @@ impl From<std::io::Error> for SacError {
 }
 
 fn duration_to_f64(dt: Duration) -> f64 {
-    dt.num_seconds() as f64 + (dt.num_milliseconds() as f64 / 1_000.0)
+    match dt.num_nanoseconds() {
+        Some(ns) => ns as f64 / 1e9,
+        None => dt.num_milliseconds() as f64 / 1e3,
+    }
+}
+
+/// Time in seconds to the nearest nanosecond
+fn f64_to_duration(t: f64) -> Duration {
+    Duration::nanoseconds((t * 1e9).round() as i64)
 }
 
+/// Time from header values
+///
+/// Second 60 is a leap second, only valid at 23:59 on days ending with one
 fn time_from_parts(year: i32, doy: i32,
-                   hour: i32, min: i32, sec: i32, msec: i32) -> NaiveDateTime {
-    NaiveDateTime::new(NaiveDate::from_yo(year, doy as u32),
-                       NaiveTime::from_hms_milli(hour as u32,
-                                                 min as u32,
-                                                 sec as u32,
-                                                 msec as u32))
+                   hour: i32, min: i32, sec: i32, msec: i32) -> Option<NaiveDateTime> {
+    if !(0..1000).contains(&msec) {
+        return None;
+    }
+    let date = NaiveDate::from_yo_opt(year, u32::try_from(doy).ok()?)?;
+    let (sec, msec) = if sec == 60 {
+        if (hour, min) != (23, 59) || !leapseconds::is_leap_day(date) {
+            return None;
+        }
+        (59, msec + 1000)
+    } else {
+        (sec, msec)
+    };
+    let time = NaiveTime::from_hms_milli_opt(u32::try_from(hour).ok()?,
+                                             u32::try_from(min).ok()?,
+                                             u32::try_from(sec).ok()?,
+                                             msec as u32)?;
+    Some(NaiveDateTime::new(date, time))
 }
 
 /// Sac Implementation
 impl Sac {
     /// Read a sac file
     ///
+    /// Compressed files are decompressed when the matching feature is
+    /// enabled, see [`compress`]
+    ///
     /// ```
     /// use sacio::Sac;
     /// # use sacio::SacError;
     ///
     /// let s = Sac::from_file("tests/file.sac")?;
     /// assert_eq!(s.delta(), 0.01);
+    /// assert_eq!(s.filename(), "tests/file.sac");
     /// # Ok::<(), SacError>(())
     /// ```
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Sac,SacError> {
-        let file = File::open(path)?;
+        let file = File::open(&path)?;
         let mut file = BufReader::new(file);
-        Sac::read(&mut file)
+        let mut s = compress::read(&mut file)?;
+        s.file = path.as_ref().to_string_lossy().to_string();
+        Ok(s)
     }
     /// Read a sac file from a buffer
     ///
@@ -309,6 +517,9 @@ impl Sac {
         sac_header_read(buf, &mut s)?;
         sac_u8_to_strings(&mut s);
         sac_data_read(buf, &mut s)?;
+        if s.nvhdr == 7 {
+            s.footer = footer::read(buf, s.swap)?;
+        }
         Ok(s)
     }
     /// Write a sac file
@@ -333,6 +544,26 @@ impl Sac {
         let mut file = BufWriter::new(file);
         self.write(&mut file)
     }
+    /// Write a compressed sac file
+    ///
+    /// Available compression depends on the enabled features, see [`compress`]
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::Compression;
+    /// # use sacio::SacError;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// s.to_file_compressed("tests/to_file_compressed.sac", Compression::None)?;
+    ///
+    /// let s2 = Sac::from_file("tests/to_file_compressed.sac")?;
+    /// assert_eq!(s.y, s2.y);
+    /// # std::fs::remove_file("tests/to_file_compressed.sac")?;
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn to_file_compressed<P: AsRef<Path>>(&mut self, path: P, compression: Compression) -> Result<(),SacError> {
+        compress::write(self, path, compression)
+    }
     /// Write a sac file to a buffer
     ///
     /// ```
@@ -355,6 +586,34 @@ impl Sac {
         sac_strings_to_u8(self);
         sac_header_write(buf, self)?;
         sac_data_write(buf, self, npts)?;
+        if self.nvhdr == 7 {
+            let f = self.footer.merged(self.footer_header());
+            footer::write(buf, self.swap, &f)?;
+        }
+        Ok(())
+    }
+    /// Write a miniSEED file
+    ///
+    /// See [`miniseed::write`] for details
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::miniseed;
+    /// # use sacio::SacError;
+    ///
+    /// let s = Sac::from_file("tests/file.sac")?;
+    /// s.to_miniseed("tests/to_miniseed.mseed", &Default::default())?;
+    ///
+    /// let s2 = miniseed::from_file("tests/to_miniseed.mseed")?;
+    /// assert_eq!(s2[0].y, s.y);
+    /// assert_eq!(s2[0].delta(), s.delta());
+    /// # std::fs::remove_file("tests/to_miniseed.mseed")?;
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn to_miniseed<P: AsRef<Path>>(&self, path: P,
+                                       opts: &miniseed::WriteOptions) -> Result<(),SacError> {
+        let buf = miniseed::write(self, opts)?;
+        std::fs::write(path, buf)?;
         Ok(())
     }
     /// Determine if file is to be swapped on output
@@ -463,7 +722,7 @@ impl Sac {
     ///     assert!( ! s.is_real_imag() );
     ///
     pub fn is_real_imag(&self) -> bool {
-        self.iftype == SacFileType::RealImag.into()
+        self.iftype == i32::from(SacFileType::RealImag)
     }
     /// Check if file is a Amplitude/Phase Pair
     ///
@@ -472,7 +731,7 @@ impl Sac {
     ///     assert!( ! s.is_amp_phase() );
     ///
     pub fn is_amp_phase(&self) -> bool {
-        self.iftype == SacFileType::AmpPhase.into()
+        self.iftype == i32::from(SacFileType::AmpPhase)
     }
     /// Check if file is a time series file
     ///
@@ -481,7 +740,7 @@ impl Sac {
     ///     assert!( s.is_time() );
     ///
     pub fn is_time(&self) -> bool {
-        self.iftype == SacFileType::Time.into()
+        self.iftype == i32::from(SacFileType::Time)
     }
     /// Get File type (iftype)
     ///
@@ -555,6 +814,33 @@ impl Sac {
     pub fn version(&self) -> i32 {
         self.nvhdr
     }
+    /// Set Header Version, 6 or 7
+    ///
+    /// Version 7 files also store the time markers, sampling interval and
+    /// locations in double precision after the data
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// # use sacio::SacError;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// s.set_version(7)?;
+    /// let mut buf = vec![];
+    /// s.write(&mut buf)?;
+    /// assert_eq!(buf.len(), 632 + 4 * 1000 + 8 * 22);
+    ///
+    /// let s2 = Sac::read(&mut std::io::Cursor::new(&buf))?;
+    /// assert_eq!(s2.version(), 7);
+    /// assert!(s.set_version(5).is_err());
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn set_version(&mut self, version: i32) -> Result<(), SacError> {
+        if !(6..=7).contains(&version) {
+            return Err(SacError::Format(format!("Unsupported header version: {}", version)));
+        }
+        self.nvhdr = version;
+        Ok(())
+    }
     /// Get Reference Time
     ///
     /// ```
@@ -572,15 +858,18 @@ impl Sac {
     pub fn time(&self) -> Result<NaiveDateTime, SacError> {
         if iis(self.nzyear) && iis(self.nzjday) && iis(self.nzhour) &&
             iis(self.nzmin) && iis(self.nzsec) && iis(self.nzmsec) {
-                Ok(time_from_parts(self.nzyear, self.nzjday,
-                                   self.nzhour, self.nzmin, self.nzsec,
-                                   self.nzmsec))
+                time_from_parts(self.nzyear, self.nzjday,
+                                self.nzhour, self.nzmin, self.nzsec,
+                                self.nzmsec).ok_or(SacError::NotTime)
             } else {
                 Err(SacError::NotTime)
             }
     }
     /// Set Reference Time
     ///
+    /// The header stores the reference time to the millisecond; anything
+    /// finer is dropped, see [`Sac::set_time_precise`] to keep it
+    ///
     /// ```
     /// use sacio::Sac;
     /// # use sacio::SacError;
@@ -601,60 +890,314 @@ impl Sac {
         self.nzjday = time.ordinal() as i32;
         self.nzhour = time.hour() as i32;
         self.nzmin  = time.minute() as i32;
-        self.nzsec  = time.second() as i32;
-        self.nzmsec = time.nanosecond() as i32 / 1_000_000;
-    }
-
-    fn time_as_duration(&self, which: &str) -> Result<Duration, SacError> {
-        let t0 = match which {
-            "z" |
-            "b"    => self.b ,
-            "day"  => unimplemented!("Start of day timing"),
-            "o"    => self.o,
-            "a"    => self.a,
-            "e"    => self.e,
-            "t0"   => self.t0,
-            "t1"   => self.t1,
-            "t2"   => self.t2,
-            "t3"   => self.t3,
-            "t4"   => self.t4,
-            "t5"   => self.t5,
-            "t6"   => self.t6,
-            "t7"   => self.t7,
-            "t8"   => self.t8,
-            "t9"   => self.t9,
-            _ => return Err(SacError::BadKey),
-        };
-        if t0 == SAC_FLOAT_UNDEF {
-            return Err(SacError::NotTime);
-        }
-        Ok(Duration::seconds(t0.trunc() as i64) +
-            Duration::nanoseconds((t0.fract() * 1e9) as i64))
+        // Leap seconds are stored as second 60
+        let leap = time.nanosecond() >= 1_000_000_000;
+        self.nzsec  = time.second() as i32 + leap as i32;
+        self.nzmsec = (time.nanosecond() % 1_000_000_000) as i32 / 1_000_000;
     }
-
-    /// Get the Date and Time of a timing mark
+    /// Set Reference Time, keeping sub-millisecond precision
+    ///
+    /// Like [`Sac::set_time`], the markers keep their values relative to
+    /// the new reference time.  The part finer than a millisecond is added
+    /// to all defined markers.  Version 7 headers keep it to the nearest
+    /// nanosecond, version 6 headers to the single precision of the markers.
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// # use sacio::SacError;
+    /// use chrono::{Duration, NaiveDate};
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// s.set_version(7)?;
+    /// let t = NaiveDate::from_ymd_opt(2021, 3, 4).unwrap()
+    ///     .and_hms_nano_opt(5, 6, 7, 123_456_789).unwrap();
+    /// let b = Duration::nanoseconds((s.b() as f64 * 1e9).round() as i64);
+    /// s.set_time_precise(t)?;
+    ///
+    /// assert_eq!(s.time()?, t - Duration::nanoseconds(456_789));
+    /// assert_eq!(s.start_time()?, t + b);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn set_time_precise(&mut self, time: NaiveDateTime) -> Result<(), SacError> {
+        let ms = time.duration_trunc(Duration::milliseconds(1))
+            .map_err(|_| SacError::NotTime)?;
+        self.set_time(ms);
+        self.shift_markers(time - ms);
+        Ok(())
+    }
+    /// Time of the first sample, to the nanosecond
+    ///
+    /// Version 7 headers use the double precision begin time
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::Marker;
+    /// # use sacio::SacError;
+    ///
+    /// let s = Sac::from_file("tests/file.sac")?;
+    /// assert_eq!(s.start_time()?, s.datetime(Marker::B)?);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn start_time(&self) -> Result<NaiveDateTime, SacError> {
+        self.datetime(Marker::B)
+    }
+    /// Time of the last sample, to the nanosecond
+    ///
+    /// Evenly spaced time series use the begin time, sampling interval and
+    /// number of points, other data use the end time.  Version 7 headers
+    /// use the double precision values.
     ///
     /// ```
     /// use sacio::Sac;
     /// # use sacio::SacError;
     /// use chrono::Duration;
     ///
+    /// let s = Sac::from_file("tests/file.sac")?;
+    /// let dt = s.end_time()? - s.start_time()?;
+    /// // delta is 0.00999999977648258209228515625 as an f32
+    /// assert_eq!(dt, Duration::nanoseconds(9_989_999_777));
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn end_time(&self) -> Result<NaiveDateTime, SacError> {
+        let ft: SacFileType = self.iftype.into();
+        if self.evenly_spaced() && ft == SacFileType::Time && self.npts > 0 {
+            if !fis(self.delta) {
+                return Err(SacError::NotTime);
+            }
+            let dt = self.delta64() * (self.npts - 1) as f64;
+            Ok(self.add_time(self.start_time()?, f64_to_duration(dt)))
+        } else {
+            self.datetime(Marker::E)
+        }
+    }
+    /// Determine if absolute times count leap seconds
+    pub fn leap_seconds(&self) -> bool {
+        self.leap_seconds
+    }
+    /// Set if absolute times count leap seconds, off by default
+    ///
+    /// Relative times are elapsed seconds.  With leap seconds counted,
+    /// conversions between relative and absolute times, e.g.
+    /// [`Sac::datetime`] and [`Sac::set_b`] with an absolute time, include
+    /// any leap seconds in between, see [`leapseconds`].  The setting is
+    /// not stored in the file.
+    ///
+    /// ```
+    /// use sacio::{Sac, Marker, TimeValue};
+    /// # use sacio::SacError;
+    /// use chrono::{Duration, NaiveDate};
+    ///
+    /// let mut s = Sac::new();
+    /// let day = NaiveDate::from_ymd_opt(2016, 12, 31).unwrap();
+    /// s.set_time(day.and_hms_opt(23, 59, 50).unwrap());
+    /// s.set_b(TimeValue::Relative(Duration::seconds(20)))?;
+    /// let after = NaiveDate::from_ymd_opt(2017, 1, 1).unwrap();
+    /// assert_eq!(s.datetime(Marker::B)?, after.and_hms_opt(0, 0, 10).unwrap());
+    ///
+    /// s.set_leap_seconds(true);
+    /// assert_eq!(s.datetime(Marker::B)?, after.and_hms_opt(0, 0, 9).unwrap());
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn set_leap_seconds(&mut self, value: bool) {
+        self.leap_seconds = value;
+    }
+    /// Time `dt` after `t`
+    fn add_time(&self, t: NaiveDateTime, dt: Duration) -> NaiveDateTime {
+        if self.leap_seconds {
+            leapseconds::add_elapsed(t, dt)
+        } else {
+            t + dt
+        }
+    }
+    /// Time from `from` to `to`
+    fn time_diff(&self, from: NaiveDateTime, to: NaiveDateTime) -> Duration {
+        if self.leap_seconds {
+            leapseconds::elapsed(from, to)
+        } else {
+            to - from
+        }
+    }
+    /// Get Reference Time in UTC
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// # use sacio::SacError;
+    ///
+    /// let s = Sac::from_file("tests/file.sac")?;
+    /// assert_eq!(s.time_utc()?.naive_utc(), s.time()?);
+    /// assert_eq!(s.time_utc()?.to_rfc3339(), "1981-03-29T10:38:14+00:00");
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn time_utc(&self) -> Result<DateTime<Utc>, SacError> {
+        Ok(Utc.from_utc_datetime(&self.time()?))
+    }
+    /// Set Reference Time in UTC, see [`Sac::set_time`]
+    pub fn set_time_utc(&mut self, time: DateTime<Utc>) {
+        self.set_time(time.naive_utc())
+    }
+    /// Get the Date and Time of a timing mark in UTC, see [`Sac::datetime`]
+    ///
+    /// Absolute UTC times can also be used as a [`TimeValue`]
+    ///
+    /// ```
+    /// use sacio::{Sac, Marker};
+    /// # use sacio::SacError;
+    /// use chrono::Duration;
     ///
     /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// let t = s.time_utc()? + Duration::seconds(12);
+    /// s.set_marker_time(Marker::T0, t.into())?;
+    /// assert_eq!(s.datetime_utc(Marker::T0)?, t);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn datetime_utc(&self, m: Marker) -> Result<DateTime<Utc>, SacError> {
+        Ok(Utc.from_utc_datetime(&self.datetime(m)?))
+    }
+
+    /// Time of a marker relative to the reference time
+    fn time_as_duration(&self, m: Marker) -> Result<Duration, SacError> {
+        match m {
+            Marker::Z => Ok(Duration::zero()),
+            Marker::Day => {
+                let t = self.time()?;
+                Ok(self.time_diff(t, t.date().and_hms_opt(0, 0, 0).unwrap()))
+            },
+            _ => {
+                if !fis(self.marker_time(m)) {
+                    return Err(SacError::NotTime);
+                }
+                Ok(f64_to_duration(self.marker_time64(m)))
+            },
+        }
+    }
+
+    /// Get the Date and Time of a timing mark
+    ///
+    /// Marker values are converted to the nearest nanosecond
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::Marker;
+    /// # use sacio::SacError;
+    /// use chrono::{Duration, NaiveDate};
+    ///
+    /// let s = Sac::from_file("tests/file.sac")?;
     /// let dt = s.time()?;
-    /// let b = s.b();
     /// assert_eq!(s.b(), 9.459999);
-    /// let bt = dt + Duration::seconds(b.trunc() as i64) +
-    ///               Duration::nanoseconds((b.fract() * 1e9) as i64);
-    /// assert_eq!(s.datetime("b")?, bt);
-    /// assert!(s.datetime("t9").is_err());
+    /// // b is 9.45999908447265625 as an f32
+    /// assert_eq!(s.datetime(Marker::B)?, dt + Duration::nanoseconds(9_459_999_084));
+    /// assert_eq!(s.datetime(Marker::Z)?, dt);
+    /// let day = NaiveDate::from_yo_opt(1981, 88).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// assert_eq!(s.datetime(Marker::Day)?, day);
+    /// assert_eq!(s.datetime("b".parse()?)?, s.datetime(Marker::B)?);
+    /// assert!(s.datetime(Marker::T9).is_err());
     /// # Ok::<(), SacError>(())
     /// ```
     ///
-    pub fn datetime(&self, which: &str) -> Result<NaiveDateTime, SacError> {
+    pub fn datetime(&self, m: Marker) -> Result<NaiveDateTime, SacError> {
         let tref = self.time()?; // Absolute Reference time
-        let dt = self.time_as_duration(which)?;
-        Ok(tref + dt)
+        let dt = self.time_as_duration(m)?;
+        Ok(self.add_time(tref, dt))
+    }
+    /// Time from marker `from` to marker `to`
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::Marker;
+    /// # use sacio::SacError;
+    ///
+    /// let s = Sac::from_file("tests/file.sac")?;
+    /// let dt = s.time_between(Marker::B, Marker::E)?;
+    /// assert!((dt.num_microseconds().unwrap() - 9_990_000).abs() < 10);
+    /// let dt = s.time_between(Marker::Day, Marker::Z)?;
+    /// assert_eq!(dt.num_seconds(), 10 * 3600 + 38 * 60 + 14);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn time_between(&self, from: Marker, to: Marker) -> Result<Duration, SacError> {
+        Ok(self.time_as_duration(to)? - self.time_as_duration(from)?)
+    }
+    /// Change the reference time without moving the data
+    ///
+    /// All defined markers are shifted to keep their absolute times.  The
+    /// reference time is rounded to the nearest millisecond, the precision
+    /// of the header, and the zero time equivalence (iztype) becomes
+    /// undefined.  The current reference time must be defined.
+    ///
+    /// ```
+    /// use sacio::{Sac, Marker, SacZeroTime};
+    /// # use sacio::SacError;
+    /// use chrono::Duration;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// let b = s.datetime(Marker::B)?;
+    /// s.set_reference_time(s.time()? - Duration::seconds(60))?;
+    /// assert!((s.b() - (60.0 + 9.459999)).abs() < 1e-4);
+    /// assert!((s.datetime(Marker::B)? - b).num_microseconds().unwrap().abs() < 10);
+    /// assert_eq!(s.zero_time(), SacZeroTime::None);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn set_reference_time(&mut self, time: NaiveDateTime) -> Result<(), SacError> {
+        self.shift_reference_time(time)?;
+        self.iztype = SacZeroTime::None.into();
+        Ok(())
+    }
+    /// Set the reference time to a marker without moving the data
+    ///
+    /// Same as SAC's `ch o gmt ...; ch allt (0 - &1,o&) iztype io` for the
+    /// origin time.  All defined markers are shifted and the zero time
+    /// equivalence (iztype) is set to the marker; E and F have no
+    /// equivalent and leave it undefined.  The marker is zero to within
+    /// the millisecond precision of the reference time.
+    ///
+    /// ```
+    /// use sacio::{Sac, Marker, SacZeroTime, TimeValue};
+    /// # use sacio::SacError;
+    /// use chrono::Duration;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// s.set_o(TimeValue::Relative(Duration::milliseconds(-1250)))?;
+    /// let b = s.datetime(Marker::B)?;
+    /// s.set_zero_to(Marker::O)?;
+    /// assert_eq!(s.o(), 0.0);
+    /// assert!((s.b() - (1.25 + 9.459999)).abs() < 1e-4);
+    /// assert_eq!(s.zero_time(), SacZeroTime::O);
+    /// assert!((s.datetime(Marker::B)? - b).num_microseconds().unwrap().abs() < 10);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn set_zero_to(&mut self, m: Marker) -> Result<(), SacError> {
+        let time = self.datetime(m)?;
+        self.shift_reference_time(time)?;
+        if let Some(z) = m.zero_time() {
+            self.iztype = z.into();
+        }
+        Ok(())
+    }
+    fn shift_reference_time(&mut self, time: NaiveDateTime) -> Result<(), SacError> {
+        let old = self.time()?;
+        let time = time.duration_round(Duration::milliseconds(1))
+            .map_err(|_| SacError::NotTime)?;
+        self.set_time(time);
+        let shift = self.time_diff(old, self.time()?);
+        self.shift_markers(-shift);
+        Ok(())
+    }
+    /// Add `dt` to all defined markers
+    fn shift_markers(&mut self, dt: Duration) {
+        for &m in Marker::HEADER.iter() {
+            if fis(self.marker_time(m)) {
+                let t = f64_to_duration(self.marker_time64(m)) + dt;
+                self.set_marker64(m, duration_to_f64(t));
+            }
+        }
+    }
+    /// Time relative to the reference time
+    fn relative_time(&self, time: TimeValue) -> Result<Duration, SacError> {
+        match time {
+            TimeValue::Relative(v) => Ok(v),
+            // Requires knowledge of the reference time
+            TimeValue::Absolute(v) => Ok(self.time_diff(self.time()?, v)),
+            TimeValue::Marker(m, v) => Ok(self.time_as_duration(m)? + v),
+        }
     }
 
     /// Compute the maximum amplitude
@@ -733,6 +1276,29 @@ impl Sac {
         self.depmen = self.calc_mean_amp();
     }
 
+    /// Downsampled minimum and maximum envelope of the data
+    ///
+    /// Data are split into at most `n` bins of `ceil(npts / n)` samples,
+    /// returning the minimum and maximum of each bin, for plotting long
+    /// traces.  Bins start at `b + i * ceil(npts / n) * delta`.
+    ///
+    /// ```
+    /// use sacio::Sac;
+    ///
+    /// let s = Sac::from_amp(vec![0., 3., -1., 2., 5.], 0.0, 1.0);
+    /// assert_eq!(s.envelope(2), vec![(-1., 3.), (2., 5.)]);
+    /// assert_eq!(s.envelope(10).len(), 5);
+    /// ```
+    pub fn envelope(&self, n: usize) -> Vec<(f32, f32)> {
+        if n == 0 || self.y.is_empty() {
+            return vec![];
+        }
+        let size = self.y.len().div_ceil(n);
+        self.y.chunks(size)
+            .map(|c| c.iter().fold((c[0], c[0]), |(lo, hi), &v| (lo.min(v), hi.max(v))))
+            .collect()
+    }
+
     /// Compute and set extremas in x and y
     ///
     ///  File Type      | y-min   | y-max    | y-mean   | x-min      | x-max
@@ -753,8 +1319,10 @@ impl Sac {
         if self.evenly_spaced() {
             match self.iftype.into() {
                 SacFileType::Time |
-                SacFileType::XY =>
-                    self.e = self.b + self.delta * ((self.npts-1) as f32),
+                SacFileType::XY => {
+                    let e = self.marker_time64(Marker::B) + self.delta64() * ((self.npts-1) as f64);
+                    self.set_marker64(Marker::E, e);
+                },
                 SacFileType::RealImag |
                 SacFileType::AmpPhase => {
                     let nfreq = if self.npts % 2 == 0 {
@@ -766,7 +1334,7 @@ impl Sac {
                 },
                 SacFileType::XYZ => {},
             }
-        } else if self.x.len() > 0 {
+        } else if !self.x.is_empty() {
             let mut xmin = self.x[0];
             let mut xmax = self.x[0];
             for xi in self.x.iter() { if *xi < xmin { xmin = *xi; } }
@@ -831,7 +1399,7 @@ impl Sac {
     /// ```
     ///
     pub fn is_finite(&self) -> bool {
-        self.y.iter().all(|x| x.is_finite() == true)
+        self.y.iter().all(|x| x.is_finite())
     }
     /// Get Zero Time Equivalent
     /// ```
@@ -944,18 +1512,43 @@ impl Sac {
         self.idep = amp_type.into();
     }
     /// Compute Distance and Azimuth between station and event
+    ///
+    /// Uses the WGS84 geodesic, see [`Sac::compute_dist_az_with`]
     pub fn compute_dist_az(&mut self) {
+        self.compute_dist_az_with(DistAzMethod::Geodesic)
+    }
+    /// Compute Distance and Azimuth between station and event
+    ///
+    /// Following SAC's definitions
+    ///   - dist: distance in km
+    ///   - gcarc: distance in degrees
+    ///   - az: azimuth from the event to the station
+    ///   - baz: back azimuth, from the station to the event
+    ///
+    /// Azimuths are degrees clockwise from north in [0, 360).  Nothing is
+    /// computed unless lcalda is set and both locations are defined.
+    ///
+    /// ```
+    /// use sacio::{Sac, DistAzMethod};
+    /// # use sacio::SacError;
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// s.compute_dist_az_with(DistAzMethod::Geocentric);
+    /// assert!((s.az() - 88.1472).abs() < 1e-3);
+    /// assert!((s.baz() - 271.8528).abs() < 1e-3);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn compute_dist_az_with(&mut self, method: DistAzMethod) {
         if ! self.calc_dist_az() {
             return;
         }
         if fis(self.stlo) && fis(self.stla) && fis(self.evlo) && fis(self.evla) {
-            let g = Geodesic::wgs84();
-            let (a12, s12, az1, az2) = g.inverse(self.stla as f64, self.stlo as f64,
-                                                 self.evla as f64, self.evlo as f64);
-            self.gcarc = a12 as f32;
-            self.dist  = (s12 / 1000.0) as f32;
-            self.az    = az1 as f32;
-            self.baz   = az2 as f32;
+            let (dist, gcarc, az, back) = dist_az(self.evla as f64, self.evlo as f64,
+                                                 self.stla as f64, self.stlo as f64,
+                                                 method);
+            self.gcarc = gcarc as f32;
+            self.dist  = dist as f32;
+            self.az    = az as f32;
+            self.baz   = back as f32;
         }
     }
     /// Get event region
@@ -968,7 +1561,9 @@ impl Sac {
     }
     /// Update event and station regions
     ///
-    /// This assumes the station and event locations are defined
+    /// This assumes the station and event locations are defined.
+    /// The event name (kevnm) is set to the event region name if undefined
+    /// or previously set from the region, see [`Sac::event_region_name`]
     pub fn update_regions(&mut self) {
         use flinn_engdahl as fe;
         if fis(self.stlo) && fis(self.stla) {
@@ -983,6 +1578,7 @@ impl Sac {
                 self.ievreg = n as i32;
             }
         }
+        self.default_event_name();
     }
 
     /// Get Current filename
@@ -1106,21 +1702,26 @@ impl Sac {
 
     pub fn station_lat(&self) -> f32 { self.stla }
     pub fn station_lon(&self) -> f32 { self.stlo }
+    /// Get Station elevation in m
     pub fn station_elevation(&self) -> f32 { self.stel }
+    /// Get Station depth below the surface in m
+    pub fn station_depth(&self) -> f32 { self.stdp }
     pub fn event_lat(&self)   -> f32 { self.evla }
     pub fn event_lon(&self)   -> f32 { self.evlo }
+    /// Get Event depth in km
     pub fn event_depth(&self) -> f32 { self.evdp }
+    /// Get Event elevation in m
+    pub fn event_elevation(&self) -> f32 { self.evel }
     pub fn dist_km(&self) -> f32 { self.dist }
     pub fn dist_deg(&self) -> f32  { self.gcarc }
     pub fn az(&self) -> f32 { self.az }
     pub fn baz(&self) -> f32 { self.baz }
+    /// Set Station location
+    ///
+    /// Elevation is in m, between [-12000, 10000]
     pub fn set_station_location(&mut self, lat: f32, lon: f32, elev: f32) -> Result<(),SacError>{
-        if lat.abs() > 90.0 {
-            return Err(SacError::BadLatitude);
-        }
-        if lon.abs() > 360.0 {
-            return Err(SacError::BadLongitude);
-        }
+        check_lat_lon(lat, lon)?;
+        check_elevation(elev)?;
         self.stla = lat;
         self.stlo = lon;
         self.stel = elev;
@@ -1128,20 +1729,49 @@ impl Sac {
         self.compute_dist_az();
         Ok(())
     }
-    pub fn set_event_location(&mut self, lat: f32, lon: f32, depth: f32) -> Result<(),SacError>{
-        if lat.abs() > 90.0 {
-            return Err(SacError::BadLatitude);
-        }
-        if lon.abs() > 360.0 {
-            return Err(SacError::BadLongitude);
+    /// Set Station elevation in m, between [-12000, 10000]
+    pub fn set_station_elevation(&mut self, elev: f32) -> Result<(),SacError>{
+        check_elevation(elev)?;
+        self.stel = elev;
+        Ok(())
+    }
+    /// Set Station depth below the surface in m, e.g. for borehole sensors
+    ///
+    /// Accepted values are between [0, 12000]
+    pub fn set_station_depth(&mut self, depth: f32) -> Result<(),SacError>{
+        if !(0.0..=MAX_DEPTH_M).contains(&depth) {
+            return Err(SacError::BadDepth);
         }
+        self.stdp = depth;
+        Ok(())
+    }
+    /// Set Event location
+    ///
+    /// Depth is in km, between [-10, 1000]
+    pub fn set_event_location(&mut self, lat: f32, lon: f32, depth: f32) -> Result<(),SacError>{
+        check_lat_lon(lat, lon)?;
+        check_event_depth(depth)?;
         self.evla = lat;
         self.evlo = lon;
-        self.evel = depth;
+        self.evdp = depth;
         self.update_regions();
         self.compute_dist_az();
         Ok(())
     }
+    /// Set Event depth in km, between [-10, 1000]
+    ///
+    /// Depths in m are rejected, SAC used m for evdp before version 101.5
+    pub fn set_event_depth(&mut self, depth: f32) -> Result<(),SacError>{
+        check_event_depth(depth)?;
+        self.evdp = depth;
+        Ok(())
+    }
+    /// Set Event elevation in m, between [-12000, 10000]
+    pub fn set_event_elevation(&mut self, elev: f32) -> Result<(),SacError>{
+        check_elevation(elev)?;
+        self.evel = elev;
+        Ok(())
+    }
     /// Get Component Azimuth
     ///
     /// North is 0 degrees, with positive values rotating clockwise.
@@ -1178,7 +1808,7 @@ impl Sac {
     ///  Horizontal  | 90
     ///
     pub fn cmpinc(&self) -> f32 {
-        self.cmpaz
+        self.cmpinc
     }
     /// Set Component Inclination
     ///
@@ -1198,27 +1828,297 @@ impl Sac {
     pub fn o(&self) -> f32 { self.o }
 
     /// Set beginning time value
+    ///
+    /// The end time is updated
     pub fn set_b(&mut self, time: TimeValue) -> Result<(), SacError> {
-        match time {
-            TimeValue::Relative(v) => self.b = duration_to_f64(v) as f32,
-            TimeValue::Absolute(v) => {
-                // Requires knowledge of the reference time
-                let dt = self.time()? - v;
-                self.b = duration_to_f64(dt) as f32;
-            },
-        }
-        self.calc_be();
-        Ok(())
+        self.set_marker_time(Marker::B, time)
     }
     /// Set origin time value
     pub fn set_o(&mut self, time: TimeValue) -> Result<(), SacError> {
-        match time {
-            TimeValue::Relative(v) => self.o = duration_to_f64(v) as f32,
-            TimeValue::Absolute(v) => {
-                // Requires knowledge of the reference time
-                let dt = self.time()? - v;
-                self.o = duration_to_f64(dt) as f32;
-            }
+        self.set_marker_time(Marker::O, time)
+    }
+    /// Set a time marker from a relative, absolute or marker relative time
+    ///
+    /// Absolute and marker relative times require the reference time.
+    /// The reference time (Z) and start of day (Day) can not be set.
+    /// Setting the begin time also updates the end time, labels are kept.
+    ///
+    /// ```
+    /// use sacio::{Sac, Marker, TimeValue};
+    /// # use sacio::SacError;
+    /// use chrono::Duration;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// let p = s.time()? + Duration::milliseconds(12_250);
+    /// s.set_marker_time(Marker::T0, TimeValue::Absolute(p))?;
+    /// assert_eq!(s.marker(Marker::T0).unwrap().time, 12.25);
+    /// assert_eq!(s.datetime(Marker::T0)?, p);
+    ///
+    /// s.set_marker_time(Marker::A, TimeValue::Marker(Marker::T0, Duration::seconds(-2)))?;
+    /// assert_eq!(s.marker(Marker::A).unwrap().time, 10.25);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn set_marker_time(&mut self, m: Marker, time: TimeValue) -> Result<(), SacError> {
+        let dt = self.relative_time(time)?;
+        if self.marker_time_mut(m).is_none() {
+            return Err(SacError::BadKey);
+        }
+        self.set_marker64(m, duration_to_f64(dt));
+        if m == Marker::B {
+            self.calc_be();
+        }
+        Ok(())
+    }
+
+    /// Get Station latitude, if defined
+    pub fn try_station_lat(&self) -> Option<f32> { fopt(self.stla) }
+    /// Get Station longitude, if defined
+    pub fn try_station_lon(&self) -> Option<f32> { fopt(self.stlo) }
+    /// Get Station elevation, if defined
+    pub fn try_station_elevation(&self) -> Option<f32> { fopt(self.stel) }
+    /// Get Event latitude, if defined
+    pub fn try_event_lat(&self) -> Option<f32> { fopt(self.evla) }
+    /// Get Event longitude, if defined
+    pub fn try_event_lon(&self) -> Option<f32> { fopt(self.evlo) }
+    /// Get Station depth, if defined
+    pub fn try_station_depth(&self) -> Option<f32> { fopt(self.stdp) }
+    /// Get Event depth, if defined
+    pub fn try_event_depth(&self) -> Option<f32> { fopt(self.evdp) }
+    /// Get Event elevation, if defined
+    pub fn try_event_elevation(&self) -> Option<f32> { fopt(self.evel) }
+    /// Get Distance in km, if defined
+    pub fn try_dist_km(&self) -> Option<f32> { fopt(self.dist) }
+    /// Get Distance in degrees, if defined
+    pub fn try_dist_deg(&self) -> Option<f32> { fopt(self.gcarc) }
+    /// Get Azimuth, if defined
+    pub fn try_az(&self) -> Option<f32> { fopt(self.az) }
+    /// Get Back Azimuth, if defined
+    pub fn try_baz(&self) -> Option<f32> { fopt(self.baz) }
+    /// Get Component Azimuth, if defined
+    pub fn try_cmpaz(&self) -> Option<f32> { fopt(self.cmpaz) }
+    /// Get Component Inclination, if defined
+    pub fn try_cmpinc(&self) -> Option<f32> { fopt(self.cmpinc) }
+    /// Get Beginning time value, if defined
+    pub fn try_b(&self) -> Option<f32> { fopt(self.b) }
+    /// Get Ending time value, if defined
+    pub fn try_e(&self) -> Option<f32> { fopt(self.e) }
+    /// Get Origin time value, if defined
+    pub fn try_o(&self) -> Option<f32> { fopt(self.o) }
+    /// Get Magnitude, if defined
+    pub fn try_magnitude(&self) -> Option<f32> { fopt(self.mag) }
+    /// Get Mean amplitude value, if defined
+    pub fn try_mean_amp(&self) -> Option<f32> { fopt(self.depmen) }
+    /// Get Minimum amplitude value, if defined
+    pub fn try_min_amp(&self) -> Option<f32> { fopt(self.depmin) }
+    /// Get Maximum amplitude value, if defined
+    pub fn try_max_amp(&self) -> Option<f32> { fopt(self.depmax) }
+    /// Get event region, if defined
+    pub fn try_event_region(&self) -> Option<i32> { iopt(self.ievreg) }
+    /// Get station region, if defined
+    pub fn try_station_region(&self) -> Option<i32> { iopt(self.istreg) }
+    /// Get an identifier, if defined
+    pub fn try_id(&self, key: SacInt) -> Option<i32> { iopt(self.id(key)) }
+    /// Get Data Quality, if defined
+    pub fn try_data_quality(&self) -> Option<SacQuality> {
+        iopt(self.iqual).map(SacQuality::from)
+    }
+    /// Get Magnitude Type, if defined
+    pub fn try_magnitude_type(&self) -> Option<SacMagnitudeType> {
+        iopt(self.imagtyp).map(SacMagnitudeType::from)
+    }
+    /// Get Magnitude Source, if defined
+    pub fn try_magnitude_source(&self) -> Option<SacMagnitudeSource> {
+        iopt(self.imagsrc).map(SacMagnitudeSource::from)
+    }
+    /// Get Instrument Type, if defined
+    pub fn try_instrument_type(&self) -> Option<SacInstrument> {
+        iopt(self.iinst).map(SacInstrument::from)
+    }
+    /// Get a string value, if defined
+    ///
+    /// Leading and trailing whitespace is removed
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::SacString;
+    /// # use sacio::SacError;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// assert_eq!(s.string_opt(SacString::Station), Some("CDV"));
+    /// assert_eq!(s.string_opt(SacString::T1), None);
+    ///
+    /// s.set_string_opt(SacString::Station, None);
+    /// assert_eq!(s.string(SacString::Station), "-12345  ");
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn string_opt(&self, key: SacString) -> Option<&str> {
+        sopt(self.string(key))
+    }
+    /// Set a string value, `None` or an empty value is undefined
+    pub fn set_string_opt(&mut self, key: SacString, value: Option<&str>) {
+        match value.and_then(sopt) {
+            Some(v) => self.set_string(key, v),
+            None => self.set_string(key, SAC_STRING_UNDEF),
+        }
+    }
+    /// Set an identifier, `None` is undefined
+    pub fn set_id_opt(&mut self, key: SacInt, value: Option<i32>) {
+        self.set_id(key, value.unwrap_or(SAC_INT_UNDEF));
+    }
+    /// Set Magnitude, `None` is undefined
+    pub fn set_magnitude_opt(&mut self, mag: Option<f32>) {
+        self.mag = mag.unwrap_or(SAC_FLOAT_UNDEF);
+    }
+    /// Set Component Azimuth, `None` is undefined
+    pub fn set_cmpaz_opt(&mut self, az: Option<f32>) -> Result<(),SacError> {
+        match az {
+            Some(az) => self.set_cmpaz(az),
+            None => { self.cmpaz = SAC_FLOAT_UNDEF; Ok(()) },
+        }
+    }
+    /// Set Component Inclination, `None` is undefined
+    pub fn set_cmpinc_opt(&mut self, inc: Option<f32>) -> Result<(),SacError> {
+        match inc {
+            Some(inc) => self.set_cmpinc(inc),
+            None => { self.cmpinc = SAC_FLOAT_UNDEF; Ok(()) },
+        }
+    }
+    /// Set Data Quality, `None` is undefined
+    pub fn set_data_quality_opt(&mut self, qual: Option<SacQuality>) {
+        self.iqual = qual.map(i32::from).unwrap_or(SAC_INT_UNDEF);
+    }
+    /// Set Magnitude Type, `None` is undefined
+    pub fn set_magnitude_type_opt(&mut self, mag: Option<SacMagnitudeType>) {
+        self.imagtyp = mag.map(i32::from).unwrap_or(SAC_INT_UNDEF);
+    }
+    /// Set Magnitude Source, `None` is undefined
+    pub fn set_magnitude_source_opt(&mut self, magsrc: Option<SacMagnitudeSource>) {
+        self.imagsrc = magsrc.map(i32::from).unwrap_or(SAC_INT_UNDEF);
+    }
+    /// Marker value relative to the reference time, Day is only an approximation
+    fn marker_time(&self, m: Marker) -> f32 {
+        match m {
+            Marker::Z  => 0.0,
+            Marker::Day => self.time_as_duration(Marker::Day)
+                .map(|d| duration_to_f64(d) as f32)
+                .unwrap_or(SAC_FLOAT_UNDEF),
+            Marker::B  => self.b,
+            Marker::E  => self.e,
+            Marker::O  => self.o,
+            Marker::A  => self.a,
+            Marker::F  => self.f,
+            Marker::T0 => self.t0,
+            Marker::T1 => self.t1,
+            Marker::T2 => self.t2,
+            Marker::T3 => self.t3,
+            Marker::T4 => self.t4,
+            Marker::T5 => self.t5,
+            Marker::T6 => self.t6,
+            Marker::T7 => self.t7,
+            Marker::T8 => self.t8,
+            Marker::T9 => self.t9,
+        }
+    }
+    /// Marker value relative to the reference time, using the version 7
+    /// double precision value if current
+    fn marker_time64(&self, m: Marker) -> f64 {
+        let t = self.marker_time(m);
+        match footer::index(m) {
+            Some(i) => footer::precise(t, self.footer.0[i]),
+            None => t as f64,
+        }
+    }
+    /// Set a marker value, keeping the double precision value
+    fn set_marker64(&mut self, m: Marker, t: f64) {
+        if let Some(i) = footer::index(m) {
+            self.footer.0[i] = t;
+        }
+        if let Some(v) = self.marker_time_mut(m) {
+            *v = t as f32;
+        }
+    }
+    /// Sampling interval, using the version 7 double precision value if current
+    fn delta64(&self) -> f64 {
+        footer::precise(self.delta, self.footer.0[footer::DELTA])
+    }
+    /// Header values in the order of the version 7 footer
+    fn footer_header(&self) -> [f32; 22] {
+        [self.delta, self.b, self.e, self.o, self.a,
+         self.t0, self.t1, self.t2, self.t3, self.t4,
+         self.t5, self.t6, self.t7, self.t8, self.t9, self.f,
+         self.evlo, self.evla, self.stlo, self.stla, self.sb, self.sdelta]
+    }
+    fn marker_time_mut(&mut self, m: Marker) -> Option<&mut f32> {
+        let t = match m {
+            Marker::Z | Marker::Day => return None,
+            Marker::B  => &mut self.b,
+            Marker::E  => &mut self.e,
+            Marker::O  => &mut self.o,
+            Marker::A  => &mut self.a,
+            Marker::F  => &mut self.f,
+            Marker::T0 => &mut self.t0,
+            Marker::T1 => &mut self.t1,
+            Marker::T2 => &mut self.t2,
+            Marker::T3 => &mut self.t3,
+            Marker::T4 => &mut self.t4,
+            Marker::T5 => &mut self.t5,
+            Marker::T6 => &mut self.t6,
+            Marker::T7 => &mut self.t7,
+            Marker::T8 => &mut self.t8,
+            Marker::T9 => &mut self.t9,
+        };
+        Some(t)
+    }
+    /// Get a time marker and its label, if defined
+    ///
+    /// The begin and end times do not have labels.  The reference time (Z)
+    /// and start of day (Day) are not header values and are `None`
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::Marker;
+    /// use sacio::Pick;
+    /// # use sacio::SacError;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// assert_eq!(s.marker(Marker::T1), None);
+    ///
+    /// s.set_marker(Marker::T1, Some(Pick { time: 12.5, label: Some("P".into()) }))?;
+    /// let p = s.marker(Marker::T1).unwrap();
+    /// assert_eq!(p.time, 12.5);
+    /// assert_eq!(p.label.as_deref(), Some("P"));
+    ///
+    /// s.set_marker(Marker::T1, None)?;
+    /// assert_eq!(s.marker(Marker::T1), None);
+    /// assert_eq!(s.marker(Marker::Z), None);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn marker(&self, m: Marker) -> Option<Pick> {
+        if matches!(m, Marker::Z | Marker::Day) {
+            return None;
+        }
+        let time = fopt(self.marker_time(m))?;
+        let label = m.label()
+            .and_then(|key| self.string_opt(key))
+            .map(String::from);
+        Some(Pick { time, label })
+    }
+    /// Set a time marker and its label, `None` is undefined
+    ///
+    /// Setting the begin time also updates the end time.  The reference
+    /// time (Z) and start of day (Day) are not header values and return
+    /// `SacError::BadKey`, see [`Sac::set_reference_time`].
+    pub fn set_marker(&mut self, m: Marker, pick: Option<Pick>) -> Result<(), SacError> {
+        let (time, label) = match pick {
+            Some(p) => (p.time, p.label),
+            None => (SAC_FLOAT_UNDEF, None),
+        };
+        *self.marker_time_mut(m).ok_or(SacError::BadKey)? = time;
+        if let Some(key) = m.label() {
+            self.set_string_opt(key, label.as_deref());
+        }
+        if m == Marker::B && fis(time) {
+            self.calc_be();
         }
         Ok(())
     }
@@ -1233,7 +2133,7 @@ mod tests {
     #[test]
     fn create_time() {
         let mut s = Sac::from_amp(vec![0.,-1.,2.], 0.0, 1.0);
-        s.file = format!("{}","create_time");
+        s.file = String::from("create_time");
         assert_eq!(s.depmin, -1.0);
         assert_eq!(s.depmax,  2.0);
         assert_eq!(s.b,       0.0);
@@ -1241,7 +2141,7 @@ mod tests {
         assert_eq!(s.depmen,  1.0/3.0);
         assert_eq!(s.e,       2.0);
         //assert_eq!(s.iztype,  SacZeroTime::B as i32);
-        assert_eq!(s.iftype,  SacFileType::Time.into());
+        assert_eq!(s.iftype,  i32::from(SacFileType::Time));
         assert_eq!(s.leven,   true as i32);
         assert_eq!(s.nvhdr,   6);
         assert_eq!(s.y,     vec![0.,-1.,2.0]);
@@ -1263,6 +2163,43 @@ mod tests {
         assert!(!s.is_finite())
     }
 
+    #[test]
+    fn unknown_version() {
+        let mut buf = std::fs::read("tests/file.sac").unwrap();
+        // nvhdr is the seventh integer header value
+        buf[304..308].copy_from_slice(&[0xff; 4]);
+        let mut rdr = std::io::Cursor::new(&buf);
+        assert!(matches!(Sac::read(&mut rdr), Err(SacError::UnknownFileType)));
+    }
+
+    #[test]
+    fn component_angles() {
+        let mut s = Sac::from_amp(vec![0.,1.], 0.0, 1.0);
+        s.set_cmpaz(90.0).unwrap();
+        s.set_cmpinc(45.0).unwrap();
+        assert_eq!(s.cmpaz(), 90.0);
+        assert_eq!(s.cmpinc(), 45.0);
+    }
+
+    #[test]
+    fn write_two_components() {
+        // Unevenly spaced time series and spectral data store x after y
+        let mut s = Sac::from_amp(vec![1.,2.,3.], 0.0, 1.0);
+        s.leven = 0;
+        s.x = vec![0.0, 0.5, 2.0];
+        for ft in [SacFileType::Time, SacFileType::RealImag] {
+            s.iftype = ft.into();
+            let mut buf = vec![];
+            s.write(&mut buf).unwrap();
+            assert_eq!(buf.len(), 632 + 2 * 3 * 4);
+            let s2 = Sac::read(&mut std::io::Cursor::new(&buf)).unwrap();
+            assert_eq!(s2.y, s.y);
+            assert_eq!(s2.x, s.x);
+            s.leven = 1;
+        }
+    }
+
+
 
     #[test]
     fn read_file() {
@@ -1272,7 +2209,7 @@ mod tests {
 
         let mut s0 = Sac::new();
         s0.file = String::from("tests/file.sac.swap true");
-        s0.set_time(time_from_parts(1981, 88, 10, 38, 14, 0));
+        s0.set_time(time_from_parts(1981, 88, 10, 38, 14, 0).unwrap());
         s0.norid  = 0;
         s0.nevid  = 0;
 
@@ -1321,12 +2258,12 @@ mod tests {
         s.to_file(path).unwrap();
 
         println!("write file with long kevnm");
-        s.kevnm = format!("{}", "123456789012345678901234567890");
+        s.kevnm = String::from("123456789012345678901234567890");
         let path = Path::new("tests/tmp2.sac");
         s.to_file(path).unwrap();
 
         println!("write file with short kevnm");
-        s.kevnm = format!("{}", "12");
+        s.kevnm = String::from("12");
         let path = Path::new("tests/tmp3.sac");
         s.to_file(path).unwrap();
         {
@@ -1339,6 +2276,231 @@ mod tests {
         std::fs::remove_file("tests/tmp3.sac").unwrap();
     }
     #[test]
+    fn optional() {
+        let mut s = Sac::new();
+        assert_eq!(s.try_station_lat(), None);
+        assert_eq!(s.try_b(), None);
+        assert_eq!(s.try_id(SacInt::EventID), None);
+        assert_eq!(s.try_magnitude_type(), None);
+        assert_eq!(s.string_opt(SacString::Network), None);
+        s.set_station_location(45.0, -120.0, 10.0).unwrap();
+        assert_eq!(s.try_station_lat(), Some(45.0));
+        assert_eq!(s.try_station_region(), Some(s.station_region()));
+        s.set_id_opt(SacInt::EventID, Some(7));
+        assert_eq!(s.try_id(SacInt::EventID), Some(7));
+        s.set_magnitude_type_opt(Some(SacMagnitudeType::Moment));
+        assert_eq!(s.try_magnitude_type(), Some(SacMagnitudeType::Moment));
+        s.set_string_opt(SacString::Network, Some("  "));
+        assert_eq!(s.string(SacString::Network), SAC_STRING_UNDEF);
+        s.set_marker(Marker::O, Some(Pick { time: 1.5, label: None })).unwrap();
+        assert_eq!(s.try_o(), Some(1.5));
+        assert_eq!(s.marker(Marker::O).unwrap().label, None);
+        s.set_cmpaz_opt(None).unwrap();
+        assert_eq!(s.try_cmpaz(), None);
+        assert!(s.set_cmpaz_opt(Some(400.0)).is_err());
+    }
+    #[test]
+    fn distance_azimuth() {
+        // Values written by SAC in the header
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        let (dist, gcarc, az, back) = (s.dist_km(), s.dist_deg(), s.az(), s.baz());
+        s.compute_dist_az_with(DistAzMethod::Geocentric);
+        assert!((s.dist_km() - dist).abs() < 1e-3);
+        assert!((s.dist_deg() - gcarc).abs() < 1e-5);
+        assert!((s.az() - az).abs() < 1e-4);
+        assert!((s.baz() - back).abs() < 1e-4);
+
+        // Event west of the station: az is east, baz is west
+        s.compute_dist_az();
+        assert!((s.dist_km() - dist).abs() < 1.0);
+        assert!((s.dist_deg() - gcarc).abs() < 0.01);
+        assert!((s.az() - az).abs() < 0.01);
+        assert!((s.baz() - back).abs() < 0.01);
+
+        let mut s = Sac::new();
+        s.set_event_location(0.0, 0.0, 0.0).unwrap();
+        s.set_station_location(0.0, 90.0, 0.0).unwrap();
+        for method in [DistAzMethod::Geodesic, DistAzMethod::Geocentric] {
+            s.compute_dist_az_with(method);
+            assert!((s.dist_deg() - 90.0).abs() < 0.5);
+            assert!((s.az() - 90.0).abs() < 1e-4);
+            assert!((s.baz() - 270.0).abs() < 1e-4);
+        }
+        s.set_station_location(10.0, 0.0, 0.0).unwrap();
+        for method in [DistAzMethod::Geodesic, DistAzMethod::Geocentric] {
+            s.compute_dist_az_with(method);
+            assert!(s.az().abs() < 1e-4);
+            assert!((s.baz() - 180.0).abs() < 1e-4);
+        }
+        s.set_station_location(0.0, 90.0, 0.0).unwrap();
+        s.compute_dist_az_with(DistAzMethod::Geocentric);
+        assert!((s.dist_deg() - 90.0).abs() < 1e-4);
+    }
+    #[test]
+    fn marker_times() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        let z = s.time().unwrap();
+        let b = z + Duration::milliseconds(5_125);
+        s.set_b(TimeValue::Absolute(b)).unwrap();
+        assert_eq!(s.b(), 5.125);
+        assert_eq!(s.datetime(Marker::B).unwrap(), b);
+        assert!((s.e() - (5.125 + 999.0 * 0.01)).abs() < 1e-4);
+
+        s.set_o(TimeValue::Marker(Marker::Day, Duration::hours(10))).unwrap();
+        assert_eq!(s.o(), -(38.0 * 60.0 + 14.0));
+        assert_eq!(s.time_between(Marker::O, Marker::Z).unwrap(), Duration::seconds(38 * 60 + 14));
+        assert_eq!(s.marker(Marker::Z), None);
+        assert_eq!(s.marker(Marker::Day), None);
+        let pick = Pick { time: 1.0, label: None };
+        assert!(matches!(s.set_marker(Marker::Z, Some(pick.clone())), Err(SacError::BadKey)));
+        assert!(matches!(s.set_marker(Marker::Day, Some(pick)), Err(SacError::BadKey)));
+
+        for m in ["z", "day", "b", "e", "o", "a", "f", "t0", "T9"] {
+            assert!(m.parse::<Marker>().is_ok());
+        }
+        assert!("t10".parse::<Marker>().is_err());
+        assert!(matches!(s.set_marker_time(Marker::Z, TimeValue::Relative(Duration::zero())),
+                         Err(SacError::BadKey)));
+
+        let mut s = Sac::new();
+        assert!(matches!(s.datetime(Marker::Day), Err(SacError::NotTime)));
+        assert!(s.set_marker_time(Marker::T1, TimeValue::Absolute(z)).is_err());
+        s.set_marker_time(Marker::T1, TimeValue::Relative(Duration::milliseconds(1500))).unwrap();
+        assert_eq!(s.marker(Marker::T1).unwrap().time, 1.5);
+    }
+    #[test]
+    fn reference_time() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        let (b, e) = (s.datetime(Marker::B).unwrap(), s.datetime(Marker::E).unwrap());
+        let o = s.datetime(Marker::O).unwrap();
+        s.set_zero_to(Marker::Day).unwrap();
+        assert_eq!(s.time().unwrap().time(), NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+        assert_eq!(s.zero_time(), SacZeroTime::Day);
+        assert!((s.b() - (38294.0 + 9.46)).abs() < 0.01);
+        assert!((s.datetime(Marker::E).unwrap() - e).num_milliseconds().abs() < 5);
+
+        s.set_marker_time(Marker::T0, TimeValue::Absolute(b)).unwrap();
+        s.set_zero_to(Marker::T0).unwrap();
+        assert_eq!(s.zero_time(), SacZeroTime::T0);
+        assert!(s.t0.abs() < 1e-3);
+        assert!(s.b().abs() < 1e-3);
+        assert!((s.datetime(Marker::O).unwrap() - o).num_milliseconds().abs() < 5);
+
+        s.set_zero_to(Marker::Z).unwrap();
+        assert_eq!(s.zero_time(), SacZeroTime::T0);
+        s.set_zero_to(Marker::E).unwrap();
+        assert_eq!(s.zero_time(), SacZeroTime::None);
+        assert!(s.e().abs() < 1e-3);
+
+        assert!(s.set_zero_to(Marker::T5).is_err());
+        let mut s = Sac::new();
+        assert!(s.set_reference_time(b).is_err());
+    }
+    #[test]
+    fn precise_time() {
+        let t = NaiveDate::from_ymd_opt(2021, 3, 4).unwrap()
+            .and_hms_nano_opt(5, 6, 7, 123_456_789).unwrap();
+        let ns = Duration::nanoseconds;
+
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_version(7).unwrap();
+        s.set_time_precise(t).unwrap();
+        s.set_marker_time(Marker::T1, TimeValue::Absolute(t + ns(2_000_000_001))).unwrap();
+        let (b, e) = (s.start_time().unwrap(), s.end_time().unwrap());
+        for file in ["tests/precise.sac", "tests/precise.sac.swap"] {
+            if file.ends_with("swap") {
+                s.swap = true;
+            }
+            s.to_file(file).unwrap();
+            let s2 = Sac::from_file(file).unwrap();
+            std::fs::remove_file(file).unwrap();
+            assert_eq!(s2.version(), 7);
+            assert_eq!(s2.swapped(), s.swapped());
+            assert_eq!(s2.start_time().unwrap(), b);
+            assert_eq!(s2.end_time().unwrap(), e);
+            assert_eq!(s2.datetime(Marker::T1).unwrap(), t + ns(2_000_000_001));
+            assert_eq!(s2.y, s.y);
+        }
+
+        // Changing a header value directly supersedes the double precision value
+        s.t1 = 3.0;
+        assert_eq!(s.datetime(Marker::T1).unwrap(), s.time().unwrap() + ns(3_000_000_000));
+
+        // Version 6 keeps single precision
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_time_precise(t).unwrap();
+        let mut buf = vec![];
+        s.write(&mut buf).unwrap();
+        let s2 = Sac::read(&mut std::io::Cursor::new(&buf)).unwrap();
+        assert_eq!(s2.version(), 6);
+        let b = t + ns((9.459999 * 1e9_f64) as i64);
+        assert!((s2.start_time().unwrap() - b).num_nanoseconds().unwrap().abs() < 1_000);
+    }
+    #[test]
+    fn leap_second_time() {
+        let day = NaiveDate::from_ymd_opt(2016, 12, 31).unwrap();
+        let leap = day.and_hms_milli_opt(23, 59, 59, 1_500).unwrap();
+        let mut s = Sac::new();
+        s.set_time(leap);
+        assert_eq!((s.nzsec, s.nzmsec), (60, 500));
+
+        let mut buf = vec![];
+        s.write(&mut buf).unwrap();
+        let mut s = Sac::read(&mut std::io::Cursor::new(&buf)).unwrap();
+        assert_eq!(s.time().unwrap(), leap);
+        assert_eq!(s.time_utc().unwrap().naive_utc(), leap);
+
+        // 10 s after 23:59:60.5 is 00:00:09.5 counting leap seconds
+        let after = NaiveDate::from_ymd_opt(2017, 1, 1).unwrap()
+            .and_hms_milli_opt(0, 0, 9, 500).unwrap();
+        s.set_b(TimeValue::Relative(Duration::seconds(10))).unwrap();
+        s.set_leap_seconds(true);
+        assert_eq!(s.datetime(Marker::B).unwrap(), after);
+        s.set_o(TimeValue::Absolute(after)).unwrap();
+        assert_eq!(s.o(), 10.0);
+
+        // Invalid reference times are not times
+        s.nzsec = 61;
+        assert!(matches!(s.time(), Err(SacError::NotTime)));
+        s.nzsec = 59;
+        s.nzmsec = 1500;
+        assert!(matches!(s.time(), Err(SacError::NotTime)));
+        s.nzsec = 60;
+        s.nzmsec = 0;
+        assert!(s.time().is_ok());
+        s.nzmin = 58;
+        assert!(matches!(s.time(), Err(SacError::NotTime)));
+        s.nzmin = 59;
+        s.nzyear = 2015;
+        assert!(matches!(s.time(), Err(SacError::NotTime)));
+        s.nzyear = 2016;
+        assert!(s.time().is_ok());
+        s.nzsec = 0;
+        s.nzjday = 400;
+        assert!(matches!(s.time(), Err(SacError::NotTime)));
+    }
+    #[test]
+    fn locations() {
+        let mut s = Sac::new();
+        s.set_event_location(48.0, -125.0, 15.0).unwrap();
+        assert_eq!(s.event_depth(), 15.0);
+        assert_eq!(s.try_event_elevation(), None);
+        s.set_event_elevation(-2500.0).unwrap();
+        assert_eq!(s.event_elevation(), -2500.0);
+        s.set_station_location(48.0, -120.0, 1000.0).unwrap();
+        assert!((s.az() - 88.15).abs() < 0.01);
+        s.set_station_depth(150.0).unwrap();
+        assert_eq!(s.try_station_depth(), Some(150.0));
+
+        assert!(matches!(s.set_event_depth(15000.0), Err(SacError::BadDepth)));
+        assert!(matches!(s.set_event_location(48.0, -125.0, f32::NAN), Err(SacError::BadDepth)));
+        assert!(matches!(s.set_event_location(f32::NAN, -125.0, 1.0), Err(SacError::BadLatitude)));
+        assert!(matches!(s.set_station_depth(-1.0), Err(SacError::BadDepth)));
+        assert!(matches!(s.set_station_elevation(20000.0), Err(SacError::BadElevation)));
+        assert_eq!(s.event_depth(), 15.0);
+        assert_eq!(s.station_depth(), 150.0);
+    }
+    #[test]
     fn stringy() {
         let mut s = Sac::new();
         s.set_string(SacString::Network, "IU");
@@ -1368,6 +2530,12 @@ pub struct Sac {
     pub file: String,
     /// If data is swapped from native byte order
     swap: bool,
+    /// Double precision values of version 7 headers
+    footer: footer::Footer,
+    /// If absolute times count leap seconds
+    leap_seconds: bool,
+    /// Event name filled in from the event region by `update_regions`
+    region_event_name: Option<String>,
 
     /// Time sampling
     delta: f32,               /* RF time increment, sec    */
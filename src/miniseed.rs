@@ -0,0 +1,1021 @@
+/*! Reading and writing of miniSEED data
+
+Both miniSEED version 2 and version 3 records can be read, records
+are written as miniSEED version 2 using [`write`] or
+[`Sac::to_miniseed`](crate::Sac::to_miniseed).
+
+ Encoding  | Code
+ ----------|------
+ Int16     | 1
+ Int32     | 3
+ Float32   | 4
+ Float64   | 5
+ Steim1    | 10
+ Steim2    | 11
+
+Records are grouped by Network, Station, Location and Channel, and
+contiguous records are merged into a single trace.  A gap or overlap
+in the data starts a new trace.
+
+ Header   | miniSEED value
+ ---------|---------------
+ knetwk   | Network
+ kstnm    | Station
+ khole    | Location
+ kcmpnm   | Channel
+ nz*      | Start time, truncated to milliseconds
+ b        | Sub-millisecond part of the start time
+ delta    | 1 / Sample rate
+
+```no_run
+use sacio::miniseed;
+# use sacio::SacError;
+
+for s in miniseed::from_file("data.mseed")? {
+    println!("{} {}", s.nslc(), s.npts());
+}
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+
+use byteorder::{BigEndian, LittleEndian, ByteOrder};
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::Datelike;
+use chrono::Timelike;
+
+use crate::Sac;
+use crate::SacError;
+use crate::SacString;
+use crate::SacZeroTime;
+
+const ENC_INT16   : u8 = 1;
+const ENC_INT32   : u8 = 3;
+const ENC_FLOAT32 : u8 = 4;
+const ENC_FLOAT64 : u8 = 5;
+const ENC_STEIM1  : u8 = 10;
+const ENC_STEIM2  : u8 = 11;
+
+/// Size of a Steim frame in bytes
+const FRAME_SIZE : usize = 64;
+
+/// Single decoded data record
+struct Record {
+    net: String,
+    sta: String,
+    loc: String,
+    cha: String,
+    start: NaiveDateTime,
+    rate: f64,
+    y: Vec<f32>,
+}
+
+impl Record {
+    fn same_channel(&self, other: &Record) -> bool {
+        self.net == other.net && self.sta == other.sta &&
+            self.loc == other.loc && self.cha == other.cha
+    }
+    fn end(&self) -> NaiveDateTime {
+        self.start + nanoseconds(self.y.len() as f64 / self.rate)
+    }
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+fn nanoseconds(secs: f64) -> Duration {
+    Duration::nanoseconds((secs * 1e9).round() as i64)
+}
+
+/// Read a u16 in the given byte order
+fn u16_at(buf: &[u8], off: usize, big: bool) -> u16 {
+    if big { BigEndian::read_u16(&buf[off..]) } else { LittleEndian::read_u16(&buf[off..]) }
+}
+/// Read a u32 in the given byte order
+fn u32_at(buf: &[u8], off: usize, big: bool) -> u32 {
+    if big { BigEndian::read_u32(&buf[off..]) } else { LittleEndian::read_u32(&buf[off..]) }
+}
+
+/// Construct a time from its day of year parts
+fn time_from_doy(year: i32, doy: u32, hour: u32, min: u32, sec: u32,
+                 nanos: i64) -> Result<NaiveDateTime, SacError> {
+    let date = NaiveDate::from_yo_opt(year, doy)
+        .ok_or_else(|| bad("Invalid record start time"))?;
+    // Second 60 is a leap second
+    if sec == 60 && (0 .. 1_000_000_000).contains(&nanos) {
+        return date.and_hms_nano_opt(hour, min, 59, 1_000_000_000 + nanos as u32)
+            .ok_or_else(|| bad("Invalid record start time"));
+    }
+    let secs = i64::from(hour) * 3600 + i64::from(min) * 60 + i64::from(sec);
+    Ok(date.and_hms_opt(0, 0, 0).unwrap() +
+       Duration::seconds(secs) + Duration::nanoseconds(nanos))
+}
+
+/// Sample rate from SEED factor and multiplier
+fn sample_rate(factor: i16, mult: i16) -> f64 {
+    let (f, m) = (f64::from(factor), f64::from(mult));
+    match (factor > 0, mult > 0) {
+        _ if factor == 0 || mult == 0 => 0.0,
+        (true, true)   => f * m,
+        (true, false)  => -f / m,
+        (false, true)  => -m / f,
+        (false, false) => 1.0 / (f * m),
+    }
+}
+
+/// Sign extend the lower `bits` of a value
+fn sext(v: u32, bits: u32) -> i32 {
+    ((v << (32 - bits)) as i32) >> (32 - bits)
+}
+
+/// Decode Steim1 or Steim2 compressed data
+fn steim_decode(data: &[u8], nsamp: usize, level: u8, big: bool) -> Result<Vec<i32>, SacError> {
+    let mut diffs = Vec::with_capacity(nsamp);
+    let mut x0 = 0;
+    for (f, frame) in data.chunks_exact(FRAME_SIZE).enumerate() {
+        let ctrl = u32_at(frame, 0, big);
+        for i in 1 .. 16 {
+            let w = u32_at(frame, i * 4, big);
+            if f == 0 && i == 1 {
+                x0 = w as i32;
+                continue;
+            }
+            if f == 0 && i == 2 {
+                continue;
+            }
+            let nib = (ctrl >> (30 - 2 * i)) & 0x3;
+            let dnib = w >> 30;
+            let (n, bits) = match (level, nib, dnib) {
+                (_, 0, _)  => continue,
+                (_, 1, _)  => (4, 8),
+                (1, 2, _)  => (2, 16),
+                (1, 3, _)  => (1, 32),
+                (2, 2, 1)  => (1, 30),
+                (2, 2, 2)  => (2, 15),
+                (2, 2, 3)  => (3, 10),
+                (2, 3, 0)  => (5, 6),
+                (2, 3, 1)  => (6, 5),
+                (2, 3, 2)  => (7, 4),
+                _ => return Err(bad("Invalid Steim compression flag")),
+            };
+            for k in (0 .. n).rev() {
+                let v = if bits == 32 { w } else { (w >> (k * bits)) & ((1 << bits) - 1) };
+                diffs.push(sext(v, bits));
+            }
+        }
+        if diffs.len() >= nsamp {
+            break;
+        }
+    }
+    if diffs.len() < nsamp {
+        return Err(bad("Not enough Steim compressed samples"));
+    }
+    let mut y = Vec::with_capacity(nsamp);
+    if nsamp > 0 {
+        y.push(x0);
+    }
+    for d in diffs.iter().take(nsamp).skip(1) {
+        let last = y[y.len() - 1];
+        y.push(last.wrapping_add(*d));
+    }
+    Ok(y)
+}
+
+/// Decode the data section of a record
+fn decode_data(data: &[u8], nsamp: usize, enc: u8, big: bool) -> Result<Vec<f32>, SacError> {
+    let size = match enc {
+        ENC_INT16 => 2,
+        ENC_INT32 | ENC_FLOAT32 => 4,
+        ENC_FLOAT64 => 8,
+        ENC_STEIM1 | ENC_STEIM2 => {
+            let level = if enc == ENC_STEIM1 { 1 } else { 2 };
+            let y = steim_decode(data, nsamp, level, big)?;
+            return Ok(y.into_iter().map(|v| v as f32).collect());
+        },
+        _ => return Err(bad(format!("Unsupported data encoding: {}", enc))),
+    };
+    if data.len() < nsamp * size {
+        return Err(bad("Record data shorter than number of samples"));
+    }
+    let y = data.chunks_exact(size).take(nsamp).map(|b| {
+        match (enc, big) {
+            (ENC_INT16, true)    => f32::from(BigEndian::read_i16(b)),
+            (ENC_INT16, false)   => f32::from(LittleEndian::read_i16(b)),
+            (ENC_INT32, true)    => BigEndian::read_i32(b) as f32,
+            (ENC_INT32, false)   => LittleEndian::read_i32(b) as f32,
+            (ENC_FLOAT32, true)  => BigEndian::read_f32(b),
+            (ENC_FLOAT32, false) => LittleEndian::read_f32(b),
+            (_, true)            => BigEndian::read_f64(b) as f32,
+            (_, false)           => LittleEndian::read_f64(b) as f32,
+        }
+    }).collect();
+    Ok(y)
+}
+
+fn ascii(buf: &[u8]) -> String {
+    String::from_utf8_lossy(buf).trim().to_string()
+}
+
+/// Decode a miniSEED version 2 record, returns the record length
+fn decode_v2(buf: &[u8]) -> Result<(usize, Option<Record>), SacError> {
+    if buf.len() < 48 {
+        return Err(bad("Truncated miniSEED record"));
+    }
+    // Byte order is determined from a sensible year value
+    let year = BigEndian::read_u16(&buf[20..]);
+    let big = (1900 ..= 2100).contains(&year);
+
+    let nsamp = u16_at(buf, 30, big) as usize;
+    let factor = u16_at(buf, 32, big) as i16;
+    let mult = u16_at(buf, 34, big) as i16;
+    let activity = buf[36];
+    let nblockettes = buf[39];
+    let correction = u32_at(buf, 40, big) as i32;
+    let begin_data = u16_at(buf, 44, big) as usize;
+    let mut next = u16_at(buf, 46, big) as usize;
+
+    let mut rate = sample_rate(factor, mult);
+    let mut enc = None;
+    let mut reclen = None;
+    let mut big_data = big;
+    let mut usec = 0;
+    for _ in 0 .. nblockettes {
+        if next == 0 || next + 4 > buf.len() {
+            break;
+        }
+        let btype = u16_at(buf, next, big);
+        let length = match btype {
+            100 => 12,
+            1000 | 1001 => 8,
+            _ => 4,
+        };
+        if next + length > buf.len() {
+            return Err(bad("Truncated blockette"));
+        }
+        match btype {
+            100 => {
+                let r = u32_at(buf, next + 4, big);
+                rate = f64::from(f32::from_bits(r));
+            },
+            1000 => {
+                enc = Some(buf[next + 4]);
+                big_data = buf[next + 5] == 1;
+                if buf[next + 6] > 30 {
+                    return Err(bad("Invalid record length"));
+                }
+                reclen = Some(1usize << buf[next + 6]);
+            },
+            1001 => {
+                usec = buf[next + 5] as i8;
+            },
+            _ => {},
+        }
+        next = u16_at(buf, next + 2, big) as usize;
+    }
+    let reclen = reclen.ok_or_else(|| bad("Missing blockette 1000"))?;
+    let enc = enc.unwrap_or(ENC_STEIM1);
+    if reclen > buf.len() {
+        return Err(bad("Truncated miniSEED record"));
+    }
+    if nsamp == 0 || rate == 0.0 || enc == 0 {
+        return Ok((reclen, None));
+    }
+    let mut nanos = i64::from(u16_at(buf, 28, big)) * 100_000 + i64::from(usec) * 1_000;
+    // Apply the time correction if not already applied
+    if activity & 0x02 == 0 {
+        nanos += i64::from(correction) * 100_000;
+    }
+    let start = time_from_doy(i32::from(u16_at(buf, 20, big)),
+                              u32::from(u16_at(buf, 22, big)),
+                              u32::from(buf[24]), u32::from(buf[25]),
+                              u32::from(buf[26]), nanos)?;
+    if begin_data > reclen {
+        return Err(bad("Invalid beginning of data"));
+    }
+    let y = decode_data(&buf[begin_data .. reclen], nsamp, enc, big_data)?;
+    Ok((reclen, Some(Record {
+        net: ascii(&buf[18..20]),
+        sta: ascii(&buf[8..13]),
+        loc: ascii(&buf[13..15]),
+        cha: ascii(&buf[15..18]),
+        start, rate, y,
+    })))
+}
+
+/// CRC-32C (Castagnoli) checksum
+pub(crate) fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in data {
+        crc ^= u32::from(*b);
+        for _ in 0 .. 8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0x82F6_3B78 & mask);
+        }
+    }
+    !crc
+}
+
+/// Split an FDSN Source Identifier into network, station, location and channel
+fn parse_sid(sid: &str) -> Result<(String, String, String, String), SacError> {
+    let parts : Vec<&str> = sid.trim_start_matches("FDSN:").split('_').collect();
+    if parts.len() != 6 {
+        return Err(bad(format!("Invalid source identifier: {}", sid)));
+    }
+    let cha = if parts[3 ..].iter().all(|p| p.len() == 1) {
+        parts[3 ..].concat()
+    } else {
+        parts[3 ..].join("_")
+    };
+    Ok((parts[0].to_string(), parts[1].to_string(), parts[2].to_string(), cha))
+}
+
+/// Decode a miniSEED version 3 record, returns the record length
+fn decode_v3(buf: &[u8]) -> Result<(usize, Option<Record>), SacError> {
+    if buf.len() < 40 {
+        return Err(bad("Truncated miniSEED record"));
+    }
+    let nanos = LittleEndian::read_u32(&buf[4..]);
+    let enc = buf[15];
+    let rate = LittleEndian::read_f64(&buf[16..]);
+    let nsamp = LittleEndian::read_u32(&buf[24..]) as usize;
+    let crc = LittleEndian::read_u32(&buf[28..]);
+    let sid_len = buf[33] as usize;
+    let extra_len = LittleEndian::read_u16(&buf[34..]) as usize;
+    let data_len = LittleEndian::read_u32(&buf[36..]) as usize;
+    let data_start = 40 + sid_len + extra_len;
+    let reclen = data_start + data_len;
+    if reclen > buf.len() {
+        return Err(bad("Truncated miniSEED record"));
+    }
+    let mut rec = buf[.. reclen].to_vec();
+    rec[28..32].copy_from_slice(&[0; 4]);
+    if crc32c(&rec) != crc {
+        return Err(bad("miniSEED record checksum mismatch"));
+    }
+    let rate = if rate < 0.0 { -1.0 / rate } else { rate };
+    if nsamp == 0 || rate == 0.0 || enc == 0 {
+        return Ok((reclen, None));
+    }
+    let sid = String::from_utf8_lossy(&buf[40 .. 40 + sid_len]);
+    let (net, sta, loc, cha) = parse_sid(&sid)?;
+    let start = time_from_doy(i32::from(LittleEndian::read_u16(&buf[8..])),
+                              u32::from(LittleEndian::read_u16(&buf[10..])),
+                              u32::from(buf[12]), u32::from(buf[13]),
+                              u32::from(buf[14]), i64::from(nanos))?;
+    // Steim frames are always big endian
+    let big = enc == ENC_STEIM1 || enc == ENC_STEIM2;
+    let y = decode_data(&buf[data_start .. reclen], nsamp, enc, big)?;
+    Ok((reclen, Some(Record { net, sta, loc, cha, start, rate, y })))
+}
+
+/// Decode all records within a buffer
+fn decode_records(buf: &[u8]) -> Result<Vec<Record>, SacError> {
+    let mut records = vec![];
+    let mut pos = 0;
+    while pos < buf.len() {
+        let rec = &buf[pos..];
+        let (reclen, r) = if rec.starts_with(b"MS") && rec.len() > 2 && rec[2] == 3 {
+            decode_v3(rec)?
+        } else if rec.len() >= 48 && b"DRQM".contains(&rec[6]) {
+            decode_v2(rec)?
+        } else {
+            return Err(bad("Unknown miniSEED record type"));
+        };
+        if let Some(r) = r {
+            records.push(r);
+        }
+        pos += reclen;
+    }
+    Ok(records)
+}
+
+/// Merge contiguous records of the same channel into traces
+fn merge(records: Vec<Record>) -> Vec<Record> {
+    let mut channels : Vec<Vec<Record>> = vec![];
+    for r in records {
+        match channels.iter_mut().find(|c| c[0].same_channel(&r)) {
+            Some(c) => c.push(r),
+            None => channels.push(vec![r]),
+        }
+    }
+    let mut traces : Vec<Record> = vec![];
+    for mut recs in channels {
+        recs.sort_by_key(|r| r.start);
+        let mut cur : Option<Record> = None;
+        for r in recs {
+            if let Some(c) = cur.as_mut() {
+                let tol = nanoseconds(0.5 / c.rate);
+                let gap = r.start - c.end();
+                if (r.rate - c.rate).abs() < 1e-6 * c.rate && gap <= tol && -gap <= tol {
+                    c.y.extend(r.y);
+                    continue;
+                }
+            }
+            if let Some(c) = cur.replace(r) {
+                traces.push(c);
+            }
+        }
+        traces.extend(cur);
+    }
+    traces
+}
+
+fn record_to_sac(r: Record) -> Sac {
+    let ms = r.start.with_nanosecond(r.start.nanosecond() / 1_000_000 * 1_000_000)
+        .unwrap_or(r.start);
+    let b = (r.start - ms).num_nanoseconds().unwrap_or(0) as f64 / 1e9;
+    let mut s = Sac::from_amp(r.y, b, 1.0 / r.rate);
+    s.set_time(ms);
+    s.set_zero_time_type(SacZeroTime::B);
+    s.set_string(SacString::Network, &r.net);
+    s.set_string(SacString::Station, &r.sta);
+    s.set_string(SacString::Location, &r.loc);
+    s.set_string(SacString::Channel, &r.cha);
+    s
+}
+
+/// Decode miniSEED records from a buffer
+///
+/// Returns one trace per channel and contiguous segment of data
+pub fn decode(buf: &[u8]) -> Result<Vec<Sac>, SacError> {
+    let records = decode_records(buf)?;
+    Ok(merge(records).into_iter().map(record_to_sac).collect())
+}
+
+/// Read miniSEED records
+pub fn read<R: Read>(reader: &mut R) -> Result<Vec<Sac>, SacError> {
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf)?;
+    decode(&buf)
+}
+
+/// Read a miniSEED file
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Sac>, SacError> {
+    let file = File::open(&path)?;
+    let mut file = BufReader::new(file);
+    let mut sacs = read(&mut file)?;
+    for s in sacs.iter_mut() {
+        s.set_filename(&path.as_ref().to_string_lossy());
+    }
+    Ok(sacs)
+}
+
+/// Data encoding used when writing miniSEED
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Encoding {
+    /// Steim2 for integer valued data, Float32 otherwise
+    Auto,
+    /// 32-bit integers
+    Int32,
+    /// 32-bit IEEE floats
+    Float32,
+    /// Steim2 compressed integers
+    Steim2,
+}
+
+/// Options for writing miniSEED
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// Data encoding
+    pub encoding: Encoding,
+    /// Record length in bytes, a power of two between 256 and 65536
+    pub record_length: usize,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions { encoding: Encoding::Auto, record_length: 4096 }
+    }
+}
+
+/// Check if all values are integers representable as i32
+fn integer_valued(y: &[f32]) -> bool {
+    y.iter().all(|v| v.fract() == 0.0 && v.abs() < 2_147_483_648.0)
+}
+
+/// Check if all first differences fit within a Steim2 30 bit word
+fn steim2_encodable(y: &[i32]) -> bool {
+    y.windows(2).all(|w| fits(i64::from(w[1]) - i64::from(w[0]), 30))
+}
+
+fn fits(v: i64, bits: u32) -> bool {
+    let lim = 1i64 << (bits - 1);
+    v >= -lim && v < lim
+}
+
+/// Steim2 packings: number of differences, bits, nibble, dnib
+const STEIM2 : [(usize, u32, u32, u32); 7] = [
+    (7, 4, 3, 2), (6, 5, 3, 1), (5, 6, 3, 0), (4, 8, 1, 0),
+    (3, 10, 2, 3), (2, 15, 2, 2), (1, 30, 2, 1),
+];
+
+/// Encode differences into Steim2 frames
+///
+/// Returns the number of differences encoded
+fn steim2_encode(diffs: &[i64], nframes: usize, out: &mut Vec<u8>) -> usize {
+    let mut k = 0;
+    for f in 0 .. nframes {
+        let mut ctrl = 0u32;
+        let mut words = [0u32; 16];
+        // Words 1 and 2 of the first frame hold the integration constants
+        let first = if f == 0 { 3 } else { 1 };
+        for (i, word) in words.iter_mut().enumerate().skip(first) {
+            let rest = &diffs[k..];
+            let packing = STEIM2.iter()
+                .find(|(n, bits, _, _)| rest.len() >= *n &&
+                      rest[.. *n].iter().all(|d| fits(*d, *bits)));
+            if let Some((n, bits, nib, dnib)) = packing {
+                let mut w = 0u32;
+                for d in &rest[.. *n] {
+                    w = (w << bits) | (*d as u32 & ((1 << bits) - 1));
+                }
+                if *nib != 1 {
+                    w |= dnib << 30;
+                }
+                *word = w;
+                ctrl |= nib << (30 - 2 * i);
+                k += n;
+            }
+        }
+        words[0] = ctrl;
+        words.iter().for_each(|w| out.extend_from_slice(&w.to_be_bytes()));
+        if k == diffs.len() {
+            break;
+        }
+    }
+    k
+}
+
+/// Sample rate as a SEED factor and multiplier, if representable
+///
+/// Rates within a relative 1e-6 of a representable rate are rounded to
+/// it, e.g. 100.0000022 from a single precision delta of 0.01
+fn factor_mult(rate: f64) -> Option<(i16, i16)> {
+    let max = f64::from(i16::MAX);
+    let whole = |v: f64| {
+        let r = v.round();
+        if r >= 1.0 && r <= max && (v - r).abs() <= 1e-6 * v {
+            Some(r as i16)
+        } else {
+            None
+        }
+    };
+    if let Some(f) = whole(rate) {
+        return Some((f, 1));
+    }
+    if let Some(p) = whole(1.0 / rate) {
+        return Some((-p, 1));
+    }
+    for m in [10i16, 100, 1000, 10000] {
+        if let Some(f) = whole(rate * f64::from(m)) {
+            return Some((f, -m));
+        }
+    }
+    None
+}
+
+/// Pad or reject a header string for a fixed length field
+fn field(s: &Sac, key: SacString, len: usize) -> Result<String, SacError> {
+    let v = s.string(key);
+    let v = if v == crate::SAC_STRING_UNDEF { "" } else { v.trim() };
+    if v.len() > len {
+        return Err(bad(format!("{:?} too long for miniSEED: {}", key, v)));
+    }
+    Ok(format!("{:width$}", v, width = len))
+}
+
+/// Encode a time series as miniSEED version 2 records
+///
+/// Data must be evenly spaced and have a defined reference time
+///
+/// ```
+/// use sacio::Sac;
+/// use sacio::miniseed;
+/// # use sacio::SacError;
+///
+/// let mut s = Sac::from_file("tests/file.sac")?;
+/// s.y.iter_mut().for_each(|v| *v = (*v * 1000.0).round());
+/// let opts = miniseed::WriteOptions {
+///     encoding: miniseed::Encoding::Steim2,
+///     record_length: 512,
+/// };
+/// let buf = miniseed::write(&s, &opts)?;
+/// assert_eq!(buf.len() % 512, 0);
+///
+/// let s2 = miniseed::decode(&buf)?;
+/// assert_eq!(s2.len(), 1);
+/// assert_eq!(s2[0].y, s.y);
+/// # Ok::<(), SacError>(())
+/// ```
+pub fn write(s: &Sac, opts: &WriteOptions) -> Result<Vec<u8>, SacError> {
+    if !s.is_time() {
+        return Err(bad("Only time series can be written as miniSEED"));
+    }
+    if !s.evenly_spaced() {
+        return Err(SacError::NotEvenlySpaced);
+    }
+    let reclen = opts.record_length;
+    if !reclen.is_power_of_two() || !(256 ..= 65536).contains(&reclen) {
+        return Err(bad(format!("Invalid record length: {}", reclen)));
+    }
+    let sta = field(s, SacString::Station, 5)?;
+    let loc = field(s, SacString::Location, 2)?;
+    let cha = field(s, SacString::Channel, 3)?;
+    let net = field(s, SacString::Network, 2)?;
+    let start = s.start_time()?;
+    let delta = s.delta64();
+    let rate = 1.0 / delta;
+
+    let ints = integer_valued(&s.y);
+    let y : Vec<i32> = if ints { s.y.iter().map(|v| *v as i32).collect() } else { vec![] };
+    let enc = match opts.encoding {
+        Encoding::Auto if ints && steim2_encodable(&y) => ENC_STEIM2,
+        Encoding::Auto if ints => ENC_INT32,
+        Encoding::Auto | Encoding::Float32 => ENC_FLOAT32,
+        Encoding::Int32 | Encoding::Steim2 if !ints => {
+            return Err(bad("Integer encoding requires integer valued data"));
+        },
+        Encoding::Int32 => ENC_INT32,
+        Encoding::Steim2 if !steim2_encodable(&y) => {
+            return Err(bad("Data differences too large for Steim2"));
+        },
+        Encoding::Steim2 => ENC_STEIM2,
+    };
+    let fm = factor_mult(rate);
+    let begin_data = if fm.is_some() { 64 } else { 128 };
+    let (factor, mult) = fm.unwrap_or((0, 0));
+    let nframes = (reclen - begin_data) / FRAME_SIZE;
+
+    let mut out = Vec::with_capacity(s.y.len() * 4);
+    let mut i = 0;
+    let mut seq = 1;
+    while i < s.y.len() {
+        let t = start + nanoseconds(i as f64 * delta);
+        // Leap seconds are written as second 60
+        let (sec, usec) = if t.nanosecond() >= 1_000_000_000 {
+            (60, (t.nanosecond() - 1_000_000_000) / 1000)
+        } else {
+            (t.second(), t.nanosecond() / 1000)
+        };
+
+        let mut data = vec![];
+        let n = match enc {
+            ENC_STEIM2 => {
+                let prev = if i == 0 { y[0] } else { y[i - 1] };
+                let diffs : Vec<i64> = y[i..].iter()
+                    .scan(i64::from(prev), |last, v| {
+                        let d = i64::from(*v) - *last;
+                        *last = i64::from(*v);
+                        Some(d)
+                    })
+                    // Number of samples is a u16 in the header
+                    .take((nframes * 105).min(u16::MAX as usize))
+                    .collect();
+                let n = steim2_encode(&diffs, nframes, &mut data);
+                data[4..8].copy_from_slice(&y[i].to_be_bytes());
+                data[8..12].copy_from_slice(&y[i + n - 1].to_be_bytes());
+                n
+            },
+            _ => {
+                let n = ((reclen - begin_data) / 4).min(s.y.len() - i);
+                for v in &s.y[i .. i + n] {
+                    if enc == ENC_INT32 {
+                        data.extend_from_slice(&(*v as i32).to_be_bytes());
+                    } else {
+                        data.extend_from_slice(&v.to_be_bytes());
+                    }
+                }
+                n
+            },
+        };
+
+        let mut r = Vec::with_capacity(reclen);
+        r.extend_from_slice(format!("{:06}D ", seq % 1_000_000).as_bytes());
+        r.extend_from_slice(format!("{}{}{}{}", sta, loc, cha, net).as_bytes());
+        r.extend_from_slice(&(t.year() as u16).to_be_bytes());
+        r.extend_from_slice(&(t.ordinal() as u16).to_be_bytes());
+        r.extend_from_slice(&[t.hour() as u8, t.minute() as u8, sec as u8, 0]);
+        r.extend_from_slice(&((usec / 100) as u16).to_be_bytes());
+        r.extend_from_slice(&(n as u16).to_be_bytes());
+        r.extend_from_slice(&factor.to_be_bytes());
+        r.extend_from_slice(&mult.to_be_bytes());
+        // Activity, IO and Quality flags, Number of blockettes
+        r.extend_from_slice(&[0, 0, 0, if fm.is_some() { 2 } else { 3 }]);
+        r.extend_from_slice(&0i32.to_be_bytes());
+        r.extend_from_slice(&(begin_data as u16).to_be_bytes());
+        r.extend_from_slice(&48u16.to_be_bytes());
+        // Blockette 1000
+        let exp = reclen.trailing_zeros() as u8;
+        r.extend_from_slice(&1000u16.to_be_bytes());
+        r.extend_from_slice(&56u16.to_be_bytes());
+        r.extend_from_slice(&[enc, 1, exp, 0]);
+        // Blockette 1001
+        let next : u16 = if fm.is_some() { 0 } else { 64 };
+        r.extend_from_slice(&1001u16.to_be_bytes());
+        r.extend_from_slice(&next.to_be_bytes());
+        r.extend_from_slice(&[0, (usec % 100) as u8, 0, 0]);
+        // Blockette 100, sample rate not representable by factor and multiplier
+        if fm.is_none() {
+            r.extend_from_slice(&100u16.to_be_bytes());
+            r.extend_from_slice(&0u16.to_be_bytes());
+            r.extend_from_slice(&(rate as f32).to_be_bytes());
+            r.extend_from_slice(&[0; 4]);
+        }
+        r.resize(begin_data, 0);
+        r.extend_from_slice(&data);
+        r.resize(reclen, 0);
+        out.extend_from_slice(&r);
+
+        i += n;
+        seq += 1;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+
+    /// Build a version 2 record of 512 bytes
+    fn v2(sta: &str, sec: u8, nsamp: u16, enc: u8, data: &[u8]) -> Vec<u8> {
+        let mut b = vec![];
+        b.extend_from_slice(b"000001D ");
+        b.extend_from_slice(format!("{:5}{:2}{:3}{:2}", sta, "00", "BHZ", "XX").as_bytes());
+        b.write_u16::<BigEndian>(2019).unwrap();
+        b.write_u16::<BigEndian>(100).unwrap();
+        b.extend_from_slice(&[12, 30, sec, 0]);
+        b.write_u16::<BigEndian>(5000).unwrap();   // 0.5 s
+        b.write_u16::<BigEndian>(nsamp).unwrap();
+        b.write_i16::<BigEndian>(10).unwrap();     // 10 sps
+        b.write_i16::<BigEndian>(1).unwrap();
+        b.extend_from_slice(&[0, 0, 0, 1]);
+        b.write_i32::<BigEndian>(0).unwrap();
+        b.write_u16::<BigEndian>(64).unwrap();
+        b.write_u16::<BigEndian>(48).unwrap();
+        // Blockette 1000
+        b.write_u16::<BigEndian>(1000).unwrap();
+        b.write_u16::<BigEndian>(0).unwrap();
+        b.extend_from_slice(&[enc, 1, 9, 0]);
+        b.resize(64, 0);
+        b.extend_from_slice(data);
+        b.resize(512, 0);
+        b
+    }
+
+    fn be_i32(v: &[i32]) -> Vec<u8> {
+        let mut b = vec![];
+        v.iter().for_each(|x| b.write_i32::<BigEndian>(*x).unwrap());
+        b
+    }
+
+    #[test]
+    fn sample_rates() {
+        assert_eq!(sample_rate(20, 1), 20.0);
+        assert_eq!(sample_rate(20, -2), 10.0);
+        assert_eq!(sample_rate(-10, 1), 0.1);
+        assert_eq!(sample_rate(-10, -2), 0.05);
+    }
+
+    #[test]
+    fn int32() {
+        let buf = v2("ANMO", 0, 4, ENC_INT32, &be_i32(&[1, -2, 3, 100000]));
+        let s = decode(&buf).unwrap();
+        assert_eq!(s.len(), 1);
+        assert_eq!(s[0].y, vec![1., -2., 3., 100000.]);
+        assert_eq!(s[0].delta(), 0.1);
+        assert_eq!(s[0].nslc(), "XX.ANMO.00.BHZ");
+        assert_eq!(format!("{}", s[0].time().unwrap()), "2019-04-10 12:30:00.500");
+    }
+
+    #[test]
+    fn int16_float() {
+        let mut d = vec![];
+        [7i16, -8].iter().for_each(|x| d.write_i16::<BigEndian>(*x).unwrap());
+        let s = decode(&v2("ANMO", 0, 2, ENC_INT16, &d)).unwrap();
+        assert_eq!(s[0].y, vec![7., -8.]);
+
+        let mut d = vec![];
+        [1.5f32, -2.25].iter().for_each(|x| d.write_f32::<BigEndian>(*x).unwrap());
+        let s = decode(&v2("ANMO", 0, 2, ENC_FLOAT32, &d)).unwrap();
+        assert_eq!(s[0].y, vec![1.5, -2.25]);
+
+        let mut d = vec![];
+        [0.5f64, 4.0].iter().for_each(|x| d.write_f64::<BigEndian>(*x).unwrap());
+        let s = decode(&v2("ANMO", 0, 2, ENC_FLOAT64, &d)).unwrap();
+        assert_eq!(s[0].y, vec![0.5, 4.0]);
+    }
+
+    #[test]
+    fn truncated() {
+        let buf = v2("ANMO", 0, 4, ENC_INT32, &be_i32(&[1, -2, 3, 100000]));
+        for n in [52, 54, 55] {
+            assert!(matches!(decode(&buf[..n]), Err(SacError::Format(_))));
+        }
+        // Blockette 100 pointing past the end of the header
+        let mut buf = buf;
+        buf[46..48].copy_from_slice(&[0, 56]);
+        buf[56..60].copy_from_slice(&[0, 100, 0, 0]);
+        assert!(matches!(decode(&buf[..62]), Err(SacError::Format(_))));
+        // Record length exponent too large to shift
+        let mut buf = v2("ANMO", 0, 4, ENC_INT32, &be_i32(&[1, -2, 3, 100000]));
+        buf[54] = 200;
+        assert!(matches!(decode(&buf), Err(SacError::Format(_))));
+    }
+
+    #[test]
+    fn steim1() {
+        // Samples 10, 12, 9, 20 as four 8-bit differences
+        let frame = [1 << 24, 10, 20, i32::from_be_bytes([10, 2, 0xfd, 11])];
+        let s = decode(&v2("ANMO", 0, 4, ENC_STEIM1, &be_i32(&frame))).unwrap();
+        assert_eq!(s[0].y, vec![10., 12., 9., 20.]);
+    }
+
+    #[test]
+    fn steim2() {
+        // Samples -5, 300, -200 as three 10-bit differences
+        let w = (3 << 30) | ((-5i32 as u32 & 0x3ff) << 20) | (305 << 10) | (-500i32 as u32 & 0x3ff);
+        let frame = [2 << 24, -5, -200, w as i32];
+        let s = decode(&v2("ANMO", 0, 3, ENC_STEIM2, &be_i32(&frame))).unwrap();
+        assert_eq!(s[0].y, vec![-5., 300., -200.]);
+    }
+
+    #[test]
+    fn merge_and_gaps() {
+        let mut buf = v2("ANMO", 0, 4, ENC_INT32, &be_i32(&[1, 2, 3, 4]));
+        // Contiguous, 0.5 + 0.4 s
+        let mut r = v2("ANMO", 0, 2, ENC_INT32, &be_i32(&[5, 6]));
+        r[28..30].copy_from_slice(&9000u16.to_be_bytes());
+        buf.extend(r);
+        // Gap
+        buf.extend(v2("ANMO", 10, 2, ENC_INT32, &be_i32(&[7, 8])));
+        // Another station
+        buf.extend(v2("COR", 0, 1, ENC_INT32, &be_i32(&[9])));
+        let s = decode(&buf).unwrap();
+        assert_eq!(s.len(), 3);
+        assert_eq!(s[0].y, vec![1., 2., 3., 4., 5., 6.]);
+        assert_eq!(s[1].y, vec![7., 8.]);
+        assert_eq!(s[2].nslc(), "XX.COR.00.BHZ");
+    }
+
+    #[test]
+    fn v3() {
+        let sid = b"FDSN:IU_ANMO_00_B_H_Z";
+        let data = [1.0f32, 2.0, 3.0];
+        let mut b = vec![];
+        b.extend_from_slice(b"MS");
+        b.extend_from_slice(&[3, 0]);
+        b.write_u32::<LittleEndian>(1_234_567).unwrap();
+        b.write_u16::<LittleEndian>(2020).unwrap();
+        b.write_u16::<LittleEndian>(1).unwrap();
+        b.extend_from_slice(&[1, 2, 3, ENC_FLOAT32]);
+        b.write_f64::<LittleEndian>(-0.5).unwrap();
+        b.write_u32::<LittleEndian>(3).unwrap();
+        b.write_u32::<LittleEndian>(0).unwrap();
+        b.extend_from_slice(&[1, sid.len() as u8]);
+        b.write_u16::<LittleEndian>(0).unwrap();
+        b.write_u32::<LittleEndian>(12).unwrap();
+        b.extend_from_slice(sid);
+        data.iter().for_each(|x| b.write_f32::<LittleEndian>(*x).unwrap());
+        let crc = crc32c(&b);
+        b[28..32].copy_from_slice(&crc.to_le_bytes());
+
+        let s = decode(&b).unwrap();
+        assert_eq!(s[0].nslc(), "IU.ANMO.00.BHZ");
+        assert_eq!(s[0].y, vec![1., 2., 3.]);
+        assert_eq!(s[0].delta(), 0.5);
+        assert_eq!(format!("{}", s[0].time().unwrap()), "2020-01-01 01:02:03.001");
+        assert!((s[0].b() - 234.567e-6).abs() < 1e-9);
+
+        b[50] = 0;
+        assert!(decode(&b).is_err());
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+
+    fn trace(y: Vec<f32>, dt: f64) -> Sac {
+        let mut s = Sac::from_amp(y, 0.00025, dt);
+        s.set_time(time_from_doy(2016, 366, 23, 59, 58, 123_000_000).unwrap());
+        s.set_string(SacString::Network, "IU");
+        s.set_string(SacString::Station, "ANMO");
+        s.set_string(SacString::Location, "10");
+        s.set_string(SacString::Channel, "HHZ");
+        s
+    }
+
+    fn round_trip(s: &Sac, encoding: Encoding, record_length: usize) -> Sac {
+        let opts = WriteOptions { encoding, record_length };
+        let buf = write(s, &opts).unwrap();
+        assert_eq!(buf.len() % record_length, 0);
+        let mut out = decode(&buf).unwrap();
+        assert_eq!(out.len(), 1);
+        out.remove(0)
+    }
+
+    #[test]
+    fn write_steim2() {
+        let y : Vec<f32> = (0 .. 5000)
+            .map(|i| ((i as f32 * 0.37).sin() * 10f32.powi(i % 9)).round())
+            .collect();
+        let s = trace(y, 0.001);
+        let s2 = round_trip(&s, Encoding::Steim2, 256);
+        assert_eq!(s2.y, s.y);
+        assert_eq!(s2.delta(), s.delta());
+        assert_eq!(s2.nslc(), "IU.ANMO.10.HHZ");
+        assert_eq!(s2.time().unwrap(), s.time().unwrap());
+        assert!((s2.b() - s.b()).abs() < 1e-6);
+        // Auto selects Steim2 for integer data
+        assert_eq!(round_trip(&s, Encoding::Auto, 4096).y, s.y);
+    }
+
+    #[test]
+    fn factor_multiplier() {
+        assert_eq!(factor_mult(1.0 / f64::from(0.01f32)), Some((100, 1)));
+        assert_eq!(factor_mult(1.0 / f64::from(0.1f32)), Some((10, 1)));
+        assert_eq!(factor_mult(0.1), Some((-10, 1)));
+        assert_eq!(factor_mult(2.5), Some((25, -10)));
+        assert_eq!(factor_mult(1.0 / f64::from(0.3f32)), None);
+        assert_eq!(factor_mult(100.001), None);
+
+        // Written without blockette 100
+        let s = trace(vec![1.0, 2.0, 3.0], 0.01);
+        let opts = WriteOptions { encoding: Encoding::Int32, record_length: 512 };
+        let buf = write(&s, &opts).unwrap();
+        assert_eq!(BigEndian::read_i16(&buf[32..]), 100);
+        assert_eq!(BigEndian::read_i16(&buf[34..]), 1);
+        assert_eq!(buf[39], 2);
+    }
+
+    #[test]
+    fn write_steim2_large_records() {
+        // Constant data packs more than u16::MAX samples into 65536 bytes
+        let s = trace(vec![7.0; 200_000], 0.01);
+        let s2 = round_trip(&s, Encoding::Steim2, 65536);
+        assert_eq!(s2.y, s.y);
+    }
+
+    #[test]
+    fn write_leap_second() {
+        let mut s = trace((0 .. 100).map(|i| i as f32).collect(), 0.01);
+        let leap = NaiveDate::from_ymd_opt(2016, 12, 31).unwrap()
+            .and_hms_milli_opt(23, 59, 59, 1_250).unwrap();
+        s.set_time(leap);
+        s.set_b(crate::TimeValue::Relative(Duration::zero())).unwrap();
+        let opts = WriteOptions { encoding: Encoding::Int32, record_length: 512 };
+        let buf = write(&s, &opts).unwrap();
+        assert_eq!(&buf[24..27], &[23, 59, 60]);
+        assert_eq!(BigEndian::read_u16(&buf[28..]), 2500);
+        let s2 = decode(&buf).unwrap();
+        assert_eq!(s2[0].time().unwrap(), leap);
+        assert_eq!(s2[0].y, s.y);
+    }
+
+    #[test]
+    fn write_precise_start() {
+        let mut s = trace((0 .. 100).map(|i| i as f32).collect(), 0.01);
+        // b of 1000.000456 s needs the version 7 double precision value
+        let t = s.time().unwrap() + Duration::microseconds(1_000_000_456);
+        s.set_version(7).unwrap();
+        s.set_b(crate::TimeValue::Absolute(t)).unwrap();
+        assert_eq!(s.start_time().unwrap(), t);
+        let opts = WriteOptions { encoding: Encoding::Int32, record_length: 512 };
+        let s2 = decode(&write(&s, &opts).unwrap()).unwrap();
+        assert_eq!(s2[0].start_time().unwrap(), t);
+    }
+
+    #[test]
+    fn write_int32_float32() {
+        let s = trace((0 .. 1000).map(|i| (i * 12345) as f32).collect(), 0.01);
+        assert_eq!(round_trip(&s, Encoding::Int32, 512).y, s.y);
+        let s = trace((0 .. 1000).map(|i| i as f32 * 0.25 - 3.1).collect(), 0.3);
+        let s2 = round_trip(&s, Encoding::Float32, 1024);
+        assert_eq!(s2.y, s.y);
+        assert_eq!(s2.delta(), s.delta());
+        assert_eq!(round_trip(&s, Encoding::Auto, 1024).y, s.y);
+    }
+
+    #[test]
+    fn write_errors() {
+        let mut s = trace(vec![0.5, 1.0], 1.0);
+        let opts = WriteOptions { encoding: Encoding::Steim2, record_length: 512 };
+        assert!(write(&s, &opts).is_err());
+        let opts = WriteOptions { encoding: Encoding::Float32, record_length: 500 };
+        assert!(write(&s, &opts).is_err());
+        s.leven = 0;
+        match write(&s, &Default::default()) {
+            Err(SacError::NotEvenlySpaced) => {},
+            _ => panic!("expected NotEvenlySpaced"),
+        }
+    }
+
+}
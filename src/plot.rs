@@ -0,0 +1,468 @@
+/*! Plotting of SAC files to SVG or PNG images
+
+Requires the `plot` feature, PNG output additionally requires the `png` feature.
+The output format is chosen from the file extension, `.svg` or `.png`.
+
+ Function                 | SAC Command | Description
+ -------------------------|-------------|-------------------------------------
+ [`plot`]                 | plotpk      | Single trace with timing marks
+ [`plot1`]                | plot1       | Traces stacked vertically, sharing the time axis
+ [`prs`]                  | prs         | Record section, traces positioned by distance
+
+```
+use sacio::Sac;
+use sacio::plot;
+# use sacio::SacError;
+
+let s = Sac::from_file("tests/file.sac")?;
+let svg = plot::plot_svg(&s, &plot::PlotOptions::default())?;
+assert!(svg.starts_with("<svg"));
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::path::Path;
+
+use plotters::prelude::*;
+use plotters::coord::Shift;
+use plotters_backend::{BackendColor, BackendCoord, BackendStyle, BackendTextStyle};
+use plotters_backend::DrawingErrorKind;
+
+use crate::Sac;
+use crate::SacError;
+use crate::SacString;
+use crate::Marker;
+use crate::fis;
+use crate::duration_to_f64;
+use crate::SAC_STRING_UNDEF;
+
+/// Options controlling the appearance of a plot
+#[derive(Debug, Clone)]
+pub struct PlotOptions {
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels, per trace for stacked plots
+    pub height: u32,
+    /// Draw timing marks (o, a, t0 - t9)
+    pub markers: bool,
+    /// Title of the plot
+    pub title: Option<String>,
+    /// Record section trace amplitude, relative to the trace spacing
+    pub scale: f32,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions {
+            width: 800,
+            height: 300,
+            markers: true,
+            title: None,
+            scale: 1.0,
+        }
+    }
+}
+
+/// Distance used to position traces within a record section
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SectionDistance {
+    /// Great circle distance in degrees (gcarc)
+    Gcarc,
+    /// Distance in km (dist)
+    Dist,
+}
+
+fn plot_err<E: std::fmt::Display>(e: E) -> SacError {
+    SacError::Plot(e.to_string())
+}
+
+/// SVG backend with fixed text metrics
+///
+/// Text is measured with the same estimate whether or not the `png`
+/// feature provides fonts, so SVG output does not depend on the fonts
+/// installed.
+struct Svg<'a>(SVGBackend<'a>);
+
+type SvgError<'a> = DrawingErrorKind<<SVGBackend<'a> as DrawingBackend>::ErrorType>;
+
+impl<'a> DrawingBackend for Svg<'a> {
+    type ErrorType = <SVGBackend<'a> as DrawingBackend>::ErrorType;
+    fn get_size(&self) -> (u32, u32) {
+        self.0.get_size()
+    }
+    fn ensure_prepared(&mut self) -> Result<(), SvgError<'a>> {
+        self.0.ensure_prepared()
+    }
+    fn present(&mut self) -> Result<(), SvgError<'a>> {
+        self.0.present()
+    }
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), SvgError<'a>> {
+        self.0.draw_pixel(point, color)
+    }
+    fn draw_line<S: BackendStyle>(&mut self, from: BackendCoord, to: BackendCoord,
+                                  style: &S) -> Result<(), SvgError<'a>> {
+        self.0.draw_line(from, to, style)
+    }
+    fn draw_rect<S: BackendStyle>(&mut self, upper_left: BackendCoord, bottom_right: BackendCoord,
+                                  style: &S, fill: bool) -> Result<(), SvgError<'a>> {
+        self.0.draw_rect(upper_left, bottom_right, style, fill)
+    }
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self, path: I, style: &S) -> Result<(), SvgError<'a>> {
+        self.0.draw_path(path, style)
+    }
+    fn draw_circle<S: BackendStyle>(&mut self, center: BackendCoord, radius: u32,
+                                    style: &S, fill: bool) -> Result<(), SvgError<'a>> {
+        self.0.draw_circle(center, radius, style, fill)
+    }
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self, vert: I, style: &S) -> Result<(), SvgError<'a>> {
+        self.0.fill_polygon(vert, style)
+    }
+    fn draw_text<S: BackendTextStyle>(&mut self, text: &str, style: &S,
+                                      pos: BackendCoord) -> Result<(), SvgError<'a>> {
+        self.0.draw_text(text, style, pos)
+    }
+    /// Estimate used by plotters without fonts
+    fn estimate_text_size<S: BackendTextStyle>(&self, text: &str,
+                                               style: &S) -> Result<(u32, u32), SvgError<'a>> {
+        let em = style.size() / 1.24 / 1.24;
+        let w = (em * 0.7 * text.len() as f64).round();
+        let h = (em * 0.24).round() + em.round();
+        Ok((w as u32, h as u32))
+    }
+    fn blit_bitmap(&mut self, pos: BackendCoord, size: (u32, u32),
+                   src: &[u8]) -> Result<(), SvgError<'a>> {
+        self.0.blit_bitmap(pos, size, src)
+    }
+}
+
+/// Time values of each data point
+fn times(s: &Sac) -> Vec<f32> {
+    if s.evenly_spaced() {
+        (0 .. s.y.len()).map(|i| s.b() + i as f32 * s.delta()).collect()
+    } else {
+        s.x.clone()
+    }
+}
+
+/// Offset of each reference time from a common time, the origin time of
+/// the first trace when defined, otherwise the earliest begin time
+fn offsets(sacs: &[Sac]) -> Result<Vec<f32>, SacError> {
+    let undefined = || SacError::Plot("Undefined reference time in record section".into());
+    let refs = sacs.iter()
+        .map(|s| s.time().map_err(|_| undefined()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let zero = match sacs.first().map(|s| s.datetime(Marker::O)) {
+        Some(Ok(o)) => o,
+        _ => sacs.iter()
+            .map(|s| s.datetime(Marker::B).map_err(|_| undefined()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .min()
+            .ok_or_else(undefined)?,
+    };
+    Ok(refs.into_iter().map(|t| duration_to_f64(t - zero) as f32).collect())
+}
+
+/// Minimum and maximum of values, expanded if equal
+fn range(v: impl Iterator<Item = f32>) -> (f32, f32) {
+    let (lo, hi) = v.fold((f32::INFINITY, f32::NEG_INFINITY),
+                          |(lo, hi), x| (lo.min(x), hi.max(x)));
+    if !lo.is_finite() || !hi.is_finite() {
+        (-1.0, 1.0)
+    } else if lo == hi {
+        (lo - 1.0, hi + 1.0)
+    } else {
+        (lo, hi)
+    }
+}
+
+/// Defined timing marks and their labels
+fn markers(s: &Sac) -> Vec<(f32, String)> {
+    let marks = [
+        (s.o, SacString::O, "O"),
+        (s.a, SacString::A, "A"),
+        (s.t0, SacString::T0, "T0"), (s.t1, SacString::T1, "T1"),
+        (s.t2, SacString::T2, "T2"), (s.t3, SacString::T3, "T3"),
+        (s.t4, SacString::T4, "T4"), (s.t5, SacString::T5, "T5"),
+        (s.t6, SacString::T6, "T6"), (s.t7, SacString::T7, "T7"),
+        (s.t8, SacString::T8, "T8"), (s.t9, SacString::T9, "T9"),
+    ];
+    marks.iter()
+        .filter(|(t, _, _)| fis(*t))
+        .map(|(t, key, name)| {
+            let label = s.string(*key);
+            let label = if label == SAC_STRING_UNDEF || label.trim().is_empty() {
+                name.to_string()
+            } else {
+                label.trim().to_string()
+            };
+            (*t, label)
+        })
+        .collect()
+}
+
+/// Name shown for a trace, the nslc code or the filename
+fn trace_name(s: &Sac) -> String {
+    let name = s.nslc();
+    if name == "..." { s.filename().to_string() } else { name }
+}
+
+/// Draw a single trace into an area, sharing the time axis range
+fn draw_trace<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, s: &Sac,
+                                  xr: (f32, f32), title: &str,
+                                  opts: &PlotOptions) -> Result<(), SacError> {
+    let t = times(s);
+    let (ymin, ymax) = range(s.y.iter().cloned());
+    let mut chart = ChartBuilder::on(area)
+        .caption(title, ("sans-serif", 14))
+        .margin(5)
+        .x_label_area_size(25)
+        .y_label_area_size(60)
+        .build_cartesian_2d(xr.0 .. xr.1, ymin .. ymax)
+        .map_err(plot_err)?;
+    chart.configure_mesh()
+        .disable_mesh()
+        .draw()
+        .map_err(plot_err)?;
+    chart.draw_series(LineSeries::new(t.into_iter().zip(s.y.iter().cloned()), &BLACK))
+        .map_err(plot_err)?;
+    if opts.markers {
+        for (tm, label) in markers(s) {
+            if tm < xr.0 || tm > xr.1 {
+                continue;
+            }
+            chart.draw_series(std::iter::once(
+                PathElement::new(vec![(tm, ymin), (tm, ymax)], RED)))
+                .map_err(plot_err)?;
+            chart.draw_series(std::iter::once(
+                Text::new(label, (tm, ymax), ("sans-serif", 12).into_font().color(&RED))))
+                .map_err(plot_err)?;
+        }
+    }
+    Ok(())
+}
+
+fn draw_plot<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, s: &Sac,
+                                 opts: &PlotOptions) -> Result<(), SacError> {
+    area.fill(&WHITE).map_err(plot_err)?;
+    let xr = range(times(s).into_iter());
+    let title = opts.title.clone().unwrap_or_else(|| trace_name(s));
+    draw_trace(area, s, xr, &title, opts)
+}
+
+fn draw_plot1<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, sacs: &[Sac],
+                                  opts: &PlotOptions) -> Result<(), SacError> {
+    area.fill(&WHITE).map_err(plot_err)?;
+    let area = match &opts.title {
+        Some(title) => area.titled(title, ("sans-serif", 16)).map_err(plot_err)?,
+        None => area.clone(),
+    };
+    let xr = range(sacs.iter().flat_map(times));
+    let areas = area.split_evenly((sacs.len().max(1), 1));
+    for (s, a) in sacs.iter().zip(areas.iter()) {
+        draw_trace(a, s, xr, &trace_name(s), opts)?;
+    }
+    Ok(())
+}
+
+fn draw_prs<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, sacs: &[Sac],
+                                dist: SectionDistance,
+                                opts: &PlotOptions) -> Result<(), SacError> {
+    let d = sacs.iter()
+        .map(|s| match dist {
+            SectionDistance::Gcarc => s.dist_deg(),
+            SectionDistance::Dist => s.dist_km(),
+        })
+        .collect::<Vec<f32>>();
+    if d.iter().any(|v| !fis(*v)) {
+        return Err(SacError::Plot("Undefined distance in record section".into()));
+    }
+    area.fill(&WHITE).map_err(plot_err)?;
+
+    let off = offsets(sacs)?;
+    let (dmin, dmax) = range(d.iter().cloned());
+    let amp = opts.scale * (dmax - dmin) / sacs.len().max(1) as f32;
+    let xr = range(sacs.iter().zip(off.iter())
+                   .flat_map(|(s, dt)| times(s).into_iter().map(move |t| t + dt)));
+    let ydesc = match dist {
+        SectionDistance::Gcarc => "Distance (deg)",
+        SectionDistance::Dist => "Distance (km)",
+    };
+    let mut chart = ChartBuilder::on(area)
+        .caption(opts.title.as_deref().unwrap_or("Record Section"), ("sans-serif", 16))
+        .margin(5)
+        .x_label_area_size(35)
+        .y_label_area_size(60)
+        .build_cartesian_2d(xr.0 .. xr.1, (dmin - amp) .. (dmax + amp))
+        .map_err(plot_err)?;
+    chart.configure_mesh()
+        .disable_mesh()
+        .x_desc("Time (s)")
+        .y_desc(ydesc)
+        .draw()
+        .map_err(plot_err)?;
+    for ((s, di), dt) in sacs.iter().zip(d.iter()).zip(off.iter()) {
+        let ymax = s.y.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let norm = if ymax > 0.0 { amp / ymax } else { 0.0 };
+        let pts = times(s).into_iter()
+            .zip(s.y.iter())
+            .map(|(t, y)| (t + dt, di + y * norm));
+        chart.draw_series(LineSeries::new(pts, &BLACK)).map_err(plot_err)?;
+        if opts.markers {
+            for (tm, label) in markers(s) {
+                let tm = tm + dt;
+                if tm < xr.0 || tm > xr.1 {
+                    continue;
+                }
+                chart.draw_series(std::iter::once(
+                    PathElement::new(vec![(tm, di - amp), (tm, di + amp)], RED)))
+                    .map_err(plot_err)?;
+                chart.draw_series(std::iter::once(
+                    Text::new(label, (tm, di + amp), ("sans-serif", 10).into_font().color(&RED))))
+                    .map_err(plot_err)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Render a plot into an SVG string
+fn svg<F>(size: (u32, u32), draw: F) -> Result<String, SacError>
+where F: FnOnce(&DrawingArea<Svg, Shift>) -> Result<(), SacError>
+{
+    let mut buf = String::new();
+    {
+        let root = Svg(SVGBackend::with_string(&mut buf, size)).into_drawing_area();
+        draw(&root)?;
+        root.present().map_err(plot_err)?;
+    }
+    Ok(buf)
+}
+
+/// Render a plot into a file, the format is chosen from the extension
+fn file<P, F>(path: P, size: (u32, u32), draw: F) -> Result<(), SacError>
+where P: AsRef<Path>,
+      F: FnOnce(&DrawingArea<Svg, Shift>) -> Result<(), SacError>,
+{
+    let path = path.as_ref();
+    let ext = path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    match ext.as_deref() {
+        Some("svg") => {
+            let root = Svg(SVGBackend::new(path, size)).into_drawing_area();
+            draw(&root)?;
+            root.present().map_err(plot_err)
+        },
+        _ => Err(SacError::Plot(format!("Unsupported output format: {}",
+                                        path.display()))),
+    }
+}
+
+#[cfg(feature = "png")]
+fn png<P, F>(path: P, size: (u32, u32), draw: F) -> Result<(), SacError>
+where P: AsRef<Path>,
+      F: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), SacError>,
+{
+    let root = BitMapBackend::new(path.as_ref(), size).into_drawing_area();
+    draw(&root)?;
+    root.present().map_err(plot_err)
+}
+
+/// Check if the output should be a PNG image
+#[cfg(feature = "png")]
+fn is_png<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("png"))
+        .unwrap_or(false)
+}
+
+macro_rules! plot_file {
+    ($path:expr, $size:expr, $draw:expr) => {{
+        #[cfg(feature = "png")]
+        {
+            if is_png(&$path) {
+                return png($path, $size, $draw);
+            }
+        }
+        file($path, $size, $draw)
+    }};
+}
+
+/// Plot a single trace with its timing marks
+///
+/// ```
+/// use sacio::Sac;
+/// use sacio::plot;
+/// # use sacio::SacError;
+///
+/// let s = Sac::from_file("tests/file.sac")?;
+/// plot::plot(&s, "tests/plot.svg", &plot::PlotOptions::default())?;
+/// # std::fs::remove_file("tests/plot.svg")?;
+/// # Ok::<(), SacError>(())
+/// ```
+pub fn plot<P: AsRef<Path>>(s: &Sac, path: P, opts: &PlotOptions) -> Result<(), SacError> {
+    plot_file!(path, (opts.width, opts.height), |a| draw_plot(a, s, opts))
+}
+
+/// Plot a single trace with its timing marks as an SVG string
+pub fn plot_svg(s: &Sac, opts: &PlotOptions) -> Result<String, SacError> {
+    svg((opts.width, opts.height), |a| draw_plot(a, s, opts))
+}
+
+/// Plot traces stacked vertically, sharing the time axis
+///
+/// ```
+/// use sacio::SacCollection;
+/// use sacio::plot;
+/// # use sacio::SacError;
+///
+/// let sacs = SacCollection::from_glob("tests/file.sac*")?;
+/// plot::plot1(sacs.as_slice(), "tests/plot1.svg", &plot::PlotOptions::default())?;
+/// # std::fs::remove_file("tests/plot1.svg")?;
+/// # Ok::<(), SacError>(())
+/// ```
+pub fn plot1<P: AsRef<Path>>(sacs: &[Sac], path: P, opts: &PlotOptions) -> Result<(), SacError> {
+    let size = (opts.width, opts.height * sacs.len().max(1) as u32);
+    plot_file!(path, size, |a| draw_plot1(a, sacs, opts))
+}
+
+/// Plot traces stacked vertically as an SVG string
+pub fn plot1_svg(sacs: &[Sac], opts: &PlotOptions) -> Result<String, SacError> {
+    let size = (opts.width, opts.height * sacs.len().max(1) as u32);
+    svg(size, |a| draw_plot1(a, sacs, opts))
+}
+
+/// Plot a record section, traces are positioned by distance
+///
+/// Traces are aligned in absolute time, with the time axis relative
+/// to the origin time of the first trace, or to the earliest begin
+/// time when it has no origin.  Each trace is normalized to its
+/// maximum absolute amplitude.  The distance and reference time of
+/// every trace must be defined.
+///
+/// ```
+/// use sacio::SacCollection;
+/// use sacio::plot;
+/// # use sacio::SacError;
+///
+/// let sacs = SacCollection::from_glob("tests/file.sac*")?;
+/// plot::prs(sacs.as_slice(), plot::SectionDistance::Gcarc,
+///           "tests/prs.svg", &plot::PlotOptions::default())?;
+/// # std::fs::remove_file("tests/prs.svg")?;
+/// # Ok::<(), SacError>(())
+/// ```
+pub fn prs<P: AsRef<Path>>(sacs: &[Sac], dist: SectionDistance,
+                           path: P, opts: &PlotOptions) -> Result<(), SacError> {
+    let size = (opts.width, opts.width);
+    plot_file!(path, size, |a| draw_prs(a, sacs, dist, opts))
+}
+
+/// Plot a record section as an SVG string
+pub fn prs_svg(sacs: &[Sac], dist: SectionDistance,
+               opts: &PlotOptions) -> Result<String, SacError> {
+    svg((opts.width, opts.width), |a| draw_prs(a, sacs, dist, opts))
+}
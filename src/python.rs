@@ -0,0 +1,289 @@
+/*! Python bindings
+
+Requires the `python` feature
+
+Builds a Python extension module `sacio`, e.g. with
+`maturin build --features python` or by copying `libsacio.so` built with
+`cargo rustc --release --lib --features python --crate-type cdylib` to
+`sacio.so`.  The data are NumPy `float32` arrays, owned by the Python
+object and shared rather than copied, so changes made to `s.y` in place
+are kept.  Header values are accessed by their SAC names; undefined
+values are `None`, enumerated values are their SAC names, e.g.
+`"ITIME"`, and logical values are `True` or `False`.
+
+Tests are in `tests/test_python.py`, run them with
+`maturin develop --features python` and `pytest tests/test_python.py`.
+
+```python
+import numpy as np
+import sacio
+
+s = sacio.read("tests/file.sac")
+print(s["delta"], s["kstnm"], s.y.mean())
+s.y *= 2.0
+s["kstnm"] = "PAS"
+s.to_file("pas.sac")
+
+t = sacio.Sac(np.sin(np.arange(100) * 0.1), {"delta": 0.1, "b": 0.0, "kstnm": "SIN"})
+buf = t.write()
+t2 = sacio.Sac.read(buf)
+```
+*/
+
+use numpy::AllowTypeChange;
+use numpy::PyArray1;
+use numpy::PyArrayLike1;
+use numpy::PyArrayMethods;
+use numpy::PyUntypedArrayMethods;
+use pyo3::exceptions::PyIOError;
+use pyo3::exceptions::PyKeyError;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::types::PyDict;
+
+use crate::header;
+use crate::header::Kind;
+use crate::Sac;
+use crate::SacError;
+use crate::SAC_FLOAT_UNDEF;
+use crate::SAC_INT_UNDEF;
+use crate::SAC_STRING_UNDEF;
+
+fn err(e: SacError) -> PyErr {
+    match e {
+        SacError::Io(e) => PyIOError::new_err(e.to_string()),
+        e => PyValueError::new_err(e.to_string()),
+    }
+}
+
+type Array = Py<PyArray1<f32>>;
+
+/// SAC file with the data as NumPy arrays
+#[pyclass(name = "Sac", module = "sacio")]
+pub struct PySac {
+    /// Header, the data are held in `y` and `x`
+    sac: Sac,
+    y: Array,
+    x: Option<Array>,
+}
+
+fn array(py: Python<'_>, v: Vec<f32>) -> Array {
+    PyArray1::from_vec(py, v).unbind()
+}
+
+fn array_like(v: PyArrayLike1<'_, f32, AllowTypeChange>) -> Array {
+    (**v).clone().unbind()
+}
+
+fn len(py: Python<'_>, a: &Array) -> usize {
+    PyUntypedArrayMethods::len(a.bind(py))
+}
+
+fn to_vec(py: Python<'_>, a: &Array) -> Vec<f32> {
+    a.bind(py).readonly().as_array().to_vec()
+}
+
+impl PySac {
+    fn from_sac(py: Python<'_>, mut sac: Sac) -> PySac {
+        let y = array(py, std::mem::take(&mut sac.y));
+        let x = if sac.x.is_empty() {
+            None
+        } else {
+            Some(array(py, std::mem::take(&mut sac.x)))
+        };
+        PySac { sac, y, x }
+    }
+    /// Sac with the current data, checking the lengths
+    fn to_sac(&self, py: Python<'_>) -> PyResult<Sac> {
+        let mut s = self.sac.clone();
+        s.y = to_vec(py, &self.y);
+        s.x = self.x.as_ref().map(|x| to_vec(py, x)).unwrap_or_default();
+        s.npts = s.y.len() as i32;
+        if s.ncomps() == 2 && s.x.len() != s.y.len() {
+            return Err(PyValueError::new_err(
+                format!("x and y lengths differ: {} != {}", s.x.len(), s.y.len())));
+        }
+        s.extrema();
+        Ok(s)
+    }
+}
+
+fn get(py: Python<'_>, s: &Sac, key: &str) -> PyResult<Option<Py<PyAny>>> {
+    if let Some(&v) = header::real(s, key) {
+        return Ok((v != SAC_FLOAT_UNDEF).then(|| v.into_pyobject(py).unwrap().into_any().unbind()));
+    }
+    if let Some(v) = header::string(s, key) {
+        let v = v.trim_end();
+        return Ok((!v.is_empty() && v != SAC_STRING_UNDEF.trim_end())
+                  .then(|| v.into_pyobject(py).unwrap().into_any().unbind()));
+    }
+    match header::int(s, key) {
+        Some(_) if header::kind(key) == Kind::Unused => Err(PyKeyError::new_err(key.to_string())),
+        Some(&v) if v == SAC_INT_UNDEF => Ok(None),
+        Some(&v) => Ok(Some(match header::kind(key) {
+            Kind::Logical => (v != 0).into_pyobject(py)?.to_owned().into_any().unbind(),
+            Kind::Enum => match header::enum_name(v) {
+                Some(name) => name.into_pyobject(py)?.into_any().unbind(),
+                None => v.into_pyobject(py)?.into_any().unbind(),
+            },
+            _ => v.into_pyobject(py)?.into_any().unbind(),
+        })),
+        None => Err(PyKeyError::new_err(key.to_string())),
+    }
+}
+
+fn set(s: &mut Sac, key: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+    let undef = value.is_none();
+    if let Some(v) = header::real_mut(s, key) {
+        *v = if undef { SAC_FLOAT_UNDEF } else { value.extract()? };
+        return Ok(());
+    }
+    if let Some(v) = header::string_mut(s, key) {
+        *v = if undef { SAC_STRING_UNDEF.to_string() } else { value.extract()? };
+        return Ok(());
+    }
+    let kind = header::kind(key);
+    if kind == Kind::Unused {
+        return Err(PyKeyError::new_err(key.to_string()));
+    }
+    let n = if undef {
+        SAC_INT_UNDEF
+    } else {
+        match kind {
+            Kind::Logical => value.extract::<bool>()? as i32,
+            Kind::Enum => match value.extract::<String>() {
+                Ok(name) => header::enum_value(&name).ok_or_else(|| {
+                    PyValueError::new_err(format!("unknown SAC enumerated value: {}", name))
+                })?,
+                Err(_) => value.extract()?,
+            },
+            _ => value.extract()?,
+        }
+    };
+    match header::int_mut(s, key) {
+        Some(v) => { *v = n; Ok(()) },
+        None => Err(PyKeyError::new_err(key.to_string())),
+    }
+}
+
+#[pymethods]
+impl PySac {
+    /// Create from data and a dict of header values
+    ///
+    /// The data are converted to float32, sharing the array when it is
+    /// already float32.  Files are evenly spaced time series unless `x`
+    /// is given; `delta` defaults to 1 and `b` to 0.
+    #[new]
+    #[pyo3(signature = (y, header=None, x=None))]
+    fn new(py: Python<'_>, y: PyArrayLike1<'_, f32, AllowTypeChange>,
+           header: Option<&Bound<'_, PyDict>>,
+           x: Option<PyArrayLike1<'_, f32, AllowTypeChange>>) -> PyResult<PySac> {
+        let n = y.as_array().len();
+        let mut sac = Sac::from_amp(vec![], 0.0, 1.0);
+        sac.leven = x.is_none() as i32;
+        if let Some(h) = header {
+            for (k, v) in h.iter() {
+                let k: String = k.extract()?;
+                set(&mut sac, &k.to_lowercase(), &v)?;
+            }
+        }
+        if crate::iis(sac.npts) && sac.npts != 0 && sac.npts as usize != n {
+            return Err(PyValueError::new_err(
+                format!("npts {} does not match data length {}", sac.npts, n)));
+        }
+        if x.as_ref().map(|x| x.as_array().len() != n).unwrap_or(false) {
+            return Err(PyValueError::new_err("x and y lengths differ"));
+        }
+        let mut s = PySac { sac, y: array_like(y), x: x.map(array_like) };
+        let full = s.to_sac(py)?;
+        s.sac = Sac { y: vec![], x: vec![], ..full };
+        Ok(s)
+    }
+    /// Read a SAC file
+    #[staticmethod]
+    fn from_file(py: Python<'_>, path: &str) -> PyResult<PySac> {
+        Ok(PySac::from_sac(py, Sac::from_file(path).map_err(err)?))
+    }
+    /// Read a SAC file from bytes
+    #[staticmethod]
+    fn read(py: Python<'_>, buf: &[u8]) -> PyResult<PySac> {
+        let s = Sac::read(&mut std::io::Cursor::new(buf)).map_err(err)?;
+        Ok(PySac::from_sac(py, s))
+    }
+    /// Write a SAC file
+    fn to_file(&self, py: Python<'_>, path: &str) -> PyResult<()> {
+        self.to_sac(py)?.to_file(path).map_err(err)
+    }
+    /// SAC file as bytes
+    fn write<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buf = vec![];
+        self.to_sac(py)?.write(&mut buf).map_err(err)?;
+        Ok(PyBytes::new(py, &buf))
+    }
+    /// Dependent variable, amplitude for time series
+    #[getter]
+    fn y(&self, py: Python<'_>) -> Array {
+        self.y.clone_ref(py)
+    }
+    #[setter]
+    fn set_y(&mut self, y: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        self.y = array_like(y);
+    }
+    /// Independent variable, for uneven or spectral files
+    #[getter]
+    fn x(&self, py: Python<'_>) -> Option<Array> {
+        self.x.as_ref().map(|x| x.clone_ref(py))
+    }
+    #[setter]
+    fn set_x(&mut self, x: Option<PyArrayLike1<'_, f32, AllowTypeChange>>) {
+        self.x = x.map(array_like);
+    }
+    /// Filename
+    #[getter]
+    fn filename(&self) -> &str {
+        self.sac.filename()
+    }
+    /// Defined header values as a dict
+    #[getter]
+    fn header<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let d = PyDict::new(py);
+        let mut s = self.sac.clone();
+        s.npts = len(py, &self.y) as i32;
+        for key in header::names() {
+            if let Some(v) = get(py, &s, key)? {
+                d.set_item(key, v)?;
+            }
+        }
+        Ok(d)
+    }
+    fn __getitem__(&self, py: Python<'_>, key: &str) -> PyResult<Option<Py<PyAny>>> {
+        let key = key.to_lowercase();
+        if key == "npts" {
+            return Ok(Some(len(py, &self.y).into_pyobject(py)?.into_any().unbind()));
+        }
+        get(py, &self.sac, &key)
+    }
+    fn __setitem__(&mut self, key: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        set(&mut self.sac, &key.to_lowercase(), value)
+    }
+    fn __len__(&self, py: Python<'_>) -> usize {
+        len(py, &self.y)
+    }
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!("Sac({}, npts={}, delta={})", self.sac.nslc(), len(py, &self.y), self.sac.delta())
+    }
+}
+
+/// Read a SAC file
+#[pyfunction]
+fn read(py: Python<'_>, path: &str) -> PyResult<PySac> {
+    PySac::from_file(py, path)
+}
+
+#[pymodule]
+fn sacio(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySac>()?;
+    m.add_function(wrap_pyfunction!(read, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,568 @@
+/*! QuakeML 1.2 event metadata
+
+Events are read from QuakeML 1.2 files and applied to SAC headers with
+[`Sac::apply_event`].  Picks stored in `t0`-`t9` can be written back out
+as QuakeML with [`Catalog::to_xml`].
+
+ Header   | QuakeML
+ ---------|-------------------------------------------
+ evla     | Preferred origin latitude
+ evlo     | Preferred origin longitude
+ evdp     | Preferred origin depth (m -> km)
+ o        | Preferred origin time
+ mag      | Preferred magnitude
+ imagtyp  | Preferred magnitude type
+ imagsrc  | Preferred magnitude agency
+ ievtyp   | Event type
+ kevnm    | Event description, earthquake name preferred
+ nevid    | Trailing digits of the event publicID
+
+```
+use sacio::Sac;
+use sacio::quakeml::Catalog;
+use sacio::SacMagnitudeType;
+# use sacio::SacError;
+
+let xml = r#"<q:quakeml xmlns:q="http://quakeml.org/xmlns/quakeml/1.2" xmlns="http://quakeml.org/xmlns/bed/1.2">
+  <eventParameters publicID="smi:local/catalog">
+    <event publicID="smi:local/event/8108838">
+      <type>earthquake</type>
+      <origin publicID="smi:local/origin/1">
+        <time><value>1981-03-29T10:38:06.000Z</value></time>
+        <latitude><value>48.0</value></latitude>
+        <longitude><value>-125.0</value></longitude>
+        <depth><value>15000</value></depth>
+      </origin>
+      <magnitude publicID="smi:local/magnitude/1">
+        <mag><value>4.5</value></mag>
+        <type>mb</type>
+      </magnitude>
+    </event>
+  </eventParameters>
+</q:quakeml>"#;
+let cat = Catalog::parse(xml)?;
+
+let mut s = Sac::from_file("tests/file.sac")?;
+s.apply_event(&cat.events[0])?;
+assert_eq!(s.o(), -8.0);
+assert_eq!(s.magnitude_type(), SacMagnitudeType::BodyWave);
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use roxmltree::Node;
+
+use crate::Sac;
+use crate::SacError;
+use crate::Marker;
+use crate::SacEventType;
+use crate::SacMagnitudeSource;
+use crate::SacMagnitudeType;
+use crate::SacString;
+use crate::stationxml::parse_time;
+
+/// Collection of events
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    /// Events
+    pub events: Vec<Event>,
+}
+
+/// Seismic event
+#[derive(Debug, Clone, Default)]
+pub struct Event {
+    /// Resource identifier
+    pub id: String,
+    /// QuakeML event type, e.g. earthquake or quarry blast
+    pub event_type: Option<String>,
+    /// Descriptions as (type, text), e.g. ("earthquake name", "Tohoku")
+    pub descriptions: Vec<(String, String)>,
+    /// Identifier of the preferred origin
+    pub preferred_origin_id: Option<String>,
+    /// Identifier of the preferred magnitude
+    pub preferred_magnitude_id: Option<String>,
+    /// Origins
+    pub origins: Vec<Origin>,
+    /// Magnitudes
+    pub magnitudes: Vec<Magnitude>,
+    /// Picks
+    pub picks: Vec<Pick>,
+}
+
+/// Event location and time
+#[derive(Debug, Clone)]
+pub struct Origin {
+    /// Resource identifier
+    pub id: String,
+    /// Origin time
+    pub time: NaiveDateTime,
+    /// Latitude in degrees
+    pub latitude: f64,
+    /// Longitude in degrees
+    pub longitude: f64,
+    /// Depth in meters
+    pub depth: Option<f64>,
+    /// Agency responsible
+    pub agency: Option<String>,
+}
+
+/// Event magnitude
+#[derive(Debug, Clone)]
+pub struct Magnitude {
+    /// Resource identifier
+    pub id: String,
+    /// Magnitude value
+    pub mag: f64,
+    /// Magnitude type, e.g. mb, Ms or Mw
+    pub magnitude_type: Option<String>,
+    /// Associated origin
+    pub origin_id: Option<String>,
+    /// Agency responsible
+    pub agency: Option<String>,
+}
+
+/// Phase arrival time on a channel
+#[derive(Debug, Clone)]
+pub struct Pick {
+    /// Resource identifier
+    pub id: String,
+    /// Arrival time
+    pub time: NaiveDateTime,
+    /// Channel as `net.sta.loc.chan`
+    pub nslc: String,
+    /// Phase name
+    pub phase_hint: Option<String>,
+}
+
+impl Event {
+    /// Preferred origin, or the first origin
+    pub fn preferred_origin(&self) -> Option<&Origin> {
+        self.preferred_origin_id.as_ref()
+            .and_then(|id| self.origins.iter().find(|o| &o.id == id))
+            .or_else(|| self.origins.first())
+    }
+    /// Preferred magnitude, or the first magnitude
+    pub fn preferred_magnitude(&self) -> Option<&Magnitude> {
+        self.preferred_magnitude_id.as_ref()
+            .and_then(|id| self.magnitudes.iter().find(|m| &m.id == id))
+            .or_else(|| self.magnitudes.first())
+    }
+    /// Event name, earthquake name preferred over other descriptions
+    pub fn name(&self) -> Option<&str> {
+        self.descriptions.iter()
+            .find(|(kind, _)| kind == "earthquake name")
+            .or_else(|| self.descriptions.first())
+            .map(|(_, text)| text.as_str())
+    }
+    /// Numeric event id taken from the trailing digits of the identifier
+    pub fn numeric_id(&self) -> Option<i32> {
+        let n = self.id.len() - self.id.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        self.id[self.id.len() - n..].parse().ok()
+    }
+    /// SAC event type
+    pub fn sac_event_type(&self) -> Option<SacEventType> {
+        self.event_type.as_deref().map(event_type)
+    }
+    /// Create picks from the defined timing marks `t0`-`t9` of `s`
+    ///
+    /// Phase names are taken from `kt0`-`kt9`
+    pub fn add_picks(&mut self, s: &Sac) -> Result<(), SacError> {
+        let keys = [SacString::T0, SacString::T1, SacString::T2, SacString::T3, SacString::T4,
+                    SacString::T5, SacString::T6, SacString::T7, SacString::T8, SacString::T9];
+        for (m, key) in Marker::TIMING.iter().zip(keys.iter()) {
+            let time = match s.datetime(*m) {
+                Ok(t) => t,
+                Err(SacError::NotTime) => continue,
+                Err(e) => return Err(e),
+            };
+            let phase = s.string(*key).trim();
+            let phase = if phase.is_empty() || phase == "-12345" { None } else { Some(phase.to_string()) };
+            self.picks.push(Pick {
+                id: format!("smi:local/pick/{}/{}", s.nslc(), self.picks.len() + 1),
+                time,
+                nslc: s.nslc(),
+                phase_hint: phase,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn event_type(t: &str) -> SacEventType {
+    match t {
+        "earthquake" => SacEventType::Earthquake,
+        "quarry blast" => SacEventType::QuarryBlast,
+        "mining explosion" => SacEventType::QuarryBlastSingle,
+        "rock burst" => SacEventType::QuarryTremor,
+        "collapse" | "mine collapse" => SacEventType::MineCollapse,
+        "explosion" | "controlled explosion" | "experimental explosion" |
+        "industrial explosion" => SacEventType::Explosion,
+        "chemical explosion" => SacEventType::ChemicalExplosion,
+        "nuclear explosion" => SacEventType::NuclearShot,
+        "underwater explosion" => SacEventType::MarineExplosion,
+        "meteorite" | "meteor impact" => SacEventType::MeteoricEvent,
+        "not reported" => SacEventType::Unknown,
+        _ => SacEventType::Other,
+    }
+}
+
+fn magnitude_type(t: &str) -> SacMagnitudeType {
+    let t = t.to_lowercase();
+    if t.starts_with("mb") {
+        SacMagnitudeType::BodyWave
+    } else if t.starts_with("ms") {
+        SacMagnitudeType::SurfaceWave
+    } else if t.starts_with("ml") {
+        SacMagnitudeType::Local
+    } else if t.starts_with("mw") {
+        SacMagnitudeType::Moment
+    } else if t.starts_with("md") || t.starts_with("mc") {
+        SacMagnitudeType::Duration
+    } else {
+        SacMagnitudeType::UserDefined
+    }
+}
+
+fn magnitude_source(agency: Option<&str>) -> SacMagnitudeSource {
+    match agency.map(|a| a.to_uppercase()).as_deref() {
+        Some("NEIC") => SacMagnitudeSource::NEIC,
+        Some("PDE") => SacMagnitudeSource::PDE,
+        Some("PDE-Q") | Some("PDEQ") => SacMagnitudeSource::PDEQ,
+        Some("PDE-W") | Some("PDEW") => SacMagnitudeSource::PDEW,
+        Some("ISC") => SacMagnitudeSource::ISC,
+        Some("IDC") | Some("REB") => SacMagnitudeSource::REB,
+        Some("US") | Some("USGS") => SacMagnitudeSource::USGS,
+        Some("BK") | Some("BERKELEY") => SacMagnitudeSource::Berkeley,
+        Some("CI") | Some("CALTECH") => SacMagnitudeSource::Caltech,
+        Some("LLNL") => SacMagnitudeSource::LLNL,
+        Some("EVLOC") => SacMagnitudeSource::EVLOC,
+        Some("JSOP") => SacMagnitudeSource::JSOP,
+        Some(_) => SacMagnitudeSource::User,
+        None => SacMagnitudeSource::Unknown,
+    }
+}
+
+impl Sac {
+    /// Set event headers from the preferred origin and magnitude of `ev`
+    ///
+    /// The origin time `o` is relative to the reference time, which must
+    /// be defined.  Depths are converted from meters to kilometers.
+    pub fn apply_event(&mut self, ev: &Event) -> Result<(), SacError> {
+        if let Some(org) = ev.preferred_origin() {
+            let dt = org.time - self.time()?;
+            if org.latitude.abs() > 90.0 {
+                return Err(SacError::BadLatitude);
+            }
+            if org.longitude.abs() > 360.0 {
+                return Err(SacError::BadLongitude);
+            }
+            self.o = (dt.num_microseconds().unwrap_or(0) as f64 / 1e6) as f32;
+            self.evla = org.latitude as f32;
+            self.evlo = org.longitude as f32;
+            if let Some(depth) = org.depth {
+                self.evdp = (depth / 1000.0) as f32;
+            }
+            self.update_regions();
+            self.compute_dist_az();
+        }
+        if let Some(m) = ev.preferred_magnitude() {
+            self.mag = m.mag as f32;
+            self.set_magnitude_type(m.magnitude_type.as_deref()
+                                    .map(magnitude_type)
+                                    .unwrap_or(SacMagnitudeType::UserDefined));
+            self.set_magnitude_source(magnitude_source(m.agency.as_deref()));
+        }
+        if let Some(t) = ev.sac_event_type() {
+            self.set_event_type(t);
+        }
+        if let Some(name) = ev.name() {
+            let n = name.char_indices().nth(16).map(|(i, _)| i).unwrap_or(name.len());
+            self.set_string(SacString::EventName, &name[..n]);
+        }
+        if let Some(id) = ev.numeric_id() {
+            self.set_id(crate::SacInt::EventID, id);
+        }
+        Ok(())
+    }
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+fn children<'a, 'b: 'a>(node: Node<'a, 'b>, name: &'a str) -> impl Iterator<Item = Node<'a, 'b>> + 'a {
+    node.children().filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child<'a, 'b>(node: Node<'a, 'b>, name: &str) -> Option<Node<'a, 'b>> {
+    node.children().find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn text(node: Node, name: &str) -> Option<String> {
+    child(node, name).and_then(|n| n.text()).map(|t| t.trim().to_string())
+}
+
+/// Value of a quantity, e.g. <latitude><value>1.0</value></latitude>
+fn value(node: Node, name: &str) -> Option<String> {
+    child(node, name).and_then(|n| text(n, "value"))
+}
+
+fn float(node: Node, name: &str) -> Result<Option<f64>, SacError> {
+    match value(node, name) {
+        None => Ok(None),
+        Some(t) => t.parse().map(Some).map_err(|_| bad(format!("Invalid {}: {:?}", name, t))),
+    }
+}
+
+fn required(node: Node, name: &str) -> Result<f64, SacError> {
+    float(node, name)?.ok_or_else(|| bad(format!("Missing {} in {}", name, node.tag_name().name())))
+}
+
+fn time(node: Node) -> Result<NaiveDateTime, SacError> {
+    value(node, "time").ok_or_else(|| bad(format!("Missing time in {}", node.tag_name().name())))
+        .and_then(|t| parse_time(&t))
+}
+
+fn id(node: Node) -> String {
+    node.attribute("publicID").unwrap_or_default().to_string()
+}
+
+fn agency(node: Node) -> Option<String> {
+    child(node, "creationInfo").and_then(|c| text(c, "agencyID"))
+}
+
+fn parse_origin(node: Node) -> Result<Origin, SacError> {
+    Ok(Origin {
+        id: id(node),
+        time: time(node)?,
+        latitude: required(node, "latitude")?,
+        longitude: required(node, "longitude")?,
+        depth: float(node, "depth")?,
+        agency: agency(node),
+    })
+}
+
+fn parse_magnitude(node: Node) -> Result<Magnitude, SacError> {
+    Ok(Magnitude {
+        id: id(node),
+        mag: required(node, "mag")?,
+        magnitude_type: text(node, "type"),
+        origin_id: text(node, "originID"),
+        agency: agency(node),
+    })
+}
+
+fn parse_pick(node: Node) -> Result<Pick, SacError> {
+    let w = child(node, "waveformID");
+    let attr = |name| w.and_then(|w| w.attribute(name)).unwrap_or_default();
+    Ok(Pick {
+        id: id(node),
+        time: time(node)?,
+        nslc: format!("{}.{}.{}.{}", attr("networkCode"), attr("stationCode"),
+                      attr("locationCode"), attr("channelCode")),
+        phase_hint: text(node, "phaseHint"),
+    })
+}
+
+fn parse_event(node: Node) -> Result<Event, SacError> {
+    Ok(Event {
+        id: id(node),
+        event_type: text(node, "type"),
+        descriptions: children(node, "description")
+            .filter_map(|d| text(d, "text").map(|t| (text(d, "type").unwrap_or_default(), t)))
+            .collect(),
+        preferred_origin_id: text(node, "preferredOriginID"),
+        preferred_magnitude_id: text(node, "preferredMagnitudeID"),
+        origins: children(node, "origin").map(parse_origin).collect::<Result<_, _>>()?,
+        magnitudes: children(node, "magnitude").map(parse_magnitude).collect::<Result<_, _>>()?,
+        picks: children(node, "pick").map(parse_pick).collect::<Result<_, _>>()?,
+    })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn time_str(t: NaiveDateTime) -> String {
+    t.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string()
+}
+
+impl Catalog {
+    /// Parse a QuakeML 1.2 document
+    pub fn parse(xml: &str) -> Result<Catalog, SacError> {
+        let doc = roxmltree::Document::parse(xml)
+            .map_err(|e| SacError::Format(format!("QuakeML: {}", e)))?;
+        let events = doc.descendants()
+            .filter(|n| n.is_element() && n.tag_name().name() == "eventParameters")
+            .flat_map(|p| children(p, "event"))
+            .map(parse_event)
+            .collect::<Result<_, _>>()?;
+        Ok(Catalog { events })
+    }
+    /// Read a QuakeML 1.2 file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Catalog, SacError> {
+        Catalog::parse(&std::fs::read_to_string(path)?)
+    }
+    /// Write the catalog as a QuakeML 1.2 document
+    ///
+    /// ```
+    /// use sacio::quakeml::{Catalog, Event};
+    /// # use sacio::SacError;
+    ///
+    /// let ev = Event {
+    ///     id: "smi:local/event/1".into(),
+    ///     event_type: Some("earthquake".into()),
+    ///     ..Default::default()
+    /// };
+    /// let cat = Catalog { events: vec![ev] };
+    ///
+    /// let cat2 = Catalog::parse(&cat.to_xml())?;
+    /// assert_eq!(cat2.events[0].event_type.as_deref(), Some("earthquake"));
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<q:quakeml xmlns:q=\"http://quakeml.org/xmlns/quakeml/1.2\" xmlns=\"http://quakeml.org/xmlns/bed/1.2\">\n");
+        out.push_str("  <eventParameters publicID=\"smi:local/sacio\">\n");
+        for ev in &self.events {
+            out.push_str(&format!("    <event publicID=\"{}\">\n", escape(&ev.id)));
+            if let Some(id) = &ev.preferred_origin_id {
+                out.push_str(&format!("      <preferredOriginID>{}</preferredOriginID>\n", escape(id)));
+            }
+            if let Some(id) = &ev.preferred_magnitude_id {
+                out.push_str(&format!("      <preferredMagnitudeID>{}</preferredMagnitudeID>\n", escape(id)));
+            }
+            if let Some(t) = &ev.event_type {
+                out.push_str(&format!("      <type>{}</type>\n", escape(t)));
+            }
+            for (kind, text) in &ev.descriptions {
+                out.push_str("      <description>\n");
+                out.push_str(&format!("        <text>{}</text>\n", escape(text)));
+                if !kind.is_empty() {
+                    out.push_str(&format!("        <type>{}</type>\n", escape(kind)));
+                }
+                out.push_str("      </description>\n");
+            }
+            for p in &ev.picks {
+                let parts: Vec<_> = p.nslc.split('.').chain(std::iter::repeat("")).take(4).collect();
+                out.push_str(&format!("      <pick publicID=\"{}\">\n", escape(&p.id)));
+                out.push_str(&format!("        <time><value>{}</value></time>\n", time_str(p.time)));
+                out.push_str(&format!("        <waveformID networkCode=\"{}\" stationCode=\"{}\" locationCode=\"{}\" channelCode=\"{}\"/>\n",
+                                      escape(parts[0]), escape(parts[1]), escape(parts[2]), escape(parts[3])));
+                if let Some(ph) = &p.phase_hint {
+                    out.push_str(&format!("        <phaseHint>{}</phaseHint>\n", escape(ph)));
+                }
+                out.push_str("      </pick>\n");
+            }
+            for o in &ev.origins {
+                out.push_str(&format!("      <origin publicID=\"{}\">\n", escape(&o.id)));
+                out.push_str(&format!("        <time><value>{}</value></time>\n", time_str(o.time)));
+                out.push_str(&format!("        <latitude><value>{}</value></latitude>\n", o.latitude));
+                out.push_str(&format!("        <longitude><value>{}</value></longitude>\n", o.longitude));
+                if let Some(d) = o.depth {
+                    out.push_str(&format!("        <depth><value>{}</value></depth>\n", d));
+                }
+                if let Some(a) = &o.agency {
+                    out.push_str(&format!("        <creationInfo><agencyID>{}</agencyID></creationInfo>\n", escape(a)));
+                }
+                out.push_str("      </origin>\n");
+            }
+            for m in &ev.magnitudes {
+                out.push_str(&format!("      <magnitude publicID=\"{}\">\n", escape(&m.id)));
+                out.push_str(&format!("        <mag><value>{}</value></mag>\n", m.mag));
+                if let Some(t) = &m.magnitude_type {
+                    out.push_str(&format!("        <type>{}</type>\n", escape(t)));
+                }
+                if let Some(id) = &m.origin_id {
+                    out.push_str(&format!("        <originID>{}</originID>\n", escape(id)));
+                }
+                if let Some(a) = &m.agency {
+                    out.push_str(&format!("        <creationInfo><agencyID>{}</agencyID></creationInfo>\n", escape(a)));
+                }
+                out.push_str("      </magnitude>\n");
+            }
+            out.push_str("    </event>\n");
+        }
+        out.push_str("  </eventParameters>\n");
+        out.push_str("</q:quakeml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mappings() {
+        assert_eq!(magnitude_type("Mww"), SacMagnitudeType::Moment);
+        assert_eq!(magnitude_type("mB"), SacMagnitudeType::BodyWave);
+        assert_eq!(magnitude_type("Ms_20"), SacMagnitudeType::SurfaceWave);
+        assert_eq!(magnitude_type("ML"), SacMagnitudeType::Local);
+        assert_eq!(magnitude_type("M"), SacMagnitudeType::UserDefined);
+        assert_eq!(magnitude_source(Some("us")), SacMagnitudeSource::USGS);
+        assert_eq!(magnitude_source(Some("GCMT")), SacMagnitudeSource::User);
+        assert_eq!(magnitude_source(None), SacMagnitudeSource::Unknown);
+        assert_eq!(event_type("quarry blast"), SacEventType::QuarryBlast);
+        assert_eq!(event_type("landslide"), SacEventType::Other);
+    }
+
+    #[test]
+    fn round_trip() {
+        let t = parse_time("1981-03-29T10:38:06.5Z").unwrap();
+        let ev = Event {
+            id: "quakeml:local/event/123".into(),
+            event_type: Some("quarry blast".into()),
+            descriptions: vec![("region name".into(), "Somewhere".into()),
+                               ("earthquake name".into(), "A very long event name".into())],
+            preferred_origin_id: Some("o2".into()),
+            preferred_magnitude_id: None,
+            origins: vec![
+                Origin { id: "o1".into(), time: t, latitude: 0.0, longitude: 0.0, depth: None, agency: None },
+                Origin { id: "o2".into(), time: t, latitude: 48.0, longitude: -125.0,
+                         depth: Some(15000.0), agency: Some("ISC".into()) },
+            ],
+            magnitudes: vec![Magnitude { id: "m1".into(), mag: 5.1, magnitude_type: Some("Mw".into()),
+                                         origin_id: Some("o2".into()), agency: Some("US".into()) }],
+            picks: vec![],
+        };
+        let cat = Catalog::parse(&Catalog { events: vec![ev] }.to_xml()).unwrap();
+        let ev = &cat.events[0];
+        assert_eq!(ev.numeric_id(), Some(123));
+        assert_eq!(ev.preferred_origin().unwrap().id, "o2");
+
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.apply_event(ev).unwrap();
+        assert_eq!((s.evla, s.evlo, s.evdp), (48.0, -125.0, 15.0));
+        assert_eq!(s.o(), -7.5);
+        assert_eq!(s.mag, 5.1);
+        assert_eq!(s.magnitude_type(), SacMagnitudeType::Moment);
+        assert_eq!(s.magnitude_source(), SacMagnitudeSource::USGS);
+        assert_eq!(s.event_type(), SacEventType::QuarryBlast);
+        assert_eq!(s.string(SacString::EventName), "A very long even");
+        assert_eq!(s.id(crate::SacInt::EventID), 123);
+    }
+
+    #[test]
+    fn picks() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_string(SacString::Network, "XX");
+        s.t0 = 12.5;
+        s.set_string(SacString::T0, "P");
+        s.t3 = 20.0;
+        let mut ev = Event::default();
+        ev.add_picks(&s).unwrap();
+        let cat = Catalog::parse(&Catalog { events: vec![ev] }.to_xml()).unwrap();
+        let picks = &cat.events[0].picks;
+        assert_eq!(picks.len(), 2);
+        assert_eq!(picks[0].phase_hint.as_deref(), Some("P"));
+        assert_eq!(picks[0].time, s.datetime(Marker::T0).unwrap());
+        assert_eq!(picks[0].nslc, s.nslc());
+        assert_eq!(picks[1].phase_hint, None);
+        assert_eq!(picks[1].time, s.datetime(Marker::T3).unwrap());
+    }
+}
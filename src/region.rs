@@ -0,0 +1,234 @@
+/*! Flinn-Engdahl geographic and seismic regions
+
+The 757 geographic regions of the 1995 revision are grouped into 50
+seismic regions.  Geographic regions are numbered so that most seismic
+regions are a consecutive range; those added in 1995 (730-757) are
+assigned to the seismic region containing them.
+
+```
+use sacio::Sac;
+# use sacio::SacError;
+
+let s = Sac::from_file("tests/file.sac")?;
+let r = s.event_region_name().unwrap();
+assert_eq!(r.number, 25);
+assert_eq!(r.name, "VANCOUVER ISLAND, CANADA REGION");
+assert_eq!(r.seismic_number, 2);
+assert_eq!(r.seismic_name, "Southeastern Alaska to Washington");
+# Ok::<(), SacError>(())
+```
+
+References
+  - Flinn, E.A., Engdahl, E.R. and Hill, A.R., 1974, Seismic and
+    geographical regionalization, BSSA, 64, 771-993.
+  - Young, J.B., Presgrave, B.W., Aichele, H., Wiens, D.A. and Flinn, E.A.,
+    1996, The Flinn-Engdahl Regionalisation Scheme: the 1995 revision,
+    PEPI, 96, 223-297.
+*/
+
+use flinn_engdahl as fe;
+
+use crate::fis;
+use crate::Sac;
+use crate::SacString;
+use crate::SAC_STRING_UNDEF;
+
+/// Seismic region names
+const SEISMIC_NAMES: [&str; 50] = [
+    "Alaska-Aleutian Arc",
+    "Southeastern Alaska to Washington",
+    "Oregon, California and Nevada",
+    "Baja California and Gulf of California",
+    "Mexico-Guatemala area",
+    "Central America",
+    "Caribbean loop",
+    "Andean South America",
+    "Extreme South America",
+    "Southern Antilles",
+    "New Zealand region",
+    "Kermadec-Tonga-Samoa area",
+    "Fiji Islands area",
+    "New Hebrides Islands",
+    "Bismarck and Solomon Islands",
+    "New Guinea",
+    "Caroline Islands to Guam",
+    "Guam to Japan",
+    "Japan-Kurils-Kamchatka",
+    "Southwestern Japan and Ryukyu Islands",
+    "Taiwan",
+    "Philippines",
+    "Borneo-Sulawesi",
+    "Sunda Arc",
+    "Myanmar and Southeast Asia",
+    "India-Xizang-Sichuan-Yunnan",
+    "Southern Xinjiang to Gansu",
+    "Alma-Ata to Lake Baikal",
+    "Western Asia",
+    "Middle East-Crimea-Eastern Balkans",
+    "Western Mediterranean area",
+    "Atlantic Ocean",
+    "Indian Ocean",
+    "Eastern North America",
+    "Eastern South America",
+    "Northwestern Europe",
+    "Africa",
+    "Australia",
+    "Pacific Basin",
+    "Arctic Zone",
+    "Eastern Asia",
+    "Northeastern Asia, Northern Alaska to Greenland",
+    "Southeastern and Antarctic Pacific Ocean",
+    "Galapagos area",
+    "Macquarie loop",
+    "Andaman Islands to Sumatra",
+    "Baluchistan",
+    "Hindu Kush and Pamir",
+    "Northern Asia",
+    "Antarctica",
+];
+
+/// Last geographic region of each seismic region, for regions 1-729
+const SEISMIC_LAST: [usize; 50] = [
+     18,  29,  46,  50,  71,  83, 101, 142, 146, 157,
+    168, 179, 182, 189, 195, 208, 210, 216, 229, 241,
+    247, 260, 272, 293, 301, 319, 325, 334, 356, 375,
+    401, 414, 437, 527, 531, 549, 587, 610, 632, 655,
+    666, 682, 692, 699, 702, 708, 712, 720, 726, 729,
+];
+
+/// Seismic regions of the geographic regions added in 1995, 730-757
+const SEISMIC_1995: [usize; 28] = [
+    44,  7, 10, 25, 25, 25, 25, 25, 32, 32,
+    33, 33, 33, 37, 37, 37, 37, 37, 37, 37,
+    37, 37, 37, 37, 37, 37, 43, 44,
+];
+
+/// Flinn-Engdahl region
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    /// Geographic region number, 1-757
+    pub number: i32,
+    /// Geographic region name, e.g. "SOUTHERN CALIFORNIA"
+    pub name: &'static str,
+    /// Seismic region number, 1-50
+    pub seismic_number: i32,
+    /// Seismic region name, e.g. "Oregon, California and Nevada"
+    pub seismic_name: &'static str,
+}
+
+/// Seismic region number of a geographic region number
+pub fn seismic_region(number: usize) -> Option<usize> {
+    match number {
+        1..=729 => SEISMIC_LAST.iter().position(|&last| number <= last).map(|i| i + 1),
+        730..=757 => Some(SEISMIC_1995[number - 730]),
+        _ => None,
+    }
+}
+
+/// Seismic region name from its number, 1-50
+pub fn seismic_region_name(number: usize) -> Option<&'static str> {
+    SEISMIC_NAMES.get(number.checked_sub(1)?).copied()
+}
+
+impl Region {
+    /// Region containing (`lat`, `lon`)
+    pub fn from_location(lat: f64, lon: f64) -> Option<Region> {
+        let n = fe::region_number(lat, lon).ok()?;
+        let name = fe::region(lat, lon).ok()?;
+        let seismic = seismic_region(n)?;
+        Some(Region {
+            number: n as i32,
+            name,
+            seismic_number: seismic as i32,
+            seismic_name: seismic_region_name(seismic)?,
+        })
+    }
+}
+
+impl Sac {
+    /// Flinn-Engdahl region of the event, if the event location is defined
+    pub fn event_region_name(&self) -> Option<Region> {
+        if fis(self.evla) && fis(self.evlo) {
+            Region::from_location(self.evla as f64, self.evlo as f64)
+        } else {
+            None
+        }
+    }
+    /// Flinn-Engdahl region of the station, if the station location is defined
+    pub fn station_region_name(&self) -> Option<Region> {
+        if fis(self.stla) && fis(self.stlo) {
+            Region::from_location(self.stla as f64, self.stlo as f64)
+        } else {
+            None
+        }
+    }
+    /// Set the event name (kevnm) to the event region name if undefined
+    ///
+    /// A name previously filled in from the region follows the event
+    /// location, and becomes undefined with it.  Names set otherwise are
+    /// kept.  The name is truncated to 16 characters
+    pub(crate) fn default_event_name(&mut self) {
+        let name = self.kevnm.trim();
+        let filled = self.region_event_name.as_deref() == Some(name);
+        if !filled && name != SAC_STRING_UNDEF.trim() && !name.is_empty() {
+            return;
+        }
+        match self.event_region_name() {
+            Some(r) => {
+                let n = r.name.char_indices().nth(16).map(|(i, _)| i).unwrap_or(r.name.len());
+                self.set_string(SacString::EventName, &r.name[..n]);
+                self.region_event_name = Some(self.kevnm.trim().to_string());
+            },
+            None if filled => {
+                self.set_string(SacString::EventName, SAC_STRING_UNDEF);
+                self.region_event_name = None;
+            },
+            None => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seismic_regions() {
+        assert_eq!(seismic_region(1), Some(1));
+        assert_eq!(seismic_region(43), Some(3));
+        assert_eq!(seismic_region(729), Some(50));
+        assert_eq!(seismic_region(731), Some(7));
+        assert_eq!(seismic_region(757), Some(44));
+        assert_eq!(seismic_region(0), None);
+        assert_eq!(seismic_region(758), None);
+        assert_eq!(seismic_region_name(50), Some("Antarctica"));
+        assert_eq!(seismic_region_name(0), None);
+
+        let r = Region::from_location(34.0, -118.0).unwrap();
+        assert_eq!(r.name, "SOUTHERN CALIFORNIA");
+        assert_eq!(r.seismic_name, "Oregon, California and Nevada");
+        let r = Region::from_location(-20.0, -175.0).unwrap();
+        assert_eq!(r.seismic_number, 12);
+    }
+
+    #[test]
+    fn event_name() {
+        let mut s = Sac::new();
+        assert_eq!(s.event_region_name(), None);
+        s.set_event_location(36.0, 70.5, 200.0).unwrap();
+        let r = s.event_region_name().unwrap();
+        assert_eq!(r.name, "HINDU KUSH REGION, AFGHANISTAN");
+        assert_eq!(r.seismic_name, "Hindu Kush and Pamir");
+        assert_eq!(s.string(SacString::EventName), "HINDU KUSH REGIO");
+
+        // Filled in names follow the location
+        s.set_event_location(34.0, -118.0, 10.0).unwrap();
+        assert_eq!(s.string(SacString::EventName), "SOUTHERN CALIFOR");
+        s.set_event_location(-20.0, -175.0, 10.0).unwrap();
+        assert_eq!(s.string(SacString::EventName), "TONGA ISLANDS");
+
+        s.set_string(SacString::EventName, "Big One");
+        s.set_event_location(34.0, -118.0, 10.0).unwrap();
+        assert_eq!(s.string(SacString::EventName), "Big One");
+    }
+}
@@ -0,0 +1,442 @@
+/*! SEED RESP response files
+
+RESP files, as written by rdseed or the FDSN web services, are parsed
+into a [`Response`] for each channel epoch.  Responses are evaluated in
+the same manner as evalresp and may be used with
+[`Sac::remove_response`](crate::Sac::remove_response).
+
+Supported blockettes are 50 and 52 (station and channel), 53 (poles and
+zeros), 54 (coefficients), 57 (decimation), 58 (sensitivity or gain) and
+61 (FIR).  Comments (59) are ignored.  Response lists (55), response
+references (60), polynomials (62) and dictionary blockettes are reported
+as errors.
+
+```
+use sacio::resp::RespFile;
+use sacio::response::FrequencyResponse;
+use chrono::NaiveDate;
+# use sacio::SacError;
+
+let text = "
+B050F03     Station:     CDV
+B050F16     Network:     XX
+B052F03     Location:    ??
+B052F04     Channel:     BHZ
+B052F22     Start date:  1980,001,00:00:00
+B052F23     End date:    No Ending Time
+B053F03     Transfer function type:                A [Laplace Transform (Rad/sec)]
+B053F04     Stage sequence number:                 1
+B053F05     Response in units lookup:              M/S - Velocity in Meters Per Second
+B053F06     Response out units lookup:             V - Volts
+B053F07     A0 normalization factor:               +1.00000E+00
+B053F08     Normalization frequency:               +1.00000E+00
+B053F09     Number of zeroes:                      0
+B053F14     Number of poles:                       0
+B058F03     Stage sequence number:                 1
+B058F04     Sensitivity:                           +1.50000E+03
+B058F05     Frequency of sensitivity:              +1.00000E+00
+";
+let resp = RespFile::parse(text)?;
+let t = NaiveDate::from_ymd_opt(1981, 3, 29).unwrap().and_hms_opt(0, 0, 0).unwrap();
+let r = resp.response("XX.CDV..BHZ", t).unwrap();
+assert_eq!(r.evaluate(1.0).norm(), 1500.0);
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+
+use crate::SacError;
+use crate::response::{Complex64, Filter, PolesZeros, Response, Sensitivity, Stage, Symmetry, Transfer};
+
+/// Channel epoch and its response
+#[derive(Debug, Clone, Default)]
+pub struct Channel {
+    /// Network code
+    pub network: String,
+    /// Station code
+    pub station: String,
+    /// Location code, empty if none
+    pub location: String,
+    /// Channel code
+    pub channel: String,
+    /// Start of epoch
+    pub start: Option<NaiveDateTime>,
+    /// End of epoch, None if open
+    pub end: Option<NaiveDateTime>,
+    /// Instrument response
+    pub response: Response,
+}
+
+impl Channel {
+    /// Channel code as `net.sta.loc.chan`
+    pub fn nslc(&self) -> String {
+        format!("{}.{}.{}.{}", self.network, self.station, self.location, self.channel)
+    }
+}
+
+/// Contents of a RESP file
+#[derive(Debug, Clone, Default)]
+pub struct RespFile {
+    /// Channel epochs
+    pub channels: Vec<Channel>,
+}
+
+impl RespFile {
+    /// Parse the contents of a RESP file
+    pub fn parse(text: &str) -> Result<RespFile, SacError> {
+        let mut p = Parser::default();
+        for line in text.lines() {
+            p.line(line)?;
+        }
+        p.flush()?;
+        p.finish_channel();
+        Ok(RespFile { channels: p.channels })
+    }
+    /// Read a RESP file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<RespFile, SacError> {
+        RespFile::parse(&std::fs::read_to_string(path)?)
+    }
+    /// Find the channel epoch for a `net.sta.loc.chan` code at time `t`
+    pub fn channel(&self, nslc: &str, t: NaiveDateTime) -> Option<&Channel> {
+        let nslc = nslc.replace(".--.", "..");
+        self.channels.iter().find(|c| {
+            c.nslc() == nslc
+                && c.start.map(|s| s <= t).unwrap_or(true)
+                && c.end.map(|e| t < e).unwrap_or(true)
+        })
+    }
+    /// Find the response for a `net.sta.loc.chan` code at time `t`
+    pub fn response(&self, nslc: &str, t: NaiveDateTime) -> Option<&Response> {
+        self.channel(nslc, t).map(|c| &c.response)
+    }
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+/// Fields of a single blockette
+#[derive(Default)]
+struct Blockette {
+    number: u32,
+    fields: BTreeMap<u32, String>,
+    rows: Vec<(u32, Vec<f64>)>,
+}
+
+impl Blockette {
+    fn text(&self, f: u32) -> &str {
+        self.fields.get(&f).map(|s| s.as_str()).unwrap_or("")
+    }
+    /// First word of a field, e.g. the unit in "M/S - Velocity"
+    fn word(&self, f: u32) -> String {
+        self.text(f).split_whitespace().next().unwrap_or("").to_string()
+    }
+    fn float(&self, f: u32) -> Result<f64, SacError> {
+        let w = self.word(f);
+        w.parse().map_err(|_| bad(format!("Invalid B{:03}F{:02}: {:?}", self.number, f, w)))
+    }
+    fn stage(&self, f: u32) -> Result<u32, SacError> {
+        Ok(self.float(f)? as u32)
+    }
+    /// Values of data rows starting at field `f`, skipping the index
+    fn column(&self, f: u32, col: usize) -> Vec<f64> {
+        self.rows.iter()
+            .filter(|(k, _)| *k == f)
+            .filter_map(|(_, v)| v.get(col).copied())
+            .collect()
+    }
+    fn complex(&self, f: u32) -> Vec<Complex64> {
+        self.column(f, 1).into_iter()
+            .zip(self.column(f, 2))
+            .map(|(re, im)| Complex64::new(re, im))
+            .collect()
+    }
+}
+
+fn transfer(code: &str) -> Result<Transfer, SacError> {
+    match code {
+        "A" => Ok(Transfer::LaplaceRadians),
+        "B" => Ok(Transfer::LaplaceHertz),
+        "D" => Ok(Transfer::Digital),
+        _ => Err(bad(format!("Unknown transfer function type: {:?}", code))),
+    }
+}
+
+fn parse_date(t: &str) -> Result<Option<NaiveDateTime>, SacError> {
+    let t = t.trim();
+    if t.is_empty() || t.starts_with("No Ending") {
+        return Ok(None);
+    }
+    let parts: Vec<_> = t.split(',').collect();
+    let num = |i: usize| -> Result<u32, SacError> {
+        parts.get(i).unwrap_or(&"0").trim().parse().map_err(|_| bad(format!("Invalid date: {:?}", t)))
+    };
+    let date = NaiveDate::from_yo_opt(num(0)? as i32, num(1)?)
+        .ok_or_else(|| bad(format!("Invalid date: {:?}", t)))?;
+    let hms = parts.get(2).unwrap_or(&"00:00:00");
+    let time = chrono::NaiveTime::parse_from_str(hms.trim(), "%H:%M:%S%.f")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(hms.trim(), "%H:%M"))
+        .map_err(|_| bad(format!("Invalid date: {:?}", t)))?;
+    Ok(Some(NaiveDateTime::new(date, time)))
+}
+
+#[derive(Default)]
+struct Parser {
+    channels: Vec<Channel>,
+    cur: Option<Channel>,
+    stages: BTreeMap<u32, Stage>,
+    sensitivity: Option<Sensitivity>,
+    blk: Blockette,
+}
+
+impl Parser {
+    fn line(&mut self, line: &str) -> Result<(), SacError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || !line.starts_with('B') || line.len() < 7 {
+            return Ok(());
+        }
+        let key = line.split_whitespace().next().unwrap_or("");
+        let rest = line[key.len()..].trim();
+        let number: u32 = key.get(1..4).and_then(|k| k.parse().ok())
+            .ok_or_else(|| bad(format!("Invalid RESP key: {}", key)))?;
+        let field: u32 = key.get(5..).and_then(|k| k.split('-').next())
+            .and_then(|k| k.parse().ok())
+            .ok_or_else(|| bad(format!("Invalid RESP key: {}", key)))?;
+        // Blockettes begin at field 3, which may repeat for the same type
+        if number != self.blk.number || (field == 3 && !self.blk.fields.is_empty()) {
+            self.flush()?;
+            self.blk.number = number;
+        }
+        match rest.split_once(':') {
+            Some((_, value)) => { self.blk.fields.insert(field, value.trim().to_string()); },
+            None => {
+                let v = rest.split_whitespace()
+                    .map(|w| w.parse().map_err(|_| bad(format!("Invalid RESP value: {:?}", w))))
+                    .collect::<Result<_, _>>()?;
+                self.blk.rows.push((field, v));
+            },
+        }
+        Ok(())
+    }
+
+    fn stage(&mut self, n: u32) -> &mut Stage {
+        self.stages.entry(n).or_default()
+    }
+
+    fn finish_channel(&mut self) {
+        if let Some(mut c) = self.cur.take() {
+            let stages: Vec<Stage> = std::mem::take(&mut self.stages).into_values().collect();
+            c.response = Response {
+                input_units: stages.first().map(|s| s.input_units.clone()).unwrap_or_default(),
+                output_units: stages.last().map(|s| s.output_units.clone()).unwrap_or_default(),
+                sensitivity: self.sensitivity.take(),
+                stages,
+            };
+            self.channels.push(c);
+        }
+        self.stages.clear();
+        self.sensitivity = None;
+    }
+
+    fn flush(&mut self) -> Result<(), SacError> {
+        let b = std::mem::take(&mut self.blk);
+        match b.number {
+            0 => {},
+            50 => {
+                self.finish_channel();
+                self.cur = Some(Channel {
+                    station: b.word(3),
+                    network: b.word(16),
+                    ..Default::default()
+                });
+            },
+            52 => {
+                let c = self.cur.get_or_insert_with(Channel::default);
+                let loc = b.word(3);
+                c.location = if loc == "??" || loc == "--" { String::new() } else { loc };
+                c.channel = b.word(4);
+                c.start = parse_date(b.text(22))?;
+                c.end = parse_date(b.text(23))?;
+            },
+            53 => {
+                let pz = PolesZeros {
+                    transfer: transfer(&b.word(3))?,
+                    normalization_factor: b.float(7)?,
+                    normalization_frequency: b.float(8)?,
+                    zeros: b.complex(10),
+                    poles: b.complex(15),
+                };
+                let s = self.stage(b.stage(4)?);
+                s.filter = Some(Filter::PolesZeros(pz));
+                s.input_units = b.word(5);
+                s.output_units = b.word(6);
+            },
+            54 => {
+                let filter = Filter::Coefficients {
+                    transfer: transfer(&b.word(3))?,
+                    numerator: b.column(8, 1),
+                    denominator: b.column(11, 1),
+                };
+                let s = self.stage(b.stage(4)?);
+                s.filter = Some(filter);
+                s.input_units = b.word(5);
+                s.output_units = b.word(6);
+            },
+            57 => {
+                let (rate, factor, corr) = (b.float(4)?, b.float(5)?, b.float(8)?);
+                let s = self.stage(b.stage(3)?);
+                s.input_sample_rate = Some(rate);
+                s.decimation = factor as u32;
+                s.correction = corr;
+            },
+            58 => {
+                let (value, frequency) = (b.float(4)?, b.float(5)?);
+                match b.stage(3)? {
+                    0 => self.sensitivity = Some(Sensitivity { value, frequency }),
+                    n => {
+                        let s = self.stage(n);
+                        s.gain = value;
+                        s.gain_frequency = frequency;
+                    }
+                }
+            },
+            61 => {
+                let symmetry = match b.word(5).as_str() {
+                    "B" => Symmetry::Odd,
+                    "C" => Symmetry::Even,
+                    _ => Symmetry::None,
+                };
+                let filter = Filter::Fir { symmetry, coefficients: b.column(9, 1) };
+                let s = self.stage(b.stage(3)?);
+                s.filter = Some(filter);
+                s.input_units = b.word(6);
+                s.output_units = b.word(7);
+            },
+            // Comments
+            59 => {},
+            n => return Err(bad(format!("Unsupported RESP blockette: {}", n))),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::FrequencyResponse;
+
+    const RESP: &str = "
+#
+B050F03     Station:     ANMO
+B050F16     Network:     IU
+B052F03     Location:    00
+B052F04     Channel:     BHZ
+B052F22     Start date:  2002,323,00:00:00
+B052F23     End date:    2008,182,00:00:00.0000
+#
+B053F03     Transfer function type:                A [Laplace Transform (Rad/sec)]
+B053F04     Stage sequence number:                 1
+B053F05     Response in units lookup:              M/S - Velocity in Meters Per Second
+B053F06     Response out units lookup:             V - Volts
+B053F07     A0 normalization factor:               +2.00000E+00
+B053F08     Normalization frequency:               +1.00000E+00
+B053F09     Number of zeroes:                      1
+B053F14     Number of poles:                       1
+#              Complex zeroes:
+#              i  real          imag          real_error    imag_error
+B053F10-13     0  +0.00000E+00  +0.00000E+00  +0.00000E+00  +0.00000E+00
+#              Complex poles:
+B053F15-18     0  -1.00000E+00  +0.00000E+00  +0.00000E+00  +0.00000E+00
+B058F03     Stage sequence number:                 1
+B058F04     Sensitivity:                           +1.00000E+03
+B058F05     Frequency of sensitivity:              +1.00000E+00
+B058F06     Number of calibrations:                0
+B054F03     Transfer function type:                D
+B054F04     Stage sequence number:                 2
+B054F05     Response in units lookup:              V - Volts
+B054F06     Response out units lookup:             COUNTS - Digital Counts
+B054F07     Number of numerators:                  0
+B054F10     Number of denominators:                0
+B057F03     Stage sequence number:                 2
+B057F04     Input sample rate:                     +4.00000E+01
+B057F05     Decimation factor:                     00001
+B057F06     Decimation offset:                     00000
+B057F07     Estimated delay (seconds):             +0.0000E+00
+B057F08     Correction applied (seconds):          +0.0000E+00
+B058F03     Stage sequence number:                 2
+B058F04     Sensitivity:                           +4.00000E+02
+B058F05     Frequency of sensitivity:              +1.00000E+00
+B061F03     Stage sequence number:                 3
+B061F04     Response Name:                         FIR_1
+B061F05     Symmetry type:                         B
+B061F06     Response in units lookup:              COUNTS - Digital Counts
+B061F07     Response out units lookup:             COUNTS - Digital Counts
+B061F08     Number of numerators:                  2
+#              Numerator coefficients:
+#              i, coefficient
+B061F09    0  +2.50000E-01
+B061F09    1  +5.00000E-01
+B057F03     Stage sequence number:                 3
+B057F04     Input sample rate:                     +4.00000E+01
+B057F05     Decimation factor:                     00002
+B057F06     Decimation offset:                     00000
+B057F07     Estimated delay (seconds):             +2.5000E-02
+B057F08     Correction applied (seconds):          +2.5000E-02
+B058F03     Stage sequence number:                 3
+B058F04     Sensitivity:                           +1.00000E+00
+B058F05     Frequency of sensitivity:              +1.00000E+00
+B058F03     Stage sequence number:                 0
+B058F04     Sensitivity:                           +4.00000E+05
+B058F05     Frequency of sensitivity:              +1.00000E+00
+#
+B050F03     Station:     ANMO
+B050F16     Network:     IU
+B052F03     Location:    00
+B052F04     Channel:     BHZ
+B052F22     Start date:  2008,182,00:00:00
+B052F23     End date:    No Ending Time
+B058F03     Stage sequence number:                 0
+B058F04     Sensitivity:                           +8.00000E+05
+B058F05     Frequency of sensitivity:              +1.00000E+00
+";
+
+    #[test]
+    fn parse() {
+        let r = RespFile::parse(RESP).unwrap();
+        assert_eq!(r.channels.len(), 2);
+        let t = parse_date("2005,001").unwrap().unwrap();
+        let c = r.channel("IU.ANMO.00.BHZ", t).unwrap();
+        assert_eq!(c.end, parse_date("2008,182,00:00:00").unwrap());
+        let resp = &c.response;
+        assert_eq!(resp.stages.len(), 3);
+        assert_eq!(resp.input_units, "M/S");
+        assert_eq!(resp.output_units, "COUNTS");
+        assert_eq!(resp.sensitivity.as_ref().unwrap().value, 4e5);
+        assert_eq!(resp.stages[1].input_sample_rate, Some(40.0));
+        assert_eq!(resp.stages[2].decimation, 2);
+        assert_eq!(resp.stages[2].filter,
+                   Some(Filter::Fir { symmetry: Symmetry::Odd, coefficients: vec![0.25, 0.5] }));
+
+        // 2 s / (s + 1) at s = i => 1 + i, with gains 1000 * 400 and
+        //   the FIR [0.25, 0.5, 0.25] at w = 2 pi f dt = 1 / 40
+        let h = resp.evaluate(1.0 / (2.0 * std::f64::consts::PI));
+        let expected = Complex64::new(1.0, 1.0) * 4e5 * (0.5 + 0.5 * (1.0f64 / 40.0).cos());
+        assert!((h - expected).norm() < 1e-6 * expected.norm());
+
+        let t = parse_date("2010,001").unwrap().unwrap();
+        assert_eq!(r.response("IU.ANMO.00.BHZ", t).unwrap().evaluate(1.0).re, 8e5);
+        assert!(r.response("IU.ANMO.10.BHZ", t).is_none());
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(RespFile::parse("B062F03     Transfer function type:   P").is_err());
+        assert!(RespFile::parse("B060F03     Number of stages:      1").is_err());
+        assert!(RespFile::parse("B059F05     Comment code key:      1").is_ok());
+    }
+}
@@ -0,0 +1,579 @@
+/*! Instrument responses
+
+A [`Response`] is a sequence of [`Stage`]s, each with an optional
+filter and a gain.  The complete response at a frequency is the product
+of the response of each stage, following the conventions of evalresp.
+Responses from StationXML or RESP files are removed from data with
+[`Sac::remove_response`].
+
+```
+use sacio::response::{Complex64, FrequencyResponse, PolesZeros, Response, Stage, Transfer};
+
+// Single pole at the origin, i.e. an integrator
+let pz = PolesZeros {
+    transfer: Transfer::LaplaceRadians,
+    normalization_factor: 1.0,
+    normalization_frequency: 1.0,
+    zeros: vec![],
+    poles: vec![Complex64::new(0.0, 0.0)],
+};
+let resp = Response {
+    stages: vec![Stage::from(pz)],
+    ..Default::default()
+};
+let h = resp.evaluate(1.0 / (2.0 * std::f64::consts::PI));
+assert!((h - Complex64::new(0.0, -1.0)).norm() < 1e-12);
+```
+*/
+
+use std::f64::consts::PI;
+
+pub use num_complex::Complex64;
+
+use crate::Sac;
+use crate::SacDataType;
+use crate::SacError;
+
+/// Evaluate a complex frequency response
+pub trait FrequencyResponse {
+    /// Response at frequency `freq` in Hz
+    fn evaluate(&self, freq: f64) -> Complex64;
+}
+
+/// Transfer function type of poles and zeros or coefficients
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Transfer {
+    /// Laplace transform in radians/second
+    LaplaceRadians,
+    /// Laplace transform in Hz
+    LaplaceHertz,
+    /// Digital, z-transform
+    Digital,
+}
+
+impl Transfer {
+    /// Complex variable of the transform at frequency `freq`, `dt` is
+    ///   the sample interval of digital stages
+    fn variable(self, freq: f64, dt: f64) -> Complex64 {
+        match self {
+            Transfer::LaplaceRadians => Complex64::new(0.0, 2.0 * PI * freq),
+            Transfer::LaplaceHertz => Complex64::new(0.0, freq),
+            Transfer::Digital => Complex64::new(0.0, 2.0 * PI * freq * dt).exp(),
+        }
+    }
+}
+
+/// Poles and zeros
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolesZeros {
+    /// Transfer function type
+    pub transfer: Transfer,
+    /// Normalization factor, A0
+    pub normalization_factor: f64,
+    /// Frequency of normalization in Hz
+    pub normalization_frequency: f64,
+    /// Zeros
+    pub zeros: Vec<Complex64>,
+    /// Poles
+    pub poles: Vec<Complex64>,
+}
+
+impl PolesZeros {
+    fn eval(&self, freq: f64, dt: f64) -> Complex64 {
+        let s = self.transfer.variable(freq, dt);
+        let num = self.zeros.iter().fold(Complex64::new(1.0, 0.0), |a, z| a * (s - z));
+        let den = self.poles.iter().fold(Complex64::new(1.0, 0.0), |a, p| a * (s - p));
+        self.normalization_factor * num / den
+    }
+}
+
+/// Symmetry of FIR coefficients
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Symmetry {
+    /// All coefficients given
+    None,
+    /// First half given, odd number of coefficients
+    Odd,
+    /// First half given, even number of coefficients
+    Even,
+}
+
+/// Filter of a response stage
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Poles and zeros
+    PolesZeros(PolesZeros),
+    /// Numerator and denominator coefficients
+    Coefficients {
+        /// Transfer function type
+        transfer: Transfer,
+        /// Numerator coefficients
+        numerator: Vec<f64>,
+        /// Denominator coefficients
+        denominator: Vec<f64>,
+    },
+    /// Finite impulse response filter
+    Fir {
+        /// Symmetry of coefficients
+        symmetry: Symmetry,
+        /// Coefficients
+        coefficients: Vec<f64>,
+    },
+}
+
+impl Filter {
+    fn eval(&self, freq: f64, dt: f64) -> Complex64 {
+        match self {
+            Filter::PolesZeros(pz) => pz.eval(freq, dt),
+            Filter::Coefficients { transfer, numerator, denominator } => {
+                // Empty coefficients are a gain only stage
+                let num = if numerator.is_empty() {
+                    Complex64::new(1.0, 0.0)
+                } else {
+                    polynomial(*transfer, numerator, freq, dt)
+                };
+                if denominator.is_empty() {
+                    num
+                } else {
+                    num / polynomial(*transfer, denominator, freq, dt)
+                }
+            },
+            Filter::Fir { symmetry, coefficients } => {
+                match symmetry {
+                    Symmetry::None => polynomial(Transfer::Digital, coefficients, freq, dt),
+                    // Symmetric filters are zero phase once delay corrected
+                    _ => {
+                        let h = full_fir(*symmetry, coefficients);
+                        let n = h.len() as f64;
+                        let w = 2.0 * PI * freq * dt;
+                        let amp: f64 = h.iter().enumerate()
+                            .map(|(k, c)| c * (w * (k as f64 - (n - 1.0) / 2.0)).cos())
+                            .sum();
+                        Complex64::new(amp, 0.0)
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Expand FIR coefficients to the full set
+pub(crate) fn full_fir(symmetry: Symmetry, h: &[f64]) -> Vec<f64> {
+    let mut full = h.to_vec();
+    match symmetry {
+        Symmetry::None => {},
+        Symmetry::Odd => full.extend(h.iter().rev().skip(1)),
+        Symmetry::Even => full.extend(h.iter().rev()),
+    }
+    full
+}
+
+/// Evaluate sum c_k x^k (Laplace) or sum c_k z^-k (Digital)
+fn polynomial(transfer: Transfer, c: &[f64], freq: f64, dt: f64) -> Complex64 {
+    let x = match transfer {
+        Transfer::Digital => transfer.variable(freq, dt).inv(),
+        _ => transfer.variable(freq, dt),
+    };
+    c.iter().rev().fold(Complex64::new(0.0, 0.0), |a, &v| a * x + v)
+}
+
+/// A single response stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    /// Filter, if any, otherwise a gain only stage
+    pub filter: Option<Filter>,
+    /// Stage gain
+    pub gain: f64,
+    /// Frequency of the stage gain in Hz
+    pub gain_frequency: f64,
+    /// Input sample rate of digital stages in Hz
+    pub input_sample_rate: Option<f64>,
+    /// Decimation factor
+    pub decimation: u32,
+    /// Delay correction applied in seconds
+    pub correction: f64,
+    /// Input units
+    pub input_units: String,
+    /// Output units
+    pub output_units: String,
+}
+
+impl Default for Stage {
+    fn default() -> Self {
+        Stage {
+            filter: None,
+            gain: 1.0,
+            gain_frequency: 0.0,
+            input_sample_rate: None,
+            decimation: 1,
+            correction: 0.0,
+            input_units: String::new(),
+            output_units: String::new(),
+        }
+    }
+}
+
+impl From<PolesZeros> for Stage {
+    fn from(pz: PolesZeros) -> Self {
+        Stage { filter: Some(Filter::PolesZeros(pz)), ..Default::default() }
+    }
+}
+
+impl FrequencyResponse for Stage {
+    fn evaluate(&self, freq: f64) -> Complex64 {
+        let dt = self.input_sample_rate.map(|r| 1.0 / r).unwrap_or(0.0);
+        let mut h = match &self.filter {
+            Some(f) => f.eval(freq, dt),
+            None => Complex64::new(1.0, 0.0),
+        };
+        // Remove the delay compensated for by the data logger
+        if let Some(Filter::Fir { symmetry: Symmetry::None, .. }) | Some(Filter::Coefficients { .. }) = self.filter {
+            h *= Complex64::new(0.0, 2.0 * PI * freq * self.correction).exp();
+        }
+        self.gain * h
+    }
+}
+
+/// Overall sensitivity of a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    /// Sensitivity
+    pub value: f64,
+    /// Frequency of sensitivity in Hz
+    pub frequency: f64,
+}
+
+/// Complete instrument response
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Response {
+    /// Response stages
+    pub stages: Vec<Stage>,
+    /// Overall sensitivity
+    pub sensitivity: Option<Sensitivity>,
+    /// Input units of the first stage, e.g. M/S
+    pub input_units: String,
+    /// Output units of the last stage, e.g. COUNTS
+    pub output_units: String,
+}
+
+impl FrequencyResponse for Response {
+    fn evaluate(&self, freq: f64) -> Complex64 {
+        if self.stages.is_empty() {
+            let v = self.sensitivity.as_ref().map(|s| s.value).unwrap_or(1.0);
+            return Complex64::new(v, 0.0);
+        }
+        self.stages.iter()
+            .fold(Complex64::new(1.0, 0.0), |a, s| a * s.evaluate(freq))
+    }
+}
+
+impl Response {
+    /// Amplitude type of the response input units
+    ///
+    /// ```
+    /// use sacio::response::Response;
+    /// use sacio::SacDataType;
+    ///
+    /// let r = Response { input_units: "m/s".into(), ..Default::default() };
+    /// assert_eq!(r.data_type(), SacDataType::Velocity);
+    /// ```
+    pub fn data_type(&self) -> SacDataType {
+        units_to_data_type(&self.input_units)
+    }
+}
+
+/// Convert ground motion units to an amplitude type
+pub(crate) fn units_to_data_type(units: &str) -> SacDataType {
+    let u = units.trim().to_uppercase().replace(' ', "");
+    match u.as_str() {
+        "M" | "NM" | "CM" | "MM" => SacDataType::Displacement,
+        "M/S" | "NM/S" | "CM/S" | "MM/S" | "M/SEC" => SacDataType::Velocity,
+        "M/S**2" | "M/S2" | "M/S/S" | "M/SEC**2" | "NM/S**2" | "NM/S/S" | "CM/S**2" | "CM/S/S" | "MM/S**2" | "MM/S/S" =>
+            SacDataType::Acceleration,
+        "V" | "VOLTS" => SacDataType::Volts,
+        _ => SacDataType::None,
+    }
+}
+
+/// Factor converting ground motion units to SI units, e.g. 1e-9 for nm/s
+fn units_to_si(units: &str) -> f64 {
+    let u = units.trim().to_uppercase();
+    if u.starts_with("NM") {
+        1e-9
+    } else if u.starts_with("MM") {
+        1e-3
+    } else if u.starts_with("CM") {
+        1e-2
+    } else {
+        1.0
+    }
+}
+
+/// Options for removing an instrument response
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RemoveOptions {
+    /// Output amplitude type, default is the response input units
+    pub output: Option<SacDataType>,
+    /// Cosine taper corner frequencies f1 < f2 < f3 < f4 in Hz
+    pub prefilter: Option<[f64; 4]>,
+    /// Water level in dB below the maximum of the response
+    pub water_level: Option<f64>,
+}
+
+impl Default for RemoveOptions {
+    fn default() -> Self {
+        RemoveOptions { output: None, prefilter: None, water_level: Some(60.0) }
+    }
+}
+
+fn order(t: SacDataType) -> Option<i32> {
+    match t {
+        SacDataType::Displacement => Some(0),
+        SacDataType::Velocity => Some(1),
+        SacDataType::Acceleration => Some(2),
+        _ => None,
+    }
+}
+
+/// Cosine taper, 0 outside [f1, f4] and 1 within [f2, f3]
+fn taper(f: f64, c: &[f64; 4]) -> f64 {
+    if f <= c[0] || f >= c[3] {
+        0.0
+    } else if f < c[1] {
+        0.5 * (1.0 - (PI * (f - c[0]) / (c[1] - c[0])).cos())
+    } else if f > c[2] {
+        0.5 * (1.0 + (PI * (f - c[2]) / (c[3] - c[2])).cos())
+    } else {
+        1.0
+    }
+}
+
+/// In place radix-2 FFT, `x.len()` must be a power of 2
+fn fft(x: &mut [Complex64], inverse: bool) {
+    let n = x.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            x.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let w = Complex64::new(0.0, sign * 2.0 * PI / len as f64).exp();
+        for start in (0..n).step_by(len) {
+            let mut wk = Complex64::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let a = x[start + k];
+                let b = x[start + k + len / 2] * wk;
+                x[start + k] = a + b;
+                x[start + k + len / 2] = a - b;
+                wk *= w;
+            }
+        }
+        len <<= 1;
+    }
+    if inverse {
+        x.iter_mut().for_each(|v| *v /= n as f64);
+    }
+}
+
+impl Sac {
+    /// Remove an instrument response by spectral division
+    ///
+    /// The mean is removed before division and the response is limited
+    /// by the water level.  The output is in SI units, e.g. m/s for
+    /// velocity, and the amplitude type `idep` is set to match.  Responses
+    /// in nm, mm or cm are scaled to m.
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// use sacio::SacDataType;
+    /// use sacio::response::{Response, Stage, RemoveOptions};
+    /// # use sacio::SacError;
+    ///
+    /// let mut s = Sac::from_file("tests/file.sac")?;
+    /// let y0 = s.y.clone();
+    /// // Flat response of 2 counts per m/s
+    /// let resp = Response {
+    ///     stages: vec![Stage { gain: 2.0, ..Default::default() }],
+    ///     input_units: "M/S".into(),
+    ///     ..Default::default()
+    /// };
+    /// s.remove_response(&resp, &RemoveOptions::default())?;
+    /// assert_eq!(s.data_type(), SacDataType::Velocity);
+    /// let mean = y0.iter().sum::<f32>() / y0.len() as f32;
+    /// assert!((s.y[10] - (y0[10] - mean) / 2.0).abs() < 1e-4);
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn remove_response(&mut self, resp: &Response, opts: &RemoveOptions) -> Result<(), SacError> {
+        if !self.is_time() {
+            return Err(SacError::NotTime);
+        }
+        if !self.evenly_spaced() {
+            return Err(SacError::NotEvenlySpaced);
+        }
+        let input = resp.data_type();
+        let output = opts.output.unwrap_or(input);
+        let (ni, no) = match (order(input), order(output)) {
+            (Some(ni), Some(no)) => (ni, no),
+            _ => return Err(SacError::Response(format!("Cannot convert {} to {:?}", resp.input_units, output))),
+        };
+        let n = self.y.len();
+        if n == 0 {
+            return Ok(());
+        }
+        let dt = f64::from(self.delta);
+        let nfft = (2 * n).next_power_of_two();
+        let mean = self.y.iter().map(|&v| f64::from(v)).sum::<f64>() / n as f64;
+        let mut x = vec![Complex64::new(0.0, 0.0); nfft];
+        for (xi, &v) in x.iter_mut().zip(&self.y) {
+            xi.re = f64::from(v) - mean;
+        }
+        fft(&mut x, false);
+
+        let half = nfft / 2;
+        let si = units_to_si(&resp.input_units);
+        let freq: Vec<f64> = (0..=half).map(|k| k as f64 / (nfft as f64 * dt)).collect();
+        let h: Vec<Complex64> = freq.iter().map(|&f| resp.evaluate(f)).collect();
+        let level = opts.water_level
+            .map(|wl| h.iter().map(|v| v.norm()).fold(0.0, f64::max) * 10f64.powf(-wl / 20.0))
+            .unwrap_or(0.0);
+        for k in 0..=half {
+            let mut hk = h[k];
+            let amp = hk.norm();
+            if amp < level {
+                hk = if amp > 0.0 { hk * (level / amp) } else { Complex64::new(level, 0.0) };
+            }
+            let w = Complex64::new(0.0, 2.0 * PI * freq[k]);
+            let mut v = if hk.norm() > 0.0 { x[k] * si / hk } else { Complex64::new(0.0, 0.0) };
+            if no != ni {
+                v = if freq[k] > 0.0 { v * w.powi(no - ni) } else { Complex64::new(0.0, 0.0) };
+            }
+            if let Some(c) = &opts.prefilter {
+                v *= taper(freq[k], c);
+            }
+            x[k] = v;
+            if k > 0 && k < half {
+                x[nfft - k] = v.conj();
+            }
+        }
+        // Nyquist is real for a real signal
+        x[half].im = 0.0;
+        fft(&mut x, true);
+        for (v, xi) in self.y.iter_mut().zip(&x) {
+            *v = xi.re as f32;
+        }
+        self.extrema_amp();
+        self.set_amp_type(output);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fir_symmetry() {
+        assert_eq!(full_fir(Symmetry::Odd, &[1.0, 2.0, 3.0]), vec![1.0, 2.0, 3.0, 2.0, 1.0]);
+        assert_eq!(full_fir(Symmetry::Even, &[1.0, 2.0]), vec![1.0, 2.0, 2.0, 1.0]);
+        // Normalized FIR filter has unit gain at 0 Hz
+        let stage = Stage {
+            filter: Some(Filter::Fir { symmetry: Symmetry::Odd, coefficients: vec![0.25, 0.5] }),
+            input_sample_rate: Some(100.0),
+            ..Default::default()
+        };
+        assert!((stage.evaluate(0.0) - Complex64::new(1.0, 0.0)).norm() < 1e-12);
+        // Nyquist is zeroed by this filter
+        assert!(stage.evaluate(50.0).norm() < 1e-12);
+    }
+
+    #[test]
+    fn digital() {
+        // y_n = x_n - x_{n-1}, a first difference
+        let stage = Stage {
+            filter: Some(Filter::Coefficients {
+                transfer: Transfer::Digital,
+                numerator: vec![1.0, -1.0],
+                denominator: vec![],
+            }),
+            input_sample_rate: Some(100.0),
+            ..Default::default()
+        };
+        assert!(stage.evaluate(0.0).norm() < 1e-12);
+        assert!((stage.evaluate(50.0) - Complex64::new(2.0, 0.0)).norm() < 1e-12);
+    }
+    #[test]
+    fn fft_round_trip() {
+        let y: Vec<Complex64> = (0..16).map(|i| Complex64::new(i as f64, 0.0)).collect();
+        let mut x = y.clone();
+        fft(&mut x, false);
+        assert!((x[0].re - 120.0).abs() < 1e-9);
+        fft(&mut x, true);
+        for (a, b) in x.iter().zip(&y) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn remove_integrate() {
+        // Velocity sine with a flat response, integrated to displacement
+        let dt = 0.01;
+        let f = 2.0;
+        let y: Vec<f32> = (0..1000).map(|i| (2.0 * PI * f * i as f64 * dt).sin() as f32).collect();
+        let mut s = Sac::from_amp(y, 0.0, dt);
+        let resp = Response {
+            stages: vec![Stage { gain: 1.0, ..Default::default() }],
+            input_units: "M/S".into(),
+            ..Default::default()
+        };
+        let opts = RemoveOptions { output: Some(SacDataType::Displacement), ..Default::default() };
+        s.remove_response(&resp, &opts).unwrap();
+        assert_eq!(s.data_type(), SacDataType::Displacement);
+        // -cos(w t) / w, up to a constant offset
+        let w = 2.0 * PI * f;
+        let d = |i: usize| f64::from(s.y[i]) + (w * i as f64 * dt).cos() / w;
+        for i in 100..900 {
+            assert!((d(i) - d(100)).abs() < 0.01 / w);
+        }
+
+        let resp = Response { input_units: "COUNTS".into(), ..Default::default() };
+        assert!(s.remove_response(&resp, &RemoveOptions::default()).is_err());
+    }
+
+    #[test]
+    fn remove_nanometers() {
+        let y: Vec<f32> = (0..100).map(|i| (i % 7) as f32).collect();
+        let mean = y.iter().sum::<f32>() / y.len() as f32;
+        for (units, si) in [("NM/S", 1e-9), ("mm/s", 1e-3), ("CM/S**2", 1e-2), ("M", 1.0)] {
+            let mut s = Sac::from_amp(y.clone(), 0.0, 0.01);
+            let resp = Response {
+                stages: vec![Stage { gain: 2.0, ..Default::default() }],
+                input_units: units.into(),
+                ..Default::default()
+            };
+            s.remove_response(&resp, &RemoveOptions::default()).unwrap();
+            for (v, v0) in s.y.iter().zip(&y) {
+                let want = f64::from(v0 - mean) / 2.0 * si;
+                assert!((f64::from(*v) - want).abs() < 1e-5 * si);
+            }
+        }
+    }
+
+    #[test]
+    fn taper_corners() {
+        let c = [1.0, 2.0, 4.0, 8.0];
+        assert_eq!(taper(0.5, &c), 0.0);
+        assert!((taper(1.5, &c) - 0.5).abs() < 1e-12);
+        assert_eq!(taper(3.0, &c), 1.0);
+        assert!((taper(6.0, &c) - 0.5).abs() < 1e-12);
+        assert_eq!(taper(9.0, &c), 0.0);
+    }
+}
@@ -0,0 +1,661 @@
+/*! Conversion between SEG-Y or Seismic Unix traces and SAC files
+
+SEG-Y files, revision 1 and 2, are read in either byte order with
+IBM float, IEEE float or integer samples.  Seismic Unix (SU) files
+are a sequence of SEG-Y trace headers and IEEE float samples without
+the textual and binary file headers.
+
+ Header   | SEG-Y trace header
+ ---------|-------------------------------------------
+ delta    | Sample interval, dt (us)
+ nz*      | Year, day, hour, minute and second
+ b        | Delay recording time, delrt (ms)
+ stlo     | Receiver group x, gx
+ stla     | Receiver group y, gy
+ stel     | Receiver group elevation, gelev
+ evlo     | Source x, sx
+ evla     | Source y, sy
+ nwfid    | Trace sequence number within line, tracl
+ norid    | Original field record number, fldr
+
+Coordinates are only mapped when given in seconds of arc, decimal
+degrees or degrees, minutes and seconds (coordinate units 2, 3 or 4).
+
+```
+use sacio::Sac;
+use sacio::Marker;
+use sacio::segy;
+# use sacio::SacError;
+
+let s = Sac::from_file("tests/file.sac")?;
+let buf = segy::write(&[s.clone()], &Default::default())?;
+
+let sacs = segy::decode(&buf)?;
+assert_eq!(sacs.len(), 1);
+assert_eq!(sacs[0].y, s.y);
+let dt = sacs[0].datetime(Marker::B)? - s.datetime(Marker::B)?;
+assert!(dt.num_microseconds().unwrap().abs() < 1000);
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+
+use byteorder::{BigEndian, LittleEndian, ByteOrder, NativeEndian};
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use chrono::Timelike;
+
+use crate::Sac;
+use crate::SacError;
+use crate::Marker;
+use crate::SacInt;
+use crate::fis;
+use crate::iis;
+
+const TEXT_HEADER : usize = 3200;
+const BINARY_HEADER : usize = 400;
+const TRACE_HEADER : usize = 240;
+
+/// Sample format of SEG-Y data
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SegyFormat {
+    /// 4-byte IBM floating point
+    IbmFloat,
+    /// 4-byte two's complement integer
+    Int32,
+    /// 2-byte two's complement integer
+    Int16,
+    /// 4-byte IEEE floating point
+    IeeeFloat,
+    /// 8-byte IEEE floating point
+    IeeeDouble,
+    /// 1-byte two's complement integer
+    Int8,
+}
+
+impl SegyFormat {
+    fn code(self) -> u16 {
+        match self {
+            SegyFormat::IbmFloat   => 1,
+            SegyFormat::Int32      => 2,
+            SegyFormat::Int16      => 3,
+            SegyFormat::IeeeFloat  => 5,
+            SegyFormat::IeeeDouble => 6,
+            SegyFormat::Int8       => 8,
+        }
+    }
+    fn from_code(code: u16) -> Result<SegyFormat, SacError> {
+        match code {
+            1 => Ok(SegyFormat::IbmFloat),
+            2 => Ok(SegyFormat::Int32),
+            3 => Ok(SegyFormat::Int16),
+            5 => Ok(SegyFormat::IeeeFloat),
+            6 => Ok(SegyFormat::IeeeDouble),
+            8 => Ok(SegyFormat::Int8),
+            _ => Err(bad(format!("Unsupported SEG-Y sample format: {}", code))),
+        }
+    }
+    fn size(self) -> usize {
+        match self {
+            SegyFormat::Int8 => 1,
+            SegyFormat::Int16 => 2,
+            SegyFormat::IeeeDouble => 8,
+            _ => 4,
+        }
+    }
+}
+
+/// Options for writing SEG-Y files
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// Sample format
+    pub format: SegyFormat,
+    /// Write big endian (standard) or little endian (revision 2 only) data
+    pub big_endian: bool,
+    /// SEG-Y revision, 1 or 2
+    pub revision: u8,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions { format: SegyFormat::IeeeFloat, big_endian: true, revision: 1 }
+    }
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+/// Convert an IBM floating point value
+fn ibm_to_f32(bits: u32) -> f32 {
+    if bits & 0x7fff_ffff == 0 {
+        return 0.0;
+    }
+    let sign = if bits >> 31 == 1 { -1.0 } else { 1.0 };
+    let exp = ((bits >> 24) & 0x7f) as i32 - 64;
+    let frac = f64::from(bits & 0x00ff_ffff) / f64::from(1 << 24);
+    (sign * frac * 16f64.powi(exp)) as f32
+}
+
+/// Convert to an IBM floating point value
+fn f32_to_ibm(v: f32) -> u32 {
+    if v == 0.0 || !v.is_finite() {
+        return 0;
+    }
+    let sign = if v < 0.0 { 1u32 << 31 } else { 0 };
+    let mut frac = f64::from(v.abs());
+    let mut exp = 64i32;
+    while frac >= 1.0 {
+        frac /= 16.0;
+        exp += 1;
+    }
+    while frac < 1.0 / 16.0 {
+        frac *= 16.0;
+        exp -= 1;
+    }
+    let mut mant = (frac * f64::from(1 << 24)).round() as u32;
+    if mant >= 1 << 24 {
+        mant >>= 4;
+        exp += 1;
+    }
+    if !(0 ..= 127).contains(&exp) {
+        return sign | 0x7fff_ffff;
+    }
+    sign | (exp as u32) << 24 | mant
+}
+
+/// ASCII to EBCDIC (code page 037) for the textual header
+fn ebcdic(c: u8) -> u8 {
+    match c {
+        b'a' ..= b'i' => 0x81 + (c - b'a'),
+        b'j' ..= b'r' => 0x91 + (c - b'j'),
+        b's' ..= b'z' => 0xa2 + (c - b's'),
+        b'A' ..= b'I' => 0xc1 + (c - b'A'),
+        b'J' ..= b'R' => 0xd1 + (c - b'J'),
+        b'S' ..= b'Z' => 0xe2 + (c - b'S'),
+        b'0' ..= b'9' => 0xf0 + (c - b'0'),
+        b'.' => 0x4b,
+        b'-' => 0x60,
+        b'/' => 0x61,
+        b',' => 0x6b,
+        b':' => 0x7a,
+        b'=' => 0x7e,
+        _ => 0x40,
+    }
+}
+
+/// Byte order aware access to headers
+#[derive(Copy, Clone)]
+struct Endian(bool);
+
+impl Endian {
+    fn i16(self, b: &[u8], off: usize) -> i16 {
+        if self.0 { BigEndian::read_i16(&b[off..]) } else { LittleEndian::read_i16(&b[off..]) }
+    }
+    fn u16(self, b: &[u8], off: usize) -> u16 {
+        self.i16(b, off) as u16
+    }
+    fn i32(self, b: &[u8], off: usize) -> i32 {
+        if self.0 { BigEndian::read_i32(&b[off..]) } else { LittleEndian::read_i32(&b[off..]) }
+    }
+    fn put_i16(self, b: &mut [u8], off: usize, v: i16) {
+        if self.0 { BigEndian::write_i16(&mut b[off..], v) } else { LittleEndian::write_i16(&mut b[off..], v) }
+    }
+    fn put_i32(self, b: &mut [u8], off: usize, v: i32) {
+        if self.0 { BigEndian::write_i32(&mut b[off..], v) } else { LittleEndian::write_i32(&mut b[off..], v) }
+    }
+}
+
+/// Decode trace samples
+fn decode_samples(b: &[u8], n: usize, fmt: SegyFormat, e: Endian) -> Vec<f32> {
+    b.chunks_exact(fmt.size()).take(n).map(|v| match fmt {
+        SegyFormat::IbmFloat => ibm_to_f32(e.i32(v, 0) as u32),
+        SegyFormat::Int32 => e.i32(v, 0) as f32,
+        SegyFormat::Int16 => f32::from(e.i16(v, 0)),
+        SegyFormat::Int8 => f32::from(v[0] as i8),
+        SegyFormat::IeeeFloat => f32::from_bits(e.i32(v, 0) as u32),
+        SegyFormat::IeeeDouble => if e.0 {
+            BigEndian::read_f64(v) as f32
+        } else {
+            LittleEndian::read_f64(v) as f32
+        },
+    }).collect()
+}
+
+/// Encode trace samples
+fn encode_samples(y: &[f32], fmt: SegyFormat, e: Endian) -> Result<Vec<u8>, SacError> {
+    let mut out = vec![0u8; y.len() * fmt.size()];
+    for (v, b) in y.iter().zip(out.chunks_exact_mut(fmt.size())) {
+        let int = |lo: f32, hi: f32| -> Result<i32, SacError> {
+            if v.fract() != 0.0 || *v < lo || *v > hi {
+                return Err(bad(format!("Value not representable as {:?}: {}", fmt, v)));
+            }
+            Ok(*v as i32)
+        };
+        match fmt {
+            SegyFormat::IbmFloat => e.put_i32(b, 0, f32_to_ibm(*v) as i32),
+            SegyFormat::IeeeFloat => e.put_i32(b, 0, v.to_bits() as i32),
+            SegyFormat::Int32 => e.put_i32(b, 0, int(-2_147_483_648.0, 2_147_483_520.0)?),
+            SegyFormat::Int16 => e.put_i16(b, 0, int(-32768.0, 32767.0)? as i16),
+            SegyFormat::Int8 => b[0] = int(-128.0, 127.0)? as i8 as u8,
+            SegyFormat::IeeeDouble => if e.0 {
+                BigEndian::write_f64(b, f64::from(*v))
+            } else {
+                LittleEndian::write_f64(b, f64::from(*v))
+            },
+        }
+    }
+    Ok(out)
+}
+
+/// Apply a SEG-Y scalar, positive values multiply, negative values divide
+fn scaled(v: i32, scalar: i16) -> f64 {
+    match scalar {
+        0 => f64::from(v),
+        s if s > 0 => f64::from(v) * f64::from(s),
+        s => f64::from(v) / -f64::from(s),
+    }
+}
+
+/// Convert a coordinate to degrees given the coordinate units
+fn degrees(v: i32, scalar: i16, units: i16) -> Option<f32> {
+    let v = scaled(v, scalar);
+    let deg = match units {
+        2 => v / 3600.0,
+        3 => v,
+        4 => {
+            // DDDMMSS.ss
+            let sign = v.signum();
+            let v = v.abs();
+            let d = (v / 10000.0).trunc();
+            let m = ((v - d * 10000.0) / 100.0).trunc();
+            let s = v - d * 10000.0 - m * 100.0;
+            sign * (d + m / 60.0 + s / 3600.0)
+        },
+        _ => return None,
+    };
+    Some(deg as f32)
+}
+
+/// Convert a trace header and samples into a Sac file
+fn trace_to_sac(h: &[u8], y: Vec<f32>, dt: f64, e: Endian) -> Result<Sac, SacError> {
+    let b = f64::from(e.i16(h, 108)) / 1000.0;
+    let mut s = Sac::from_amp(y, b, dt);
+
+    let year = i32::from(e.i16(h, 156));
+    let day = e.i16(h, 158);
+    if year > 0 && day > 0 {
+        let year = match year {
+            0 ..= 49 => year + 2000,
+            50 ..= 99 => year + 1900,
+            _ => year,
+        };
+        let date = NaiveDate::from_yo_opt(year, day as u32)
+            .ok_or_else(|| bad("Invalid trace start time"))?;
+        let secs = i64::from(e.i16(h, 160)) * 3600 + i64::from(e.i16(h, 162)) * 60 +
+            i64::from(e.i16(h, 164));
+        s.set_time(date.and_hms_opt(0, 0, 0).unwrap() + Duration::seconds(secs));
+    }
+
+    let scalco = e.i16(h, 70);
+    let units = e.i16(h, 88);
+    if let (Some(x), Some(y)) = (degrees(e.i32(h, 80), scalco, units),
+                                 degrees(e.i32(h, 84), scalco, units)) {
+        s.stlo = x;
+        s.stla = y;
+        s.stel = scaled(e.i32(h, 40), e.i16(h, 68)) as f32;
+    }
+    if let (Some(x), Some(y)) = (degrees(e.i32(h, 72), scalco, units),
+                                 degrees(e.i32(h, 76), scalco, units)) {
+        s.evlo = x;
+        s.evla = y;
+    }
+    s.compute_dist_az();
+    s.set_id(SacInt::WaveformID, e.i32(h, 0));
+    s.set_id(SacInt::OriginID, e.i32(h, 8));
+    Ok(s)
+}
+
+/// Build a trace header for a Sac file
+fn sac_to_trace_header(s: &Sac, i: usize, e: Endian) -> Result<Vec<u8>, SacError> {
+    if !s.is_time() {
+        return Err(bad("Only time series can be written as SEG-Y"));
+    }
+    if !s.evenly_spaced() {
+        return Err(SacError::NotEvenlySpaced);
+    }
+    if s.y.len() > usize::from(u16::MAX) {
+        return Err(bad("Too many samples for a SEG-Y trace"));
+    }
+    let dt = (f64::from(s.delta) * 1e6).round();
+    if dt < 1.0 || dt > f64::from(u16::MAX) {
+        return Err(bad(format!("Sample interval not representable: {}", s.delta)));
+    }
+    let mut h = vec![0u8; TRACE_HEADER];
+    let wfid = if iis(s.nwfid) { s.nwfid } else { i as i32 + 1 };
+    let orid = if iis(s.norid) { s.norid } else { 1 };
+    e.put_i32(&mut h, 0, wfid);
+    e.put_i32(&mut h, 4, i as i32 + 1);
+    e.put_i32(&mut h, 8, orid);
+    e.put_i32(&mut h, 12, i as i32 + 1);
+    e.put_i16(&mut h, 28, 1);
+    e.put_i16(&mut h, 114, s.y.len() as i16);
+    e.put_i16(&mut h, 116, dt as u16 as i16);
+
+    // Coordinates in seconds of arc, scaled by 1/100
+    e.put_i16(&mut h, 68, -100);
+    e.put_i16(&mut h, 70, -100);
+    e.put_i16(&mut h, 88, 2);
+    let arcsec = |v: f32| (f64::from(v) * 3600.0 * 100.0).round() as i32;
+    if fis(s.stla) && fis(s.stlo) {
+        e.put_i32(&mut h, 80, arcsec(s.stlo));
+        e.put_i32(&mut h, 84, arcsec(s.stla));
+    }
+    if fis(s.stel) {
+        e.put_i32(&mut h, 40, (f64::from(s.stel) * 100.0).round() as i32);
+    }
+    if fis(s.evla) && fis(s.evlo) {
+        e.put_i32(&mut h, 72, arcsec(s.evlo));
+        e.put_i32(&mut h, 76, arcsec(s.evla));
+    }
+
+    // Start time at a whole second with the remainder as the delay time
+    if let Ok(t) = s.datetime(Marker::B) {
+        let t = t + Duration::microseconds(500);
+        let sec = t.with_nanosecond(0).unwrap();
+        let ms = (t - sec).num_milliseconds();
+        e.put_i16(&mut h, 156, sec.year() as i16);
+        e.put_i16(&mut h, 158, sec.ordinal() as i16);
+        e.put_i16(&mut h, 160, sec.hour() as i16);
+        e.put_i16(&mut h, 162, sec.minute() as i16);
+        e.put_i16(&mut h, 164, sec.second() as i16);
+        e.put_i16(&mut h, 166, 4);
+        e.put_i16(&mut h, 108, ms as i16);
+    } else if fis(s.b) {
+        let ms = (f64::from(s.b) * 1000.0).round();
+        if ms < f64::from(i16::MIN) || ms > f64::from(i16::MAX) {
+            return Err(bad(format!("Begin time not representable as a delay: {}", s.b)));
+        }
+        e.put_i16(&mut h, 108, ms as i16);
+    }
+    Ok(h)
+}
+
+/// Decode a SEG-Y file from a buffer
+pub fn decode(buf: &[u8]) -> Result<Vec<Sac>, SacError> {
+    if buf.len() < TEXT_HEADER + BINARY_HEADER {
+        return Err(bad("Truncated SEG-Y file"));
+    }
+    let bh = &buf[TEXT_HEADER .. TEXT_HEADER + BINARY_HEADER];
+    // Revision 2 byte order constant, otherwise a sensible format code
+    let e = match BigEndian::read_u32(&bh[96..]) {
+        0x0102_0304 => Endian(true),
+        0x0403_0201 => Endian(false),
+        _ => Endian((1 ..= 16).contains(&BigEndian::read_u16(&bh[24..]))),
+    };
+    let fmt = SegyFormat::from_code(e.u16(bh, 24))?;
+    let ns_file = usize::from(e.u16(bh, 20));
+    let dt_file = e.u16(bh, 16);
+    let next = e.i16(bh, 304);
+    if next < 0 {
+        return Err(bad("Variable number of extended textual headers not supported"));
+    }
+    let mut pos = TEXT_HEADER + BINARY_HEADER + TEXT_HEADER * next as usize;
+    let mut sacs = vec![];
+    while pos + TRACE_HEADER <= buf.len() {
+        let h = &buf[pos .. pos + TRACE_HEADER];
+        let ns = match usize::from(e.u16(h, 114)) { 0 => ns_file, n => n };
+        let dt = match e.u16(h, 116) { 0 => dt_file, d => d };
+        let start = pos + TRACE_HEADER;
+        let end = start + ns * fmt.size();
+        if end > buf.len() {
+            return Err(bad("Truncated SEG-Y trace"));
+        }
+        let y = decode_samples(&buf[start .. end], ns, fmt, e);
+        sacs.push(trace_to_sac(h, y, f64::from(dt) * 1e-6, e)?);
+        pos = end;
+    }
+    Ok(sacs)
+}
+
+/// Read a SEG-Y file
+pub fn read<R: Read>(reader: &mut R) -> Result<Vec<Sac>, SacError> {
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf)?;
+    decode(&buf)
+}
+
+/// Read a SEG-Y file from a path
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Sac>, SacError> {
+    let mut file = BufReader::new(File::open(&path)?);
+    let mut sacs = read(&mut file)?;
+    sacs.iter_mut().for_each(|s| s.set_filename(&path.as_ref().to_string_lossy()));
+    Ok(sacs)
+}
+
+/// Encode Sac files as a SEG-Y file
+///
+/// The binary header takes the number of samples and sample interval
+/// from the first trace
+pub fn write(sacs: &[Sac], opts: &WriteOptions) -> Result<Vec<u8>, SacError> {
+    if opts.revision != 1 && opts.revision != 2 {
+        return Err(bad(format!("Unsupported SEG-Y revision: {}", opts.revision)));
+    }
+    if !opts.big_endian && opts.revision < 2 {
+        return Err(bad("Little endian SEG-Y requires revision 2"));
+    }
+    let e = Endian(opts.big_endian);
+    let mut out = Vec::new();
+
+    let text = format!("{:80}{:80}", "C 1 SEG-Y written by sacio",
+                       format!("C 2 {} traces", sacs.len()));
+    let mut th : Vec<u8> = text.bytes().map(ebcdic).collect();
+    for i in 3 ..= 40 {
+        th.extend(format!("{:80}", format!("C{:2}", i)).bytes().map(ebcdic));
+    }
+    out.extend_from_slice(&th);
+
+    let mut bh = vec![0u8; BINARY_HEADER];
+    if let Some(s) = sacs.first() {
+        e.put_i16(&mut bh, 12, 1);
+        e.put_i16(&mut bh, 16, (f64::from(s.delta) * 1e6).round() as u16 as i16);
+        e.put_i16(&mut bh, 20, s.y.len() as u16 as i16);
+    }
+    e.put_i16(&mut bh, 24, opts.format.code() as i16);
+    e.put_i16(&mut bh, 28, 1);
+    e.put_i32(&mut bh, 96, 0x0102_0304);
+    // Major and minor revision are single bytes in either byte order
+    bh[300] = opts.revision;
+    bh[301] = 0;
+    let fixed = sacs.windows(2).all(|w| w[0].y.len() == w[1].y.len());
+    e.put_i16(&mut bh, 302, fixed as i16);
+    out.extend_from_slice(&bh);
+
+    for (i, s) in sacs.iter().enumerate() {
+        out.extend(sac_to_trace_header(s, i, e)?);
+        out.extend(encode_samples(&s.y, opts.format, e)?);
+    }
+    Ok(out)
+}
+
+/// Decode a Seismic Unix file from a buffer
+///
+/// The byte order is detected from the number of samples
+pub fn decode_su(buf: &[u8]) -> Result<Vec<Sac>, SacError> {
+    if buf.len() < TRACE_HEADER {
+        return Err(bad("Truncated SU file"));
+    }
+    let fits = |e: Endian| {
+        let ns = usize::from(e.u16(buf, 114));
+        ns > 0 && buf.len().is_multiple_of(TRACE_HEADER + 4 * ns)
+    };
+    let e = if fits(Endian(cfg!(target_endian = "big"))) {
+        Endian(cfg!(target_endian = "big"))
+    } else {
+        Endian(cfg!(target_endian = "little"))
+    };
+    let mut pos = 0;
+    let mut sacs = vec![];
+    while pos + TRACE_HEADER <= buf.len() {
+        let h = &buf[pos .. pos + TRACE_HEADER];
+        let ns = usize::from(e.u16(h, 114));
+        let start = pos + TRACE_HEADER;
+        let end = start + ns * 4;
+        if end > buf.len() {
+            return Err(bad("Truncated SU trace"));
+        }
+        let y = decode_samples(&buf[start .. end], ns, SegyFormat::IeeeFloat, e);
+        sacs.push(trace_to_sac(h, y, f64::from(e.u16(h, 116)) * 1e-6, e)?);
+        pos = end;
+    }
+    Ok(sacs)
+}
+
+/// Read a Seismic Unix file
+pub fn read_su<R: Read>(reader: &mut R) -> Result<Vec<Sac>, SacError> {
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf)?;
+    decode_su(&buf)
+}
+
+/// Read a Seismic Unix file from a path
+pub fn from_su_file<P: AsRef<Path>>(path: P) -> Result<Vec<Sac>, SacError> {
+    let mut file = BufReader::new(File::open(&path)?);
+    let mut sacs = read_su(&mut file)?;
+    sacs.iter_mut().for_each(|s| s.set_filename(&path.as_ref().to_string_lossy()));
+    Ok(sacs)
+}
+
+/// Encode Sac files as a Seismic Unix file in native byte order
+///
+/// ```
+/// use sacio::Sac;
+/// use sacio::segy;
+/// # use sacio::SacError;
+///
+/// let s = Sac::from_file("tests/file.sac")?;
+/// let buf = segy::write_su(&[s.clone(), s.clone()])?;
+///
+/// let sacs = segy::decode_su(&buf)?;
+/// assert_eq!(sacs.len(), 2);
+/// assert_eq!(sacs[1].y, s.y);
+/// assert_eq!(sacs[1].delta(), s.delta());
+/// # Ok::<(), SacError>(())
+/// ```
+pub fn write_su(sacs: &[Sac]) -> Result<Vec<u8>, SacError> {
+    let e = Endian(cfg!(target_endian = "big"));
+    let mut out = Vec::new();
+    for (i, s) in sacs.iter().enumerate() {
+        out.extend(sac_to_trace_header(s, i, e)?);
+        let mut y = vec![0u8; s.y.len() * 4];
+        NativeEndian::write_f32_into(&s.y, &mut y);
+        out.extend(y);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ibm() {
+        // Examples from the SEG-Y revision 1 standard
+        assert_eq!(ibm_to_f32(0xc276_a000), -118.625);
+        assert_eq!(f32_to_ibm(-118.625), 0xc276_a000);
+        assert_eq!(ibm_to_f32(0x4110_0000), 1.0);
+        assert_eq!(f32_to_ibm(1.0), 0x4110_0000);
+        assert_eq!(f32_to_ibm(0.0), 0);
+        for v in &[0.1f32, -3.5e-7, 12345.678, 1.0e20] {
+            assert!((ibm_to_f32(f32_to_ibm(*v)) - v).abs() <= v.abs() * 1e-6);
+        }
+    }
+
+    #[test]
+    fn dms() {
+        assert_eq!(degrees(1203000, 0, 4), Some(120.5));
+        assert_eq!(degrees(-4800000, -100, 2), Some(-48000.0 / 3600.0));
+        assert_eq!(degrees(100, 0, 1), None);
+    }
+
+    fn check(s: &Sac, s2: &Sac) {
+        assert_eq!(s2.npts(), s.npts());
+        assert_eq!(s2.delta(), s.delta());
+        let dt = s2.datetime(Marker::B).unwrap() - s.datetime(Marker::B).unwrap();
+        assert!(dt.num_microseconds().unwrap().abs() < 1000);
+        assert!((s2.station_lat() - s.station_lat()).abs() < 1e-5);
+        assert!((s2.station_lon() - s.station_lon()).abs() < 1e-5);
+        assert!((s2.event_lat() - s.event_lat()).abs() < 1e-5);
+        assert!((s2.event_lon() - s.event_lon()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn segy_formats() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_id(SacInt::WaveformID, 17);
+        s.set_id(SacInt::OriginID, 3);
+        for fmt in &[SegyFormat::IbmFloat, SegyFormat::IeeeFloat, SegyFormat::IeeeDouble] {
+            for (big, rev) in &[(true, 1), (false, 2), (true, 2)] {
+                let opts = WriteOptions { format: *fmt, big_endian: *big, revision: *rev };
+                let s2 = decode(&write(&[s.clone()], &opts).unwrap()).unwrap();
+                check(&s, &s2[0]);
+                assert_eq!(s2[0].id(SacInt::WaveformID), 17);
+                assert_eq!(s2[0].id(SacInt::OriginID), 3);
+                for (a, b) in s.y.iter().zip(s2[0].y.iter()) {
+                    assert!((a - b).abs() <= a.abs() * 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn segy_integers() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.y.iter_mut().for_each(|v| *v = (*v * 50.0).round());
+        for fmt in &[SegyFormat::Int32, SegyFormat::Int16, SegyFormat::Int8] {
+            let opts = WriteOptions { format: *fmt, .. Default::default() };
+            let s2 = decode(&write(&[s.clone()], &opts).unwrap()).unwrap();
+            assert_eq!(s2[0].y, s.y);
+        }
+        s.y[0] = 0.5;
+        let opts = WriteOptions { format: SegyFormat::Int32, .. Default::default() };
+        assert!(write(&[s], &opts).is_err());
+    }
+
+    #[test]
+    fn revision_and_delay() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        for big in &[true, false] {
+            let opts = WriteOptions { big_endian: *big, revision: 2, .. Default::default() };
+            let buf = write(&[s.clone()], &opts).unwrap();
+            assert_eq!(&buf[TEXT_HEADER + 300 .. TEXT_HEADER + 302], &[2, 0]);
+        }
+        // Without a reference time b is the delay time in milliseconds
+        s.nzyear = crate::SAC_INT_UNDEF;
+        s.b = 32.0;
+        let s2 = decode(&write(&[s.clone()], &Default::default()).unwrap()).unwrap();
+        assert_eq!(s2[0].b(), 32.0);
+        s.b = 40.0;
+        assert!(write(&[s], &Default::default()).is_err());
+    }
+
+    #[test]
+    fn su_byte_order() {
+        let s = Sac::from_file("tests/file.sac").unwrap();
+        let s2 = decode_su(&write_su(std::slice::from_ref(&s)).unwrap()).unwrap();
+        check(&s, &s2[0]);
+        assert_eq!(s2[0].y, s.y);
+
+        let e = Endian(cfg!(target_endian = "little"));
+        let mut buf = sac_to_trace_header(&s, 0, e).unwrap();
+        buf.extend(encode_samples(&s.y, SegyFormat::IeeeFloat, e).unwrap());
+        let s2 = decode_su(&buf).unwrap();
+        check(&s, &s2[0]);
+        assert_eq!(s2[0].y, s.y);
+    }
+}
@@ -0,0 +1,297 @@
+/*! Serde serialization of SAC headers and data
+
+Requires the `serde` feature
+
+A [`Sac`] serializes as a map keyed by the SAC header names.  Undefined
+values (-12345) are left out, and are undefined again when deserialized,
+so formats without a null like TOML work as well as JSON.  Header values
+are written as:
+
+| Header                            | Value                               |
+|-----------------------------------|-------------------------------------|
+| Reals, e.g. `delta`, `stla`       | number                              |
+| Integers, e.g. `npts`, `nzyear`   | number                              |
+| Enumerated, e.g. `iftype`, `idep` | SAC name, e.g. `"ITIME"`, `"IVEL"`  |
+| Logical, e.g. `leven`             | `true` or `false`                   |
+| Strings, e.g. `kstnm`             | string, trailing whitespace removed |
+| Data                              | `y` and, if present, `x` arrays     |
+
+Enumerated values without a SAC name are written as numbers.  When
+deserializing, either the name or the number is accepted.  The unused
+header values are not written.
+
+Serializing a [`Sac`] includes the data; [`Sac::header`] serializes only
+the header.  Both are deserialized into a [`Sac`], with the data left
+empty for a header.
+
+```
+use sacio::Sac;
+# use sacio::SacError;
+
+let s = Sac::from_file("tests/file.sac")?;
+
+let json = serde_json::to_string(&s.header()).unwrap();
+assert!(json.contains(r#""kstnm":"CDV""#));
+assert!(json.contains(r#""iftype":"ITIME""#));
+assert!(!json.contains("evel"));
+
+let json = serde_json::to_string(&s).unwrap();
+let s2 : Sac = serde_json::from_str(&json).unwrap();
+assert_eq!(s2.y, s.y);
+assert_eq!(s2.delta(), s.delta());
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de;
+use serde::de::MapAccess;
+use serde::de::Visitor;
+use serde::ser::SerializeMap;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::Sac;
+use crate::header::enum_name;
+use crate::header::enum_value;
+use crate::header::int_mut;
+use crate::header::kind;
+use crate::header::Kind;
+use crate::header::real_mut;
+use crate::header::string_mut;
+use crate::SAC_FLOAT_UNDEF;
+use crate::SAC_INT_UNDEF;
+
+fn is_undef_str(v: &str) -> bool {
+    v.trim_end().is_empty() || v.trim_end() == "-12345"
+}
+
+macro_rules! ser_reals {
+    ($s:ident, $m:ident, $t:ty, $($x:ident),*) => ( $(
+        if $s.$x != SAC_FLOAT_UNDEF && kind(stringify!($x)) != Kind::Unused {
+            $m.serialize_entry(stringify!($x), &$s.$x)?;
+        }
+    )* );
+}
+macro_rules! ser_ints {
+    ($s:ident, $m:ident, $t:ty, $($x:ident),*) => ( $(
+        if $s.$x != SAC_INT_UNDEF {
+            match kind(stringify!($x)) {
+                Kind::Int => $m.serialize_entry(stringify!($x), &$s.$x)?,
+                Kind::Logical => $m.serialize_entry(stringify!($x), &($s.$x != 0))?,
+                Kind::Enum => match enum_name($s.$x) {
+                    Some(name) => $m.serialize_entry(stringify!($x), name)?,
+                    None => $m.serialize_entry(stringify!($x), &$s.$x)?,
+                },
+                Kind::Unused => {},
+            }
+        }
+    )* );
+}
+macro_rules! ser_strings {
+    ($s:ident, $m:ident, $($x:ident),*) => ( $(
+        if !is_undef_str(&$s.$x) {
+            $m.serialize_entry(stringify!($x), $s.$x.trim_end())?;
+        }
+    )* );
+}
+fn serialize_sac<S: Serializer>(s: &Sac, data: bool, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut m = serializer.serialize_map(None)?;
+    sac_reals!(s, m, ser_reals);
+    sac_ints!(s, m, ser_ints);
+    sac_strings!(s, m, ser_strings);
+    if data {
+        m.serialize_entry("y", &s.y)?;
+        if !s.x.is_empty() {
+            m.serialize_entry("x", &s.x)?;
+        }
+    }
+    m.end()
+}
+
+/// Header of a [`Sac`] for serialization without the data
+///
+/// Created by [`Sac::header`]
+pub struct Header<'a>(&'a Sac);
+
+impl Serialize for Header<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_sac(self.0, false, serializer)
+    }
+}
+
+impl Serialize for Sac {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_sac(self, true, serializer)
+    }
+}
+
+impl Sac {
+    /// Header only serialization
+    ///
+    /// ```
+    /// use sacio::Sac;
+    /// # use sacio::SacError;
+    ///
+    /// let s = Sac::from_file("tests/file.sac")?;
+    /// let json = serde_json::to_string(&s.header()).unwrap();
+    /// assert!(!json.contains(r#""y":"#));
+    ///
+    /// let h : Sac = serde_json::from_str(&json).unwrap();
+    /// assert_eq!(h.npts(), 1000);
+    /// assert!(h.y.is_empty());
+    /// # Ok::<(), SacError>(())
+    /// ```
+    pub fn header(&self) -> Header<'_> {
+        Header(self)
+    }
+}
+
+/// Enumerated value as a SAC name or number
+struct EnumValue(i32);
+
+impl<'de> Deserialize<'de> for EnumValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct V;
+        impl Visitor<'_> for V {
+            type Value = EnumValue;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a SAC enumerated name or integer")
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<EnumValue, E> {
+                enum_value(v).map(EnumValue)
+                    .ok_or_else(|| E::custom(format!("unknown SAC enumerated value: {}", v)))
+            }
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<EnumValue, E> {
+                i32::try_from(v).map(EnumValue).map_err(|_| E::custom("enumerated value out of range"))
+            }
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<EnumValue, E> {
+                i32::try_from(v).map(EnumValue).map_err(|_| E::custom("enumerated value out of range"))
+            }
+        }
+        deserializer.deserialize_any(V)
+    }
+}
+
+struct SacVisitor;
+
+impl<'de> Visitor<'de> for SacVisitor {
+    type Value = Sac;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of SAC header values")
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Sac, A::Error> {
+        let mut s = Sac::new();
+        let mut npts = None;
+        while let Some(key) = map.next_key::<String>()? {
+            let k = key.as_str();
+            if k == "y" {
+                s.y = map.next_value()?;
+            } else if k == "x" {
+                s.x = map.next_value()?;
+            } else if let Some(v) = real_mut(&mut s, k) {
+                *v = map.next_value::<Option<f32>>()?.unwrap_or(SAC_FLOAT_UNDEF);
+            } else if let Some(v) = string_mut(&mut s, k) {
+                *v = map.next_value::<Option<String>>()?.unwrap_or_else(|| "-12345  ".to_string());
+            } else if kind(k) != Kind::Unused && int_mut(&mut s, k).is_some() {
+                let value = match kind(k) {
+                    Kind::Enum => map.next_value::<Option<EnumValue>>()?.map(|v| v.0),
+                    Kind::Logical => map.next_value::<Option<bool>>()?.map(i32::from),
+                    _ => map.next_value::<Option<i32>>()?,
+                };
+                let value = value.unwrap_or(SAC_INT_UNDEF);
+                if k == "npts" {
+                    npts = Some(value);
+                }
+                if let Some(v) = int_mut(&mut s, k) {
+                    *v = value;
+                }
+            } else {
+                return Err(de::Error::custom(format!("unknown SAC header value: {}", k)));
+            }
+        }
+        if !s.y.is_empty() || !s.x.is_empty() {
+            let n = s.y.len() as i32;
+            if let Some(npts) = npts.filter(|&npts| npts != n) {
+                return Err(de::Error::custom(format!("npts {} does not match data length {}",
+                                                     npts, n)));
+            }
+            if !s.x.is_empty() && s.x.len() != s.y.len() {
+                return Err(de::Error::custom("x and y data lengths differ"));
+            }
+            s.npts = n;
+        } else if npts.is_none() {
+            s.npts = 0;
+        }
+        Ok(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sac {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(SacVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(s: &mut Sac) -> Sac {
+        let mut buf = vec![];
+        s.write(&mut buf).unwrap();
+        Sac::read(&mut std::io::Cursor::new(buf)).unwrap()
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let s = Sac::from_file("tests/file.sac").unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        let mut s2: Sac = serde_json::from_str(&json).unwrap();
+        assert!(binary(&mut s2) == s);
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let s = Sac::from_file("tests/file.sac").unwrap();
+        let text = toml::to_string(&s.header()).unwrap();
+        assert!(text.contains("kstnm = \"CDV\""));
+        let mut s2: Sac = toml::from_str(&text).unwrap();
+        assert_eq!(s2.npts(), 1000);
+        s2.y = s.y.clone();
+        assert!(binary(&mut s2) == s);
+    }
+
+    #[test]
+    fn undefined() {
+        let s = Sac::new();
+        let v = serde_json::to_value(s.header()).unwrap();
+        let m = v.as_object().unwrap();
+        assert!(!m.contains_key("stla"));
+        assert!(!m.contains_key("kstnm"));
+        assert!(!m.contains_key("idep"));
+        assert!(!m.contains_key("unused27"));
+        assert_eq!(m["iftype"], "ITIME");
+        assert_eq!(m["lovrok"], true);
+        assert_eq!(m["lpspol"], false);
+
+        let s2: Sac = serde_json::from_str(r#"{"stla": null, "kstnm": null, "iztype": "IO", "ievtyp": 40, "leven": true}"#).unwrap();
+        assert_eq!(s2.stla, SAC_FLOAT_UNDEF);
+        assert_eq!(s2.kstnm, "-12345  ");
+        assert_eq!(s2.iztype, 11);
+        assert_eq!(s2.ievtyp, 40);
+        assert_eq!(s2.leven, 1);
+        assert_eq!(s2.npts, 0);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(serde_json::from_str::<Sac>(r#"{"stlx": 1.0}"#).is_err());
+        assert!(serde_json::from_str::<Sac>(r#"{"iftype": "IBAD"}"#).is_err());
+        assert!(serde_json::from_str::<Sac>(r#"{"npts": 3, "y": [1.0, 2.0]}"#).is_err());
+    }
+}
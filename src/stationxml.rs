@@ -0,0 +1,444 @@
+/*! StationXML station metadata
+
+Parse FDSN StationXML files and fill station headers from them
+
+ Header   | StationXML Channel
+ ---------|-------------------------------------------
+ stla     | Latitude
+ stlo     | Longitude
+ stel     | Elevation (m)
+ stdp     | Depth (m)
+ cmpaz    | Azimuth
+ cmpinc   | Dip + 90
+ kinst    | Sensor Model, Type or Description
+
+```
+use sacio::Sac;
+use sacio::SacString;
+use sacio::stationxml::{self, Inventory};
+# use sacio::SacError;
+
+let xml = r#"<FDSNStationXML xmlns="http://www.fdsn.org/xml/station/1" schemaVersion="1.1">
+  <Network code="XX">
+    <Station code="CDV" startDate="1980-01-01T00:00:00">
+      <Latitude>48.0</Latitude><Longitude>-120.0</Longitude><Elevation>1000</Elevation>
+      <Channel code="BHZ" locationCode="" startDate="1980-01-01T00:00:00">
+        <Latitude>48.0</Latitude><Longitude>-120.0</Longitude>
+        <Elevation>1000</Elevation><Depth>5</Depth>
+        <Azimuth>0</Azimuth><Dip>-90</Dip>
+        <Sensor><Model>STS-2</Model></Sensor>
+      </Channel>
+    </Station>
+  </Network>
+</FDSNStationXML>"#;
+let inv = Inventory::parse(xml)?;
+
+let mut s = Sac::from_file("tests/file.sac")?;
+s.set_string(SacString::Network, "XX");
+s.set_string(SacString::Location, "");
+s.set_string(SacString::Channel, "BHZ");
+let cha = stationxml::apply_station_metadata(&mut s, &inv)?;
+assert_eq!(cha.code, "BHZ");
+assert_eq!(s.cmpinc(), 0.0);
+assert_eq!(s.string(SacString::Instrument), "STS-2");
+# Ok::<(), SacError>(())
+```
+*/
+
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::NaiveDateTime;
+use num_complex::Complex64;
+use roxmltree::Node;
+
+use crate::Sac;
+use crate::SacError;
+use crate::SacString;
+use crate::response::{Filter, PolesZeros, Response, Sensitivity, Stage, Symmetry, Transfer};
+
+/// Collection of networks
+#[derive(Debug, Clone, Default)]
+pub struct Inventory {
+    /// Networks
+    pub networks: Vec<Network>,
+}
+
+/// Network and its stations
+#[derive(Debug, Clone)]
+pub struct Network {
+    /// Network code
+    pub code: String,
+    /// Stations
+    pub stations: Vec<Station>,
+}
+
+/// Station epoch and its channels
+#[derive(Debug, Clone)]
+pub struct Station {
+    /// Station code
+    pub code: String,
+    /// Start of epoch
+    pub start: Option<NaiveDateTime>,
+    /// End of epoch, None if open
+    pub end: Option<NaiveDateTime>,
+    /// Latitude in degrees
+    pub latitude: f64,
+    /// Longitude in degrees
+    pub longitude: f64,
+    /// Elevation in meters
+    pub elevation: f64,
+    /// Channels
+    pub channels: Vec<Channel>,
+}
+
+/// Channel epoch
+#[derive(Debug, Clone)]
+pub struct Channel {
+    /// Channel code
+    pub code: String,
+    /// Location code, empty if none
+    pub location: String,
+    /// Start of epoch
+    pub start: Option<NaiveDateTime>,
+    /// End of epoch, None if open
+    pub end: Option<NaiveDateTime>,
+    /// Latitude in degrees
+    pub latitude: f64,
+    /// Longitude in degrees
+    pub longitude: f64,
+    /// Elevation in meters
+    pub elevation: f64,
+    /// Depth of burial in meters
+    pub depth: f64,
+    /// Azimuth in degrees from north
+    pub azimuth: Option<f64>,
+    /// Dip in degrees down from horizontal
+    pub dip: Option<f64>,
+    /// Sample rate in Hz
+    pub sample_rate: Option<f64>,
+    /// Sensor name
+    pub sensor: Option<String>,
+    /// Instrument response
+    pub response: Option<Response>,
+}
+
+fn active(start: Option<NaiveDateTime>, end: Option<NaiveDateTime>, t: NaiveDateTime) -> bool {
+    start.map(|s| s <= t).unwrap_or(true) && end.map(|e| t < e).unwrap_or(true)
+}
+
+impl Inventory {
+    /// Parse a StationXML document
+    pub fn parse(xml: &str) -> Result<Inventory, SacError> {
+        let doc = roxmltree::Document::parse(xml)
+            .map_err(|e| SacError::Format(format!("StationXML: {}", e)))?;
+        let networks = children(doc.root_element(), "Network")
+            .map(parse_network)
+            .collect::<Result<_, _>>()?;
+        Ok(Inventory { networks })
+    }
+    /// Read a StationXML file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Inventory, SacError> {
+        Inventory::parse(&std::fs::read_to_string(path)?)
+    }
+    /// Find the channel epoch for a `net.sta.loc.chan` code at time `t`
+    pub fn channel(&self, nslc: &str, t: NaiveDateTime) -> Option<&Channel> {
+        let parts: Vec<_> = nslc.split('.').collect();
+        if parts.len() != 4 {
+            return None;
+        }
+        let loc = if parts[2] == "--" { "" } else { parts[2] };
+        self.networks.iter()
+            .filter(|n| n.code == parts[0])
+            .flat_map(|n| n.stations.iter())
+            .filter(|s| s.code == parts[1] && active(s.start, s.end, t))
+            .flat_map(|s| s.channels.iter())
+            .find(|c| c.location == loc && c.code == parts[3] && active(c.start, c.end, t))
+    }
+}
+
+/// Fill station headers from the matching channel epoch in `inv`
+///
+/// The channel is found using [`Sac::nslc`] and the reference time
+/// [`Sac::time`].  The channel is returned so its response can be used later
+pub fn apply_station_metadata<'a>(s: &mut Sac, inv: &'a Inventory) -> Result<&'a Channel, SacError> {
+    let nslc = s.nslc();
+    let cha = inv.channel(&nslc, s.time()?)
+        .ok_or(SacError::MissingMetadata(nslc))?;
+    s.set_station_location(cha.latitude as f32, cha.longitude as f32, cha.elevation as f32)?;
+    s.stdp = cha.depth as f32;
+    if let Some(az) = cha.azimuth {
+        s.set_cmpaz(az as f32)?;
+    }
+    if let Some(dip) = cha.dip {
+        s.set_cmpinc((dip + 90.0) as f32)?;
+    }
+    if let Some(sensor) = &cha.sensor {
+        let n = sensor.char_indices().nth(8).map(|(i, _)| i).unwrap_or(sensor.len());
+        s.set_string(SacString::Instrument, &sensor[..n]);
+    }
+    Ok(cha)
+}
+
+fn bad<S: Into<String>>(msg: S) -> SacError {
+    SacError::Format(msg.into())
+}
+
+fn children<'a, 'b: 'a>(node: Node<'a, 'b>, name: &'a str) -> impl Iterator<Item = Node<'a, 'b>> + 'a {
+    node.children().filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child<'a, 'b>(node: Node<'a, 'b>, name: &str) -> Option<Node<'a, 'b>> {
+    node.children().find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn text(node: Node, name: &str) -> Option<String> {
+    child(node, name).and_then(|n| n.text()).map(|t| t.trim().to_string())
+}
+
+fn float(node: Node, name: &str) -> Result<Option<f64>, SacError> {
+    match text(node, name) {
+        None => Ok(None),
+        Some(t) => t.parse().map(Some).map_err(|_| bad(format!("Invalid {}: {:?}", name, t))),
+    }
+}
+
+fn required(node: Node, name: &str) -> Result<f64, SacError> {
+    float(node, name)?.ok_or_else(|| bad(format!("Missing {} in {}", name, node.tag_name().name())))
+}
+
+fn units(node: Node, name: &str) -> String {
+    child(node, name).and_then(|n| text(n, "Name")).unwrap_or_default()
+}
+
+/// Parse a StationXML date, which may have a time zone designator
+pub(crate) fn parse_time(t: &str) -> Result<NaiveDateTime, SacError> {
+    let t = t.trim();
+    if let Ok(d) = DateTime::parse_from_rfc3339(t) {
+        return Ok(d.naive_utc());
+    }
+    let t = t.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(&format!("{}T00:00:00", t), "%Y-%m-%dT%H:%M:%S"))
+        .map_err(|_| bad(format!("Invalid date: {:?}", t)))
+}
+
+fn date(node: Node, attr: &str) -> Result<Option<NaiveDateTime>, SacError> {
+    node.attribute(attr).map(parse_time).transpose()
+}
+
+fn parse_network(node: Node) -> Result<Network, SacError> {
+    Ok(Network {
+        code: node.attribute("code").unwrap_or_default().to_string(),
+        stations: children(node, "Station").map(parse_station).collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_station(node: Node) -> Result<Station, SacError> {
+    Ok(Station {
+        code: node.attribute("code").unwrap_or_default().to_string(),
+        start: date(node, "startDate")?,
+        end: date(node, "endDate")?,
+        latitude: required(node, "Latitude")?,
+        longitude: required(node, "Longitude")?,
+        elevation: float(node, "Elevation")?.unwrap_or(0.0),
+        channels: children(node, "Channel").map(parse_channel).collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_channel(node: Node) -> Result<Channel, SacError> {
+    let sensor = child(node, "Sensor").and_then(|s| {
+        text(s, "Model").or_else(|| text(s, "Type")).or_else(|| text(s, "Description"))
+    });
+    let loc = node.attribute("locationCode").unwrap_or_default().trim();
+    Ok(Channel {
+        code: node.attribute("code").unwrap_or_default().to_string(),
+        location: if loc == "--" { String::new() } else { loc.to_string() },
+        start: date(node, "startDate")?,
+        end: date(node, "endDate")?,
+        latitude: required(node, "Latitude")?,
+        longitude: required(node, "Longitude")?,
+        elevation: float(node, "Elevation")?.unwrap_or(0.0),
+        depth: float(node, "Depth")?.unwrap_or(0.0),
+        azimuth: float(node, "Azimuth")?,
+        dip: float(node, "Dip")?,
+        sample_rate: float(node, "SampleRate")?,
+        sensor: sensor.filter(|s| !s.is_empty()),
+        response: child(node, "Response").map(parse_response).transpose()?,
+    })
+}
+
+fn parse_transfer(t: &str) -> Result<Transfer, SacError> {
+    let t = t.to_uppercase();
+    if t.contains("RADIANS") {
+        Ok(Transfer::LaplaceRadians)
+    } else if t.contains("HERTZ") {
+        Ok(Transfer::LaplaceHertz)
+    } else if t.contains("DIGITAL") {
+        Ok(Transfer::Digital)
+    } else {
+        Err(bad(format!("Unknown transfer function type: {}", t)))
+    }
+}
+
+fn complex(node: Node) -> Result<Complex64, SacError> {
+    Ok(Complex64::new(required(node, "Real")?, required(node, "Imaginary")?))
+}
+
+fn values(node: Node, name: &str) -> Result<Vec<f64>, SacError> {
+    children(node, name)
+        .map(|n| {
+            let t = n.text().unwrap_or_default().trim();
+            t.parse().map_err(|_| bad(format!("Invalid {}: {:?}", name, t)))
+        })
+        .collect()
+}
+
+fn parse_stage(node: Node) -> Result<Stage, SacError> {
+    let mut stage = Stage::default();
+    let filt = ["PolesZeros", "Coefficients", "FIR"].iter()
+        .find_map(|name| child(node, name));
+    if let Some(f) = filt {
+        stage.input_units = units(f, "InputUnits");
+        stage.output_units = units(f, "OutputUnits");
+        let filter = match f.tag_name().name() {
+            "PolesZeros" => Filter::PolesZeros(PolesZeros {
+                transfer: parse_transfer(&text(f, "PzTransferFunctionType").unwrap_or_default())?,
+                normalization_factor: float(f, "NormalizationFactor")?.unwrap_or(1.0),
+                normalization_frequency: float(f, "NormalizationFrequency")?.unwrap_or(0.0),
+                zeros: children(f, "Zero").map(complex).collect::<Result<_, _>>()?,
+                poles: children(f, "Pole").map(complex).collect::<Result<_, _>>()?,
+            }),
+            "Coefficients" => Filter::Coefficients {
+                transfer: parse_transfer(&text(f, "CfTransferFunctionType").unwrap_or_default())?,
+                numerator: values(f, "Numerator")?,
+                denominator: values(f, "Denominator")?,
+            },
+            _ => Filter::Fir {
+                symmetry: match text(f, "Symmetry").as_deref() {
+                    Some("ODD") => Symmetry::Odd,
+                    Some("EVEN") => Symmetry::Even,
+                    _ => Symmetry::None,
+                },
+                coefficients: values(f, "NumeratorCoefficient")?,
+            },
+        };
+        stage.filter = Some(filter);
+    }
+    if let Some(d) = child(node, "Decimation") {
+        stage.input_sample_rate = float(d, "InputSampleRate")?;
+        stage.decimation = float(d, "Factor")?.unwrap_or(1.0) as u32;
+        stage.correction = float(d, "Correction")?.unwrap_or(0.0);
+    }
+    if let Some(g) = child(node, "StageGain") {
+        stage.gain = required(g, "Value")?;
+        stage.gain_frequency = float(g, "Frequency")?.unwrap_or(0.0);
+    }
+    Ok(stage)
+}
+
+fn parse_response(node: Node) -> Result<Response, SacError> {
+    let mut resp = Response::default();
+    if let Some(s) = child(node, "InstrumentSensitivity") {
+        resp.sensitivity = Some(Sensitivity {
+            value: required(s, "Value")?,
+            frequency: float(s, "Frequency")?.unwrap_or(0.0),
+        });
+        resp.input_units = units(s, "InputUnits");
+        resp.output_units = units(s, "OutputUnits");
+    }
+    resp.stages = children(node, "Stage").map(parse_stage).collect::<Result<_, _>>()?;
+    if resp.input_units.is_empty() {
+        if let Some(s) = resp.stages.first() {
+            resp.input_units = s.input_units.clone();
+        }
+    }
+    if resp.output_units.is_empty() {
+        if let Some(s) = resp.stages.last() {
+            resp.output_units = s.output_units.clone();
+        }
+    }
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::FrequencyResponse;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<FDSNStationXML xmlns="http://www.fdsn.org/xml/station/1" schemaVersion="1.1">
+  <Source>test</Source>
+  <Network code="XX">
+    <Station code="CDV" startDate="1980-01-01T00:00:00Z">
+      <Latitude>48.0</Latitude><Longitude>-120.0</Longitude><Elevation>1000</Elevation>
+      <Channel code="BHN" locationCode="00" startDate="1980-01-01T00:00:00Z" endDate="1981-01-01T00:00:00Z">
+        <Latitude>10.0</Latitude><Longitude>10.0</Longitude><Elevation>0</Elevation><Depth>0</Depth>
+      </Channel>
+      <Channel code="BHN" locationCode="00" startDate="1981-01-01T00:00:00Z">
+        <Latitude>48.0</Latitude><Longitude>-120.0</Longitude><Elevation>1000</Elevation><Depth>5</Depth>
+        <Azimuth>2.5</Azimuth><Dip>0</Dip><SampleRate>100</SampleRate>
+        <Sensor><Type>Streckeisen STS-2 Broadband</Type></Sensor>
+        <Response>
+          <InstrumentSensitivity>
+            <Value>1000</Value><Frequency>1</Frequency>
+            <InputUnits><Name>M/S</Name></InputUnits><OutputUnits><Name>COUNTS</Name></OutputUnits>
+          </InstrumentSensitivity>
+          <Stage number="1">
+            <PolesZeros>
+              <InputUnits><Name>M/S</Name></InputUnits><OutputUnits><Name>V</Name></OutputUnits>
+              <PzTransferFunctionType>LAPLACE (RADIANS/SECOND)</PzTransferFunctionType>
+              <NormalizationFactor>1</NormalizationFactor><NormalizationFrequency>1</NormalizationFrequency>
+              <Zero number="0"><Real>0</Real><Imaginary>0</Imaginary></Zero>
+              <Pole number="0"><Real>-0.1</Real><Imaginary>0</Imaginary></Pole>
+            </PolesZeros>
+            <StageGain><Value>100</Value><Frequency>1</Frequency></StageGain>
+          </Stage>
+          <Stage number="2">
+            <Coefficients>
+              <InputUnits><Name>V</Name></InputUnits><OutputUnits><Name>COUNTS</Name></OutputUnits>
+              <CfTransferFunctionType>DIGITAL</CfTransferFunctionType>
+            </Coefficients>
+            <Decimation><InputSampleRate>100</InputSampleRate><Factor>1</Factor><Offset>0</Offset><Delay>0</Delay><Correction>0</Correction></Decimation>
+            <StageGain><Value>10</Value><Frequency>1</Frequency></StageGain>
+          </Stage>
+        </Response>
+      </Channel>
+    </Station>
+  </Network>
+</FDSNStationXML>"#;
+
+    #[test]
+    fn epochs() {
+        let inv = Inventory::parse(XML).unwrap();
+        let t0 = parse_time("1980-06-01T00:00:00").unwrap();
+        let t1 = parse_time("1981-03-29").unwrap();
+        assert_eq!(inv.channel("XX.CDV.00.BHN", t0).unwrap().latitude, 10.0);
+        assert_eq!(inv.channel("XX.CDV.00.BHN", t1).unwrap().latitude, 48.0);
+        assert!(inv.channel("XX.CDV..BHN", t1).is_none());
+        assert!(inv.channel("XX.CDV.00.BHN", parse_time("1970-01-01").unwrap()).is_none());
+    }
+
+    #[test]
+    fn apply() {
+        let inv = Inventory::parse(XML).unwrap();
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_string(SacString::Network, "XX");
+        s.set_string(SacString::Location, "00");
+        s.set_string(SacString::Channel, "BHN");
+        let cha = apply_station_metadata(&mut s, &inv).unwrap();
+        assert_eq!((s.stla, s.stlo, s.stel, s.stdp), (48.0, -120.0, 1000.0, 5.0));
+        assert_eq!((s.cmpaz(), s.cmpinc()), (2.5, 90.0));
+        assert_eq!(s.string(SacString::Instrument), "Streckei");
+
+        let resp = cha.response.as_ref().unwrap();
+        assert_eq!(resp.stages.len(), 2);
+        assert_eq!(resp.data_type(), crate::SacDataType::Velocity);
+        // High pass, well above the corner the gain is the product of stage gains
+        let h = resp.evaluate(10.0);
+        assert!((h.norm() - 1000.0).abs() < 0.01);
+
+        s.set_string(SacString::Channel, "BHE");
+        assert!(apply_station_metadata(&mut s, &inv).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+/*! WebAssembly bindings
+
+Requires the `wasm` feature
+
+Exposes reading SAC files from bytes, the header values and min/max
+envelopes for plotting to JavaScript through wasm-bindgen.  Nothing here
+uses the filesystem.  Build with
+
+```sh
+cargo rustc --release --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/sacio.wasm
+```
+
+```js
+import init, { Sac } from "./pkg/sacio.js";
+
+await init();
+const bytes = new Uint8Array(await file.arrayBuffer());
+const s = new Sac(bytes);
+console.log(s.nslc, s.npts, s.header());
+// Interleaved minimum and maximum of 800 bins
+const env = s.envelope(800);
+```
+
+Header values are named by their SAC names.  Undefined values are left
+out, enumerated values are their SAC names, e.g. `"ITIME"`, and logical
+values are booleans.
+*/
+
+use js_sys::Object;
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+
+use crate::header;
+use crate::header::Kind;
+use crate::Sac;
+use crate::SAC_FLOAT_UNDEF;
+use crate::SAC_INT_UNDEF;
+use crate::SAC_STRING_UNDEF;
+
+/// SAC file read from bytes
+#[wasm_bindgen(js_name = Sac)]
+pub struct WasmSac {
+    sac: Sac,
+}
+
+/// Header value as a JavaScript value, `None` if undefined
+fn value(s: &Sac, key: &str) -> Option<JsValue> {
+    if let Some(&v) = header::real(s, key) {
+        return (v != SAC_FLOAT_UNDEF).then(|| JsValue::from(v));
+    }
+    if let Some(v) = header::string(s, key) {
+        let v = v.trim_end();
+        return (!v.is_empty() && v != SAC_STRING_UNDEF.trim_end()).then(|| JsValue::from(v));
+    }
+    let v = *header::int(s, key)?;
+    if v == SAC_INT_UNDEF {
+        return None;
+    }
+    Some(match header::kind(key) {
+        Kind::Logical => JsValue::from(v != 0),
+        Kind::Enum => header::enum_name(v).map(JsValue::from).unwrap_or_else(|| JsValue::from(v)),
+        Kind::Int => JsValue::from(v),
+        Kind::Unused => return None,
+    })
+}
+
+#[wasm_bindgen(js_class = Sac)]
+impl WasmSac {
+    /// Read a SAC file from bytes
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<WasmSac, JsError> {
+        let sac = Sac::read(&mut std::io::Cursor::new(bytes))
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(WasmSac { sac })
+    }
+    /// Defined header values as an object keyed by SAC name
+    pub fn header(&self) -> Result<Object, JsValue> {
+        let obj = Object::new();
+        for key in header::names() {
+            if let Some(v) = value(&self.sac, key) {
+                Reflect::set(&obj, &JsValue::from(key), &v)?;
+            }
+        }
+        Ok(obj)
+    }
+    /// Header value by SAC name, `undefined` if not defined
+    pub fn get(&self, key: &str) -> Option<JsValue> {
+        value(&self.sac, &key.to_lowercase())
+    }
+    /// Interleaved minimum and maximum of the data in at most `n` bins
+    ///
+    /// See [`Sac::envelope`]
+    pub fn envelope(&self, n: usize) -> Vec<f32> {
+        self.sac.envelope(n).into_iter().flat_map(|(lo, hi)| [lo, hi]).collect()
+    }
+    /// Number of samples in each bin of [`WasmSac::envelope`]
+    #[wasm_bindgen(js_name = envelopeBinSize)]
+    pub fn envelope_bin_size(&self, n: usize) -> usize {
+        if n == 0 { 0 } else { self.sac.y.len().div_ceil(n) }
+    }
+    /// Data, copied
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> Vec<f32> {
+        self.sac.y.clone()
+    }
+    /// Number of points
+    #[wasm_bindgen(getter)]
+    pub fn npts(&self) -> usize {
+        self.sac.y.len()
+    }
+    /// Sampling interval
+    #[wasm_bindgen(getter)]
+    pub fn delta(&self) -> f32 {
+        self.sac.delta()
+    }
+    /// Begin time
+    #[wasm_bindgen(getter)]
+    pub fn b(&self) -> f32 {
+        self.sac.b()
+    }
+    /// End time
+    #[wasm_bindgen(getter)]
+    pub fn e(&self) -> f32 {
+        self.sac.e()
+    }
+    /// Network, station, location and channel, `net.sta.loc.cha`
+    #[wasm_bindgen(getter)]
+    pub fn nslc(&self) -> String {
+        self.sac.nslc()
+    }
+    /// Flinn-Engdahl region of the event, if the location is defined
+    #[wasm_bindgen(getter, js_name = eventRegion)]
+    pub fn event_region(&self) -> Option<String> {
+        let lat = self.sac.try_event_lat()?;
+        let lon = self.sac.try_event_lon()?;
+        flinn_engdahl::region(lat as f64, lon as f64).ok().map(String::from)
+    }
+    /// Distance in degrees, if the locations are defined
+    #[wasm_bindgen(getter)]
+    pub fn gcarc(&self) -> Option<f32> {
+        let mut s = self.sac.clone();
+        s.y.clear();
+        s.compute_dist_az();
+        s.try_dist_deg()
+    }
+}
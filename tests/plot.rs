@@ -0,0 +1,76 @@
+#![cfg(feature = "plot")]
+// SVG text is measured with fixed metrics, so the snapshots do not
+// depend on the fonts used with the `png` feature.
+//
+// Set SACIO_UPDATE_SNAPSHOTS=1 to regenerate the snapshots.
+
+use sacio::Sac;
+use sacio::SacString;
+use sacio::plot;
+use chrono::Duration;
+
+fn snapshot(name: &str, svg: &str) {
+    let path = format!("tests/snapshots/{}.svg", name);
+    if std::env::var("SACIO_UPDATE_SNAPSHOTS").is_ok() {
+        std::fs::write(&path, svg).unwrap();
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert!(svg == expected, "snapshot {} differs", path);
+}
+
+fn gather() -> Vec<Sac> {
+    let mut out = vec![];
+    for (i, lon) in [-121.0, -118.0, -115.0].iter().enumerate() {
+        let mut s = Sac::from_file("tests/file.sac").unwrap();
+        s.set_station_location(48.0, *lon, 0.0).unwrap();
+        s.set_string(SacString::Network, "XX");
+        s.set_string(SacString::Station, &format!("S{}", i));
+        s.set_string(SacString::Channel, "BHZ");
+        out.push(s);
+    }
+    out
+}
+
+#[test]
+fn plot_single() {
+    let mut s = Sac::from_file("tests/file.sac").unwrap();
+    s.t1 = 12.0;
+    s.set_string(SacString::T1, "PKIKP");
+    let svg = plot::plot_svg(&s, &plot::PlotOptions::default()).unwrap();
+    snapshot("plot_single", &svg);
+}
+
+#[test]
+fn plot_stacked() {
+    let sacs = gather();
+    let svg = plot::plot1_svg(&sacs, &plot::PlotOptions::default()).unwrap();
+    snapshot("plot_stacked", &svg);
+}
+
+#[test]
+fn plot_record_section() {
+    let sacs = gather();
+    let opts = plot::PlotOptions { markers: false, .. Default::default() };
+    let svg = plot::prs_svg(&sacs, plot::SectionDistance::Gcarc, &opts).unwrap();
+    snapshot("plot_record_section", &svg);
+}
+
+#[test]
+fn plot_record_section_aligned() {
+    // Moving the reference time of a trace keeps its absolute times
+    let sacs = gather();
+    let mut moved = sacs.clone();
+    let t = moved[1].time().unwrap() - Duration::seconds(64);
+    moved[1].set_reference_time(t).unwrap();
+    let opts = plot::PlotOptions { markers: false, .. Default::default() };
+    let svg = plot::prs_svg(&sacs, plot::SectionDistance::Gcarc, &opts).unwrap();
+    let svg2 = plot::prs_svg(&moved, plot::SectionDistance::Gcarc, &opts).unwrap();
+    assert!(svg == svg2);
+}
+
+#[test]
+fn plot_record_section_undefined() {
+    let s = Sac::from_amp(vec![0.0, 1.0, 0.0], 0.0, 1.0);
+    let opts = plot::PlotOptions::default();
+    assert!(plot::prs_svg(&[s], plot::SectionDistance::Dist, &opts).is_err());
+}
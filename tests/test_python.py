@@ -0,0 +1,79 @@
+# Tests for the Python bindings, run with
+#
+#   maturin develop --features python
+#   pytest tests/test_python.py
+
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import sacio
+
+FILE = str(Path(__file__).parent / "file.sac")
+
+
+def test_header_round_trip():
+    s = sacio.read(FILE)
+    h = s.header
+    assert h["kstnm"] == "CDV"
+    assert h["npts"] == 1000
+    assert h["leven"] is True
+    assert h["iftype"] == "ITIME"
+
+    t = sacio.Sac(s.y.copy(), h)
+    th = t.header
+    for key, value in h.items():
+        if key not in ("depmin", "depmax", "depmen"):
+            assert th[key] == value, key
+
+    t2 = sacio.Sac.read(t.write())
+    assert t2.header == th
+    assert np.array_equal(t2.y, s.y)
+
+
+def test_undefined_is_none():
+    s = sacio.read(FILE)
+    assert s["evel"] is None
+    assert "evel" not in s.header
+    assert s["kcmpnm"] is None
+    assert s["stla"] == 48.0
+    s["stla"] = None
+    assert s["stla"] is None
+    assert s["kevnm"] == "K8108838"
+    s["kevnm"] = None
+    assert s["kevnm"] is None
+
+    t = sacio.Sac.read(s.write())
+    assert t["stla"] is None
+    assert t["kevnm"] is None
+    with pytest.raises(KeyError):
+        s["stlx"]
+
+
+def test_y_is_shared():
+    y = np.zeros(10, dtype=np.float32)
+    s = sacio.Sac(y, {"delta": 0.5})
+    y[3] = 5.0
+    assert s.y[3] == 5.0
+
+    s.y *= 2.0
+    s.y[0] = 1.0
+    assert y[3] == 10.0
+    t = sacio.Sac.read(s.write())
+    assert t.y[0] == 1.0
+    assert t.y[3] == 10.0
+    assert t["depmax"] == 10.0
+
+
+def test_length_mismatch():
+    with pytest.raises(ValueError):
+        sacio.Sac(np.zeros(10, dtype=np.float32), {"npts": 5})
+    with pytest.raises(ValueError):
+        sacio.Sac(np.zeros(10), x=np.zeros(9))
+
+    s = sacio.Sac(np.zeros(3), x=np.arange(3.0))
+    assert s["leven"] is False
+    s.x = np.zeros(2)
+    with pytest.raises(ValueError):
+        s.write()
@@ -0,0 +1,43 @@
+//! Run with `wasm-pack test --node -- --features wasm` or
+//! `wasm-pack test --headless --firefox -- --features wasm`
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use sacio::wasm::WasmSac;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+const FILE: &[u8] = include_bytes!("file.sac");
+
+#[wasm_bindgen_test]
+fn read_bytes() {
+    let s = WasmSac::new(FILE).unwrap();
+    assert_eq!(s.npts(), 1000);
+    assert_eq!(s.delta(), 0.01);
+    assert_eq!(s.nslc(), ".CDV..");
+    assert!(WasmSac::new(&FILE[..100]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn header() {
+    let s = WasmSac::new(FILE).unwrap();
+    assert_eq!(s.get("kstnm"), Some(JsValue::from("CDV")));
+    assert_eq!(s.get("IFTYPE"), Some(JsValue::from("ITIME")));
+    assert_eq!(s.get("leven"), Some(JsValue::from(true)));
+    assert_eq!(s.get("evel"), None);
+    let h = s.header().unwrap();
+    let npts = js_sys::Reflect::get(&h, &JsValue::from("npts")).unwrap();
+    assert_eq!(npts, JsValue::from(1000));
+}
+
+#[wasm_bindgen_test]
+fn envelope_and_location() {
+    let s = WasmSac::new(FILE).unwrap();
+    let env = s.envelope(100);
+    assert_eq!(env.len(), 200);
+    assert_eq!(s.envelope_bin_size(100), 10);
+    let y = s.y();
+    assert_eq!(env[0], y[..10].iter().cloned().fold(f32::MAX, f32::min));
+    assert_eq!(env[1], y[..10].iter().cloned().fold(f32::MIN, f32::max));
+    assert!(s.gcarc().is_some());
+    assert!(s.event_region().is_some());
+}